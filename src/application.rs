@@ -0,0 +1,390 @@
+use std::sync::mpsc::Receiver;
+
+use glcall_macro::gl_call;
+use imgui_glfw_rs::glfw::{
+    self, Action, Context, Glfw, Key, OpenGlProfileHint, SwapInterval, Window, WindowEvent,
+    WindowHint, WindowMode,
+};
+use imgui_glfw_rs::imgui::{self, Context as ImContext, Ui};
+use imgui_glfw_rs::ImguiGLFW;
+
+use crate::{
+    cli::AppArgs, debug_draw::DebugDraw, frame_timer::FrameTimer, input::Input, renderer::Renderer,
+    str_to_imstr,
+};
+
+#[derive(Debug, Clone)]
+pub struct WindowConfig {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub vsync: bool,
+    pub gl_version: (u32, u32),
+    pub msaa: Option<u32>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: "LearnOpenGL".into(),
+            width: 1280,
+            height: 960,
+            vsync: true,
+            gl_version: (4, 5),
+            msaa: None,
+        }
+    }
+}
+
+impl From<&AppArgs> for WindowConfig {
+    fn from(args: &AppArgs) -> Self {
+        Self {
+            width: args.width,
+            height: args.height,
+            vsync: args.vsync,
+            gl_version: args.gl_version,
+            msaa: args.msaa,
+            ..Default::default()
+        }
+    }
+}
+
+///
+/// A window event travelling down the layer stack
+///
+/// Layers receive events from the top of the stack (the last overlay pushed) downwards,
+/// marking an event as handled stops it from reaching the layers below.
+///
+pub struct Event {
+    window_event: WindowEvent,
+    handled: bool,
+}
+
+impl Event {
+    pub fn new(window_event: WindowEvent) -> Self {
+        Self {
+            window_event,
+            handled: false,
+        }
+    }
+
+    pub fn window_event(&self) -> &WindowEvent {
+        &self.window_event
+    }
+
+    pub fn is_handled(&self) -> bool {
+        self.handled
+    }
+
+    pub fn set_handled(&mut self) {
+        self.handled = true;
+    }
+
+    /// Runs `handler` if the event has not been handled yet, a `true` result marks it as handled
+    pub fn dispatch(&mut self, handler: impl FnOnce(&WindowEvent) -> bool) {
+        if !self.handled && handler(&self.window_event) {
+            self.handled = true;
+        }
+    }
+}
+
+pub trait Layer {
+    fn name(&self) -> String {
+        "Layer".into()
+    }
+
+    fn on_attach(&mut self) {}
+    fn on_detach(&mut self) {}
+    fn on_update(&mut self, _delta_time: f32, _input: &Input) {}
+    fn on_render(&mut self, _screen_size: (f32, f32), _renderer: &Renderer) {}
+    fn on_event(&mut self, _event: &mut Event) {}
+    fn on_imgui(&mut self, _screen_size: (f32, f32), _ui: &Ui) {}
+}
+
+///
+/// Ordered collection of layers, overlays always sit above regular layers
+///
+#[derive(Default)]
+pub struct LayerStack {
+    layers: Vec<Box<dyn Layer>>,
+    overlay_start: usize,
+}
+
+impl LayerStack {
+    pub fn push_layer(&mut self, mut layer: Box<dyn Layer>) {
+        layer.on_attach();
+        self.layers.insert(self.overlay_start, layer);
+        self.overlay_start += 1;
+    }
+
+    pub fn push_overlay(&mut self, mut overlay: Box<dyn Layer>) {
+        overlay.on_attach();
+        self.layers.push(overlay);
+    }
+
+    pub fn pop_layer(&mut self) -> Option<Box<dyn Layer>> {
+        if self.overlay_start == 0 {
+            return None;
+        }
+        self.overlay_start -= 1;
+        let mut layer = self.layers.remove(self.overlay_start);
+        layer.on_detach();
+        Some(layer)
+    }
+
+    pub fn pop_overlay(&mut self) -> Option<Box<dyn Layer>> {
+        if self.layers.len() == self.overlay_start {
+            return None;
+        }
+        let mut overlay = self.layers.pop()?;
+        overlay.on_detach();
+        Some(overlay)
+    }
+
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Bottom to top, the order layers are updated and rendered in
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Layer>> {
+        self.layers.iter_mut()
+    }
+
+    pub fn dispatch(&mut self, event: &mut Event) {
+        for layer in self.layers.iter_mut().rev() {
+            if event.is_handled() {
+                break;
+            }
+            layer.on_event(event);
+        }
+    }
+}
+
+impl Drop for LayerStack {
+    fn drop(&mut self) {
+        for layer in self.layers.iter_mut().rev() {
+            layer.on_detach();
+        }
+    }
+}
+
+///
+/// Owns the window, OpenGL context, imgui and the main loop
+///
+/// # Usage
+/// ```ignore
+/// let mut app = Application::new(WindowConfig::default());
+/// app.push_layer(TestMenu::default());
+/// app.run();
+/// ```
+///
+pub struct Application {
+    // Fields drop in declaration order, layers own GL objects and must go before the context
+    layers: LayerStack,
+    renderer: Renderer,
+    frame_timer: FrameTimer,
+    input: Input,
+
+    imgui_glfw: ImguiGLFW,
+    imgui: ImContext,
+
+    events: Receiver<(f64, WindowEvent)>,
+    window: Window,
+    glfw: Glfw,
+
+    screen_size: (u32, u32),
+    frame_limit: Option<u64>,
+    frame_dump: Option<(String, u64)>,
+    show_frame_timer: bool,
+}
+
+impl Application {
+    pub fn new(config: WindowConfig) -> Self {
+        let (major, minor) = config.gl_version;
+        let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
+        glfw.window_hint(WindowHint::ContextVersion(major, minor));
+        glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+        glfw.window_hint(WindowHint::Samples(config.msaa));
+
+        #[cfg(target_os = "macos")]
+        glfw.window_hint(WindowHint::OpenGlForwardCompat(true));
+
+        let (mut window, events) = glfw
+            .create_window(
+                config.width,
+                config.height,
+                &config.title,
+                WindowMode::Windowed,
+            )
+            .expect("Failed to create GLFW window");
+
+        window.make_current();
+        window.set_all_polling(true);
+        window.set_framebuffer_size_polling(true);
+
+        glfw.set_swap_interval(if config.vsync {
+            SwapInterval::Sync(1)
+        } else {
+            SwapInterval::None
+        });
+
+        gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
+
+        let mut imgui = ImContext::create();
+        let imgui_glfw = ImguiGLFW::new(&mut imgui, &mut window);
+
+        let renderer = Renderer::new((0.0, 0.0, 0.0, 1.0));
+        renderer.reset_state();
+
+        if config.msaa.is_some() {
+            gl_call!({
+                gl::Enable(gl::MULTISAMPLE);
+            });
+        }
+
+        Self {
+            layers: LayerStack::default(),
+            renderer,
+            frame_timer: FrameTimer::default(),
+            input: Input::default(),
+            imgui_glfw,
+            imgui,
+            events,
+            window,
+            glfw,
+            screen_size: (config.width, config.height),
+            frame_limit: None,
+            frame_dump: None,
+            show_frame_timer: true,
+        }
+    }
+
+    pub fn push_layer(&mut self, layer: impl Layer + 'static) {
+        self.layers.push_layer(Box::new(layer));
+    }
+
+    pub fn push_overlay(&mut self, overlay: impl Layer + 'static) {
+        self.layers.push_overlay(Box::new(overlay));
+    }
+
+    pub fn layers(&mut self) -> &mut LayerStack {
+        &mut self.layers
+    }
+
+    pub fn renderer(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    pub fn frame_timer(&mut self) -> &mut FrameTimer {
+        &mut self.frame_timer
+    }
+
+    /// Closes the window after `frames` frames have been rendered
+    pub fn set_frame_limit(&mut self, frames: Option<u64>) {
+        self.frame_limit = frames;
+    }
+
+    /// Saves the contents of the given (1-based) frame to `path`
+    pub fn set_frame_dump(&mut self, path: impl Into<String>, frame: u64) {
+        self.frame_dump = Some((path.into(), frame));
+    }
+
+    pub fn set_show_frame_timer(&mut self, show: bool) {
+        self.show_frame_timer = show;
+    }
+
+    pub fn run(mut self) {
+        while !self.window.should_close() {
+            let tick = self.frame_timer.tick(self.glfw.get_time());
+            let frame = self.frame_timer.frame_count();
+            let screen_size = (self.screen_size.0 as f32, self.screen_size.1 as f32);
+
+            self.renderer.reset_state();
+            self.renderer.clear();
+
+            let ui = self.imgui_glfw.frame(&mut self.window, &mut self.imgui);
+            self.input
+                .set_captured(ui.io().want_capture_keyboard, ui.io().want_capture_mouse);
+
+            for layer in self.layers.iter_mut() {
+                layer.on_imgui(screen_size, &ui);
+            }
+            if self.show_frame_timer {
+                let frame_timer = &mut self.frame_timer;
+                ui.window(&str_to_imstr("Frame Timer"))
+                    .size([300.0, 150.0], imgui::Condition::FirstUseEver)
+                    .position([10.0, 120.0], imgui::Condition::FirstUseEver)
+                    .build(|| {
+                        frame_timer.imgui_render(&ui);
+                    });
+            }
+
+            for step in 0..tick.steps {
+                DebugDraw::with(|debug| debug.begin_update(tick.step_size));
+                for layer in self.layers.iter_mut() {
+                    layer.on_update(tick.step_size, &self.input);
+                }
+                // Presses and cursor movement are kept until an update has seen them, and
+                // only the first of several steps sees them
+                if step == 0 {
+                    self.input.end_frame();
+                }
+            }
+
+            for layer in self.layers.iter_mut() {
+                layer.on_render(screen_size, &self.renderer);
+            }
+            self.imgui_glfw.draw(ui, &mut self.window);
+
+            if let Some((path, dump_frame)) = &self.frame_dump {
+                if frame == *dump_frame {
+                    let (width, height) = self.screen_size;
+                    let image = self.renderer.read_pixels(width, height);
+                    match image.save(path) {
+                        Ok(_) => println!("Saved frame {} to {}", frame, path),
+                        Err(e) => eprintln!("Failed to save frame {} to {}: {}", frame, path, e),
+                    }
+                }
+            }
+
+            if self.frame_limit.is_some_and(|frames| frame >= frames) {
+                self.window.set_should_close(true);
+            }
+
+            self.window.swap_buffers();
+            self.glfw.poll_events();
+            self.process_events();
+            self.input.poll_gamepads(&self.glfw);
+        }
+    }
+
+    fn process_events(&mut self) {
+        for (_, window_event) in glfw::flush_messages(&self.events) {
+            self.imgui_glfw.handle_event(&mut self.imgui, &window_event);
+            self.input.handle_event(&window_event);
+
+            if let WindowEvent::FramebufferSize(width, height) = window_event {
+                self.screen_size = (width as u32, height as u32);
+                gl_call!({
+                    gl::Viewport(0, 0, width, height);
+                });
+            }
+
+            let mut event = Event::new(window_event);
+            self.layers.dispatch(&mut event);
+
+            let window = &mut self.window;
+            event.dispatch(|window_event| match window_event {
+                WindowEvent::Key(Key::Escape, _, Action::Release, _) => {
+                    window.set_should_close(true);
+                    true
+                }
+                _ => false,
+            });
+        }
+    }
+}
@@ -0,0 +1,561 @@
+use std::{collections::BTreeMap, fmt::Display, rc::Rc};
+
+use image::{DynamicImage, ImageError, RgbaImage};
+
+use crate::texture::{Texture, TextureError, TextureOptions};
+
+#[derive(Debug)]
+pub enum AtlasError {
+    Io(std::io::Error),
+    Image(ImageError),
+    Texture(TextureError),
+    Json(serde_json::Error),
+    /// An image larger than a whole page, including padding and extrusion
+    DoesNotFit {
+        name: String,
+        width: usize,
+        height: usize,
+    },
+    /// Sprite sheet metadata missing a field or with a field of the wrong type
+    InvalidMetadata(String),
+}
+
+impl Display for AtlasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "IO error: {}", e),
+            Self::Image(e) => write!(f, "Failed to load image: {}", e),
+            Self::Texture(e) => write!(f, "{}", e),
+            Self::Json(e) => write!(f, "Failed to parse sprite sheet: {}", e),
+            Self::DoesNotFit {
+                name,
+                width,
+                height,
+            } => write!(
+                f,
+                "Image `{}` ({}x{}) does not fit on an atlas page",
+                name, width, height
+            ),
+            Self::InvalidMetadata(e) => write!(f, "Invalid sprite sheet metadata: {}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for AtlasError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<ImageError> for AtlasError {
+    fn from(value: ImageError) -> Self {
+        Self::Image(value)
+    }
+}
+
+impl From<TextureError> for AtlasError {
+    fn from(value: TextureError) -> Self {
+        Self::Texture(value)
+    }
+}
+
+impl From<serde_json::Error> for AtlasError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+///
+/// A rectangle of an atlas page or sprite sheet, in pixels with the origin at the top left
+/// of the image, and the texture coordinates of its corners
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion {
+    pub page: usize,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub uv_top_left: [f32; 2],
+    pub uv_bottom_right: [f32; 2],
+    /// The image is stored rotated 90° clockwise, as sprite packers do to save space.
+    /// `width` and `height` are those of the stored, rotated rectangle
+    pub rotated: bool,
+}
+
+impl AtlasRegion {
+    ///
+    /// A region of a page of `page_size` pixels
+    ///
+    /// `flipped` tells whether the page was uploaded flipped vertically, as
+    /// `TextureOptions::default()` does, which puts the top row at v = 1.
+    ///
+    pub fn new(
+        page: usize,
+        (x, y, width, height): (usize, usize, usize, usize),
+        (page_width, page_height): (usize, usize),
+        flipped: bool,
+    ) -> Self {
+        let u = |x: usize| x as f32 / page_width as f32;
+        let v = |y: usize| {
+            let v = y as f32 / page_height as f32;
+            if flipped {
+                1.0 - v
+            } else {
+                v
+            }
+        };
+        Self {
+            page,
+            x,
+            y,
+            width,
+            height,
+            uv_top_left: [u(x), v(y)],
+            uv_bottom_right: [u(x + width), v(y + height)],
+            rotated: false,
+        }
+    }
+
+    /// Size of the image as drawn, undoing the rotation
+    pub fn size(&self) -> (usize, usize) {
+        if self.rotated {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        }
+    }
+
+    ///
+    /// Texture coordinates for the bottom left, bottom right, top right and top left corners
+    /// of a quad showing the image upright
+    ///
+    pub fn uvs(&self) -> [[f32; 2]; 4] {
+        let [left, top] = self.uv_top_left;
+        let [right, bottom] = self.uv_bottom_right;
+        if self.rotated {
+            // Clockwise rotation moves the image's top left corner to the region's top right
+            [[left, top], [left, bottom], [right, bottom], [right, top]]
+        } else {
+            [[left, bottom], [right, bottom], [right, top], [left, top]]
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SkylineSegment {
+    x: usize,
+    y: usize,
+    width: usize,
+}
+
+///
+/// Packs rectangles into a fixed area by keeping track of the top contour of what has
+/// been placed, pure CPU work
+///
+/// Each rectangle goes where it rests lowest (closest to y = 0) on the contour, ties going
+/// to the narrowest spot. Sorting rectangles by height first packs noticeably tighter.
+///
+#[derive(Debug, Clone)]
+pub struct SkylinePacker {
+    width: usize,
+    height: usize,
+    skyline: Vec<SkylineSegment>,
+}
+
+impl SkylinePacker {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            skyline: vec![SkylineSegment { x: 0, y: 0, width }],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Finds a spot for a `width` x `height` rectangle and reserves it, `None` if it is full
+    pub fn pack(&mut self, width: usize, height: usize) -> Option<(usize, usize)> {
+        if width == 0 || height == 0 {
+            return Some((0, 0));
+        }
+
+        let (index, y) = (0..self.skyline.len())
+            .filter_map(|index| self.fit(index, width, height).map(|y| (index, y)))
+            .min_by_key(|&(index, y)| (y, self.skyline[index].width))?;
+        let x = self.skyline[index].x;
+        self.insert(index, x, y + height, width);
+        Some((x, y))
+    }
+
+    /// The y a rectangle would rest at when its left edge is on segment `index`
+    fn fit(&self, index: usize, width: usize, height: usize) -> Option<usize> {
+        let x = self.skyline[index].x;
+        if x + width > self.width {
+            return None;
+        }
+
+        let mut y = 0;
+        let mut remaining = width as isize;
+        for segment in &self.skyline[index..] {
+            if remaining <= 0 {
+                break;
+            }
+            y = y.max(segment.y);
+            remaining -= segment.width as isize;
+        }
+        (y + height <= self.height).then_some(y)
+    }
+
+    fn insert(&mut self, index: usize, x: usize, y: usize, width: usize) {
+        self.skyline.insert(index, SkylineSegment { x, y, width });
+
+        // Cut away what the new segment now covers
+        let right = x + width;
+        let next = index + 1;
+        while next < self.skyline.len() && self.skyline[next].x < right {
+            let segment = &mut self.skyline[next];
+            let segment_right = segment.x + segment.width;
+            if segment_right <= right {
+                self.skyline.remove(next);
+            } else {
+                segment.width = segment_right - right;
+                segment.x = right;
+                break;
+            }
+        }
+
+        self.skyline.dedup_by(|next, previous| {
+            let merge = previous.y == next.y;
+            if merge {
+                previous.width += next.width;
+            }
+            merge
+        });
+    }
+}
+
+///
+/// Collects named images and packs them into as many atlas pages as needed
+///
+/// `padding` leaves transparent pixels between images, `extrusion` repeats each image's
+/// border pixels outwards so linear filtering and mipmaps don't pick up their neighbours.
+///
+pub struct TextureAtlasBuilder {
+    page_width: usize,
+    page_height: usize,
+    padding: usize,
+    extrusion: usize,
+    options: TextureOptions,
+    images: Vec<(String, RgbaImage)>,
+}
+
+impl TextureAtlasBuilder {
+    pub fn new(page_width: usize, page_height: usize) -> Self {
+        Self {
+            page_width,
+            page_height,
+            padding: 1,
+            extrusion: 0,
+            options: TextureOptions::default(),
+            images: Vec::new(),
+        }
+    }
+
+    pub fn with_padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_extrusion(mut self, extrusion: usize) -> Self {
+        self.extrusion = extrusion;
+        self
+    }
+
+    /// Options used to upload the pages, the format is always 8-bit RGBA
+    pub fn with_options(mut self, options: TextureOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Adds an image, replacing one with the same name
+    pub fn with_image(mut self, name: impl Into<String>, image: impl Into<DynamicImage>) -> Self {
+        let name = name.into();
+        let image = image.into().to_rgba8();
+        self.images.retain(|(existing, _)| *existing != name);
+        self.images.push((name, image));
+        self
+    }
+
+    pub fn with_file(
+        self,
+        name: impl Into<String>,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, AtlasError> {
+        Ok(self.with_image(name, image::open(path)?))
+    }
+
+    ///
+    /// Packs every image and composes the pages, without touching OpenGL
+    ///
+    /// Returns the page images and the regions by name, UVs assuming the pages get
+    /// uploaded with the builder's options.
+    ///
+    pub fn pack(&self) -> Result<(Vec<RgbaImage>, BTreeMap<String, AtlasRegion>), AtlasError> {
+        let border = self.extrusion * 2 + self.padding;
+
+        // Tallest first, then widest, packs the skyline tightest
+        let mut order: Vec<_> = (0..self.images.len()).collect();
+        order.sort_by_key(|&i| {
+            let (_, image) = &self.images[i];
+            std::cmp::Reverse((image.height(), image.width()))
+        });
+
+        let mut packers: Vec<SkylinePacker> = Vec::new();
+        let mut placements = vec![(0, 0, 0); self.images.len()];
+        for index in order {
+            let (name, image) = &self.images[index];
+            let (width, height) = (
+                image.width() as usize + border,
+                image.height() as usize + border,
+            );
+
+            let placement = packers
+                .iter_mut()
+                .enumerate()
+                .find_map(|(page, packer)| packer.pack(width, height).map(|(x, y)| (page, x, y)));
+            placements[index] = match placement {
+                Some(placement) => placement,
+                None => {
+                    let mut packer = SkylinePacker::new(self.page_width, self.page_height);
+                    let (x, y) =
+                        packer
+                            .pack(width, height)
+                            .ok_or_else(|| AtlasError::DoesNotFit {
+                                name: name.clone(),
+                                width: image.width() as usize,
+                                height: image.height() as usize,
+                            })?;
+                    packers.push(packer);
+                    (packers.len() - 1, x, y)
+                }
+            };
+        }
+
+        let mut pages: Vec<_> = packers
+            .iter()
+            .map(|_| RgbaImage::new(self.page_width as u32, self.page_height as u32))
+            .collect();
+        let mut regions = BTreeMap::new();
+        for ((name, image), (page, x, y)) in self.images.iter().zip(placements) {
+            let (x, y) = (x + self.extrusion, y + self.extrusion);
+            self.blit(&mut pages[page], image, x, y);
+
+            let rect = (x, y, image.width() as usize, image.height() as usize);
+            let page_size = (self.page_width, self.page_height);
+            let region = AtlasRegion::new(page, rect, page_size, self.options.flip_vertically);
+            regions.insert(name.clone(), region);
+        }
+        Ok((pages, regions))
+    }
+
+    /// Copies `image` to (`x`, `y`), clamping reads so the extrusion repeats the border
+    fn blit(&self, page: &mut RgbaImage, image: &RgbaImage, x: usize, y: usize) {
+        let extrusion = self.extrusion as i64;
+        let (width, height) = (image.width() as i64, image.height() as i64);
+        for dy in -extrusion..height + extrusion {
+            for dx in -extrusion..width + extrusion {
+                let source = (
+                    dx.clamp(0, width - 1) as u32,
+                    dy.clamp(0, height - 1) as u32,
+                );
+                let target = ((x as i64 + dx) as u32, (y as i64 + dy) as u32);
+                page.put_pixel(target.0, target.1, *image.get_pixel(source.0, source.1));
+            }
+        }
+    }
+
+    /// Packs the images and uploads one texture per page
+    pub fn build(self) -> Result<TextureAtlas, AtlasError> {
+        let (pages, regions) = self.pack()?;
+        let pages = pages
+            .into_iter()
+            .map(|page| {
+                Texture::from_image(DynamicImage::ImageRgba8(page), &self.options).map(Rc::new)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(TextureAtlas { pages, regions })
+    }
+}
+
+///
+/// Packed images looked up by name, see `TextureAtlasBuilder`
+///
+/// Sprites from the same page can be drawn in one batch with a single texture bound.
+///
+pub struct TextureAtlas {
+    pages: Vec<Rc<Texture>>,
+    regions: BTreeMap<String, AtlasRegion>,
+}
+
+impl TextureAtlas {
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.regions.get(name)
+    }
+
+    pub fn regions(&self) -> impl Iterator<Item = (&String, &AtlasRegion)> {
+        self.regions.iter()
+    }
+
+    pub fn page(&self, index: usize) -> Option<&Rc<Texture>> {
+        self.pages.get(index)
+    }
+
+    pub fn pages(&self) -> &[Rc<Texture>] {
+        &self.pages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    type Rect = (usize, usize, usize, usize);
+
+    fn overlaps(a: Rect, b: Rect) -> bool {
+        a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+    }
+
+    fn image(width: u32, height: u32, value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba([value, value, value, 255]))
+    }
+
+    #[test]
+    fn packed_rects_do_not_overlap() {
+        let mut packer = SkylinePacker::new(128, 128);
+        let mut rects: Vec<Rect> = Vec::new();
+        // Deterministic mix of sizes, enough to fill the packer
+        for i in 0..200 {
+            let (width, height) = (4 + i * 7 % 23, 4 + i * 11 % 19);
+            if let Some((x, y)) = packer.pack(width, height) {
+                rects.push((x, y, width, height));
+            }
+        }
+
+        assert!(rects.len() > 20);
+        for (i, &a) in rects.iter().enumerate() {
+            assert!(a.0 + a.2 <= 128 && a.1 + a.3 <= 128, "{:?} is outside", a);
+            for &b in &rects[i + 1..] {
+                assert!(!overlaps(a, b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn packer_fills_rows_from_the_bottom() {
+        let mut packer = SkylinePacker::new(64, 64);
+        assert_eq!(packer.pack(32, 16), Some((0, 0)));
+        assert_eq!(packer.pack(32, 8), Some((32, 0)));
+        // Rests on the lower of the two
+        assert_eq!(packer.pack(32, 8), Some((32, 8)));
+        assert_eq!(packer.pack(64, 48), Some((0, 16)));
+        assert_eq!(packer.pack(1, 1), None);
+        assert_eq!(packer.pack(65, 1), None);
+    }
+
+    #[test]
+    fn padding_and_extrusion() {
+        let mut border = image(4, 4, 10);
+        border.put_pixel(0, 0, Rgba([200, 0, 0, 255]));
+        let (pages, regions) = TextureAtlasBuilder::new(64, 64)
+            .with_padding(2)
+            .with_extrusion(1)
+            .with_image("a", border)
+            .with_image("b", image(4, 4, 20))
+            .pack()
+            .unwrap();
+
+        let (a, b) = (regions["a"], regions["b"]);
+        assert_eq!((a.width, a.height), (4, 4));
+        // Each image is surrounded by its extrusion, padding separates the two
+        let expand = |r: &AtlasRegion| (r.x - 1, r.y - 1, r.width + 2 + 2, r.height + 2 + 2);
+        assert!(!overlaps(expand(&a), expand(&b)));
+
+        let page = &pages[0];
+        let pixel = |x: usize, y: usize| page.get_pixel(x as u32, y as u32).0;
+        assert_eq!(pixel(a.x, a.y), [200, 0, 0, 255]);
+        assert_eq!(pixel(a.x - 1, a.y - 1), [200, 0, 0, 255]);
+        assert_eq!(pixel(a.x + 4, a.y + 3), [10, 10, 10, 255]);
+        assert_eq!(pixel(b.x - 1, b.y), [20, 20, 20, 255]);
+        // Past the extrusion the padding stays transparent
+        assert_eq!(pixel(a.x + 5, a.y)[3], 0);
+    }
+
+    #[test]
+    fn overflow_starts_a_new_page() {
+        let (pages, regions) = TextureAtlasBuilder::new(32, 32)
+            .with_padding(0)
+            .with_image("a", image(32, 20, 1))
+            .with_image("b", image(32, 20, 2))
+            .with_image("c", image(16, 12, 3))
+            .pack()
+            .unwrap();
+
+        assert_eq!(pages.len(), 2);
+        assert_ne!(regions["a"].page, regions["b"].page);
+        // Fills the space left on the first page
+        assert_eq!(regions["c"].page, 0);
+        assert_eq!(pages[1].get_pixel(0, 0).0[0], 2);
+    }
+
+    #[test]
+    fn image_larger_than_a_page() {
+        let result = TextureAtlasBuilder::new(32, 32)
+            .with_image("big", image(32, 32, 1))
+            .pack();
+        assert!(matches!(
+            result,
+            Err(AtlasError::DoesNotFit { name, width: 32, height: 32 }) if name == "big"
+        ));
+    }
+
+    #[test]
+    fn regions_by_name() {
+        let (_, regions) = TextureAtlasBuilder::new(64, 32)
+            .with_padding(0)
+            .with_image("wide", image(32, 16, 1))
+            .with_image("small", image(8, 8, 2))
+            .with_image("small", image(16, 16, 3))
+            .pack()
+            .unwrap();
+
+        assert_eq!(regions.len(), 2);
+        let wide = regions["wide"];
+        assert_eq!((wide.x, wide.y), (0, 0));
+        // Pages are flipped by default, the top row is at v = 1
+        assert_eq!(wide.uv_top_left, [0.0, 1.0]);
+        assert_eq!(wide.uv_bottom_right, [0.5, 0.5]);
+        assert_eq!(wide.uvs(), [[0.0, 0.5], [0.5, 0.5], [0.5, 1.0], [0.0, 1.0]]);
+        // The later image replaced the first one
+        assert_eq!(regions["small"].size(), (16, 16));
+    }
+
+    #[test]
+    fn rotated_region() {
+        let mut region = AtlasRegion::new(0, (0, 0, 4, 8), (8, 8), false);
+        region.rotated = true;
+        assert_eq!(region.size(), (8, 4));
+        assert_eq!(
+            region.uvs(),
+            [[0.0, 0.0], [0.0, 1.0], [0.5, 1.0], [0.5, 0.0]]
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use nalgebra_glm as glm;
+
+pub trait Camera {
+    fn view(&self) -> &glm::Mat4;
+    fn projection(&self) -> &glm::Mat4;
+    fn view_projection(&self) -> &glm::Mat4;
+    fn position(&self) -> glm::Vec3;
+}
+
+///
+/// 2D camera with an orthographic projection
+///
+/// `OrthographicCamera::from_screen` matches the `glm::ortho(0.0, width, 0.0, height, -1.0, 1.0)`
+/// projection the tests use, one unit per pixel with the origin in the bottom left.
+///
+pub struct OrthographicCamera {
+    bounds: (f32, f32, f32, f32),
+    depth: (f32, f32),
+
+    position: glm::Vec3,
+    rotation: f32,
+
+    view: glm::Mat4,
+    projection: glm::Mat4,
+    view_projection: glm::Mat4,
+}
+
+impl OrthographicCamera {
+    pub fn new(left: f32, right: f32, bottom: f32, top: f32) -> Self {
+        let mut instance = Self {
+            bounds: (left, right, bottom, top),
+            depth: (-1.0, 1.0),
+            position: glm::vec3(0.0, 0.0, 0.0),
+            rotation: 0.0,
+            view: glm::Mat4::identity(),
+            projection: glm::Mat4::identity(),
+            view_projection: glm::Mat4::identity(),
+        };
+        instance.recalculate_projection();
+        instance
+    }
+
+    pub fn from_screen((width, height): (f32, f32)) -> Self {
+        Self::new(0.0, width, 0.0, height)
+    }
+
+    pub fn set_projection(&mut self, left: f32, right: f32, bottom: f32, top: f32) {
+        self.bounds = (left, right, bottom, top);
+        self.recalculate_projection();
+    }
+
+    pub fn set_depth_range(&mut self, near: f32, far: f32) {
+        self.depth = (near, far);
+        self.recalculate_projection();
+    }
+
+    /// Resets the projection to screen space for the new framebuffer size
+    pub fn resize(&mut self, (width, height): (f32, f32)) {
+        self.set_projection(0.0, width, 0.0, height);
+    }
+
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        self.bounds
+    }
+
+    pub fn set_position(&mut self, position: glm::Vec3) {
+        self.position = position;
+        self.recalculate_view();
+    }
+
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    /// Rotation around the z axis, in degrees
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+        self.recalculate_view();
+    }
+
+    fn recalculate_projection(&mut self) {
+        let (left, right, bottom, top) = self.bounds;
+        let (near, far) = self.depth;
+        self.projection = glm::ortho(left, right, bottom, top, near, far);
+        self.view_projection = self.projection * self.view;
+    }
+
+    fn recalculate_view(&mut self) {
+        let transform = glm::translate(&glm::Mat4::identity(), &self.position);
+        let transform = glm::rotate(
+            &transform,
+            self.rotation.to_radians(),
+            &glm::vec3(0.0, 0.0, 1.0),
+        );
+        self.view = glm::inverse(&transform);
+        self.view_projection = self.projection * self.view;
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn view(&self) -> &glm::Mat4 {
+        &self.view
+    }
+
+    fn projection(&self) -> &glm::Mat4 {
+        &self.projection
+    }
+
+    fn view_projection(&self) -> &glm::Mat4 {
+        &self.view_projection
+    }
+
+    fn position(&self) -> glm::Vec3 {
+        self.position
+    }
+}
+
+///
+/// 3D camera with a perspective projection, oriented by yaw and pitch (in degrees)
+///
+/// A yaw of `-90.0` and pitch of `0.0` looks down the negative z axis.
+///
+pub struct PerspectiveCamera {
+    fov: f32,
+    aspect: f32,
+    near: f32,
+    far: f32,
+
+    position: glm::Vec3,
+    yaw: f32,
+    pitch: f32,
+
+    view: glm::Mat4,
+    projection: glm::Mat4,
+    view_projection: glm::Mat4,
+}
+
+impl PerspectiveCamera {
+    pub const UP: glm::Vec3 = glm::Vec3::new(0.0, 1.0, 0.0);
+
+    pub fn new(fov: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let mut instance = Self {
+            fov,
+            aspect,
+            near,
+            far,
+            position: glm::vec3(0.0, 0.0, 3.0),
+            yaw: -90.0,
+            pitch: 0.0,
+            view: glm::Mat4::identity(),
+            projection: glm::Mat4::identity(),
+            view_projection: glm::Mat4::identity(),
+        };
+        instance.recalculate_projection();
+        instance.recalculate_view();
+        instance
+    }
+
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    /// Vertical field of view, in degrees
+    pub fn set_fov(&mut self, fov: f32) {
+        self.fov = fov.clamp(1.0, 179.0);
+        self.recalculate_projection();
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    pub fn clip_planes(&self) -> (f32, f32) {
+        (self.near, self.far)
+    }
+
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        self.near = near;
+        self.far = far;
+        self.recalculate_projection();
+    }
+
+    pub fn resize(&mut self, (width, height): (f32, f32)) {
+        if width > 0.0 && height > 0.0 {
+            self.aspect = width / height;
+            self.recalculate_projection();
+        }
+    }
+
+    pub fn set_position(&mut self, position: glm::Vec3) {
+        self.position = position;
+        self.recalculate_view();
+    }
+
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    pub fn set_rotation(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-89.0, 89.0);
+        self.recalculate_view();
+    }
+
+    pub fn look_at(&mut self, target: &glm::Vec3) {
+        let direction = target - self.position;
+        if glm::length(&direction) <= f32::EPSILON {
+            return;
+        }
+        let direction = glm::normalize(&direction);
+        let yaw = direction.z.atan2(direction.x).to_degrees();
+        let pitch = direction.y.clamp(-1.0, 1.0).asin().to_degrees();
+        self.set_rotation(yaw, pitch);
+    }
+
+    pub fn forward(&self) -> glm::Vec3 {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        glm::normalize(&glm::vec3(
+            yaw.cos() * pitch.cos(),
+            pitch.sin(),
+            yaw.sin() * pitch.cos(),
+        ))
+    }
+
+    pub fn right(&self) -> glm::Vec3 {
+        glm::normalize(&glm::cross(&self.forward(), &Self::UP))
+    }
+
+    pub fn up(&self) -> glm::Vec3 {
+        glm::cross(&self.right(), &self.forward())
+    }
+
+    fn recalculate_projection(&mut self) {
+        self.projection = glm::perspective(self.aspect, self.fov.to_radians(), self.near, self.far);
+        self.view_projection = self.projection * self.view;
+    }
+
+    fn recalculate_view(&mut self) {
+        let target = self.position + self.forward();
+        self.view = glm::look_at(&self.position, &target, &Self::UP);
+        self.view_projection = self.projection * self.view;
+    }
+}
+
+impl Camera for PerspectiveCamera {
+    fn view(&self) -> &glm::Mat4 {
+        &self.view
+    }
+
+    fn projection(&self) -> &glm::Mat4 {
+        &self.projection
+    }
+
+    fn view_projection(&self) -> &glm::Mat4 {
+        &self.view_projection
+    }
+
+    fn position(&self) -> glm::Vec3 {
+        self.position
+    }
+}
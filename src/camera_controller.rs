@@ -0,0 +1,304 @@
+use imgui_glfw_rs::glfw::{Key, MouseButton};
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::{Camera, OrthographicCamera, PerspectiveCamera},
+    input::Input,
+};
+
+///
+/// Pans with WASD / the arrow keys or by dragging with the right mouse button, zooms with
+/// the scroll wheel
+///
+pub struct OrthographicCameraController {
+    camera: OrthographicCamera,
+    screen_size: (f32, f32),
+    zoom: f32,
+
+    pub pan_speed: f32,
+    pub zoom_speed: f32,
+}
+
+impl OrthographicCameraController {
+    pub fn new(screen_size: (f32, f32)) -> Self {
+        let mut instance = Self {
+            camera: OrthographicCamera::from_screen(screen_size),
+            screen_size,
+            zoom: 1.0,
+            pan_speed: 500.0,
+            zoom_speed: 0.1,
+        };
+        instance.reset();
+        instance
+    }
+
+    /// Centers the camera so the view matches a plain screen space projection
+    pub fn reset(&mut self) {
+        let (width, height) = self.screen_size;
+        self.zoom = 1.0;
+        self.camera
+            .set_position(glm::vec3(width / 2.0, height / 2.0, 0.0));
+        self.recalculate_projection();
+    }
+
+    pub fn camera(&self) -> &OrthographicCamera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut OrthographicCamera {
+        &mut self.camera
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(0.05, 20.0);
+        self.recalculate_projection();
+    }
+
+    /// Keeps the bottom left corner of the view in place, so the screen space origin stays put
+    pub fn resize(&mut self, screen_size: (f32, f32)) {
+        if screen_size.0 <= 0.0 || screen_size.1 <= 0.0 {
+            return;
+        }
+
+        let offset = glm::vec3(
+            (screen_size.0 - self.screen_size.0) / 2.0 * self.zoom,
+            (screen_size.1 - self.screen_size.1) / 2.0 * self.zoom,
+            0.0,
+        );
+        let offset = glm::rotate_z_vec3(&offset, self.camera.rotation().to_radians());
+        self.camera.set_position(self.camera.position() + offset);
+
+        self.screen_size = screen_size;
+        self.recalculate_projection();
+    }
+
+    pub fn update(&mut self, delta_time: f32, input: &Input) {
+        let mut position = self.camera.position();
+        let speed = self.pan_speed * self.zoom * delta_time;
+
+        if input.is_key_held(Key::A) || input.is_key_held(Key::Left) {
+            position.x -= speed;
+        }
+        if input.is_key_held(Key::D) || input.is_key_held(Key::Right) {
+            position.x += speed;
+        }
+        if input.is_key_held(Key::S) || input.is_key_held(Key::Down) {
+            position.y -= speed;
+        }
+        if input.is_key_held(Key::W) || input.is_key_held(Key::Up) {
+            position.y += speed;
+        }
+
+        if input.is_mouse_held(MouseButton::Button2) {
+            // Cursor coordinates grow downwards, world coordinates upwards
+            let (dx, dy) = input.cursor_delta();
+            position.x -= dx * self.zoom;
+            position.y += dy * self.zoom;
+        }
+
+        if position != self.camera.position() {
+            self.camera.set_position(position);
+        }
+
+        let (_, scroll) = input.scroll();
+        if scroll != 0.0 {
+            self.set_zoom(self.zoom * (1.0 - scroll * self.zoom_speed));
+        }
+    }
+
+    fn recalculate_projection(&mut self) {
+        let (half_width, half_height) = (
+            self.screen_size.0 / 2.0 * self.zoom,
+            self.screen_size.1 / 2.0 * self.zoom,
+        );
+        self.camera
+            .set_projection(-half_width, half_width, -half_height, half_height);
+    }
+}
+
+///
+/// Free flying camera, WASD to move, Space / Left Shift to rise and fall and the mouse to
+/// look around while the right mouse button is held
+///
+pub struct FlyCameraController {
+    camera: PerspectiveCamera,
+
+    pub move_speed: f32,
+    pub sprint_multiplier: f32,
+    pub look_sensitivity: f32,
+}
+
+impl FlyCameraController {
+    pub fn new(camera: PerspectiveCamera) -> Self {
+        Self {
+            camera,
+            move_speed: 3.0,
+            sprint_multiplier: 3.0,
+            look_sensitivity: 0.15,
+        }
+    }
+
+    pub fn camera(&self) -> &PerspectiveCamera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut PerspectiveCamera {
+        &mut self.camera
+    }
+
+    pub fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera.resize(screen_size);
+    }
+
+    pub fn update(&mut self, delta_time: f32, input: &Input) {
+        if input.is_mouse_held(MouseButton::Button2) {
+            let (dx, dy) = input.cursor_delta();
+            if dx != 0.0 || dy != 0.0 {
+                let yaw = self.camera.yaw() + dx * self.look_sensitivity;
+                let pitch = self.camera.pitch() - dy * self.look_sensitivity;
+                self.camera.set_rotation(yaw, pitch);
+            }
+        }
+
+        let forward = self.camera.forward();
+        let right = self.camera.right();
+        let mut direction = glm::vec3(0.0, 0.0, 0.0);
+
+        if input.is_key_held(Key::W) {
+            direction += forward;
+        }
+        if input.is_key_held(Key::S) {
+            direction -= forward;
+        }
+        if input.is_key_held(Key::D) {
+            direction += right;
+        }
+        if input.is_key_held(Key::A) {
+            direction -= right;
+        }
+        if input.is_key_held(Key::Space) {
+            direction += PerspectiveCamera::UP;
+        }
+        if input.is_key_held(Key::LeftShift) {
+            direction -= PerspectiveCamera::UP;
+        }
+
+        if glm::length(&direction) > 0.0 {
+            let mut speed = self.move_speed;
+            if input.is_key_held(Key::LeftControl) {
+                speed *= self.sprint_multiplier;
+            }
+            let position = self.camera.position() + glm::normalize(&direction) * speed * delta_time;
+            self.camera.set_position(position);
+        }
+    }
+}
+
+///
+/// Orbits a target point, drag with the left mouse button to rotate, the middle mouse
+/// button to pan and scroll to zoom
+///
+pub struct OrbitCameraController {
+    camera: PerspectiveCamera,
+
+    target: glm::Vec3,
+    distance: f32,
+    yaw: f32,
+    pitch: f32,
+
+    pub rotate_sensitivity: f32,
+    pub pan_sensitivity: f32,
+    pub zoom_speed: f32,
+}
+
+impl OrbitCameraController {
+    pub fn new(camera: PerspectiveCamera, target: glm::Vec3, distance: f32) -> Self {
+        let mut instance = Self {
+            camera,
+            target,
+            distance,
+            yaw: 45.0,
+            pitch: 30.0,
+            rotate_sensitivity: 0.3,
+            pan_sensitivity: 0.002,
+            zoom_speed: 0.1,
+        };
+        instance.recalculate();
+        instance
+    }
+
+    pub fn camera(&self) -> &PerspectiveCamera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut PerspectiveCamera {
+        &mut self.camera
+    }
+
+    pub fn target(&self) -> glm::Vec3 {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: glm::Vec3) {
+        self.target = target;
+        self.recalculate();
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.max(0.1);
+        self.recalculate();
+    }
+
+    /// Orbit angles around the target, in degrees
+    pub fn set_angles(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-89.0, 89.0);
+        self.recalculate();
+    }
+
+    pub fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera.resize(screen_size);
+    }
+
+    pub fn update(&mut self, _delta_time: f32, input: &Input) {
+        let (dx, dy) = input.cursor_delta();
+
+        if input.is_mouse_held(MouseButton::Button1) && (dx != 0.0 || dy != 0.0) {
+            self.set_angles(
+                self.yaw + dx * self.rotate_sensitivity,
+                self.pitch + dy * self.rotate_sensitivity,
+            );
+        }
+
+        if input.is_mouse_held(MouseButton::Button3) && (dx != 0.0 || dy != 0.0) {
+            let scale = self.distance * self.pan_sensitivity;
+            let offset = self.camera.right() * -dx * scale + self.camera.up() * dy * scale;
+            self.set_target(self.target + offset);
+        }
+
+        let (_, scroll) = input.scroll();
+        if scroll != 0.0 {
+            self.set_distance(self.distance * (1.0 - scroll * self.zoom_speed));
+        }
+    }
+
+    fn recalculate(&mut self) {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        let offset = glm::vec3(
+            yaw.cos() * pitch.cos(),
+            pitch.sin(),
+            yaw.sin() * pitch.cos(),
+        ) * self.distance;
+
+        self.camera.set_position(self.target + offset);
+        self.camera.look_at(&self.target);
+    }
+}
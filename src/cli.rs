@@ -0,0 +1,241 @@
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub enum ArgsError {
+    UnknownFlag(String),
+    MissingValue(String),
+    InvalidValue { flag: String, value: String },
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownFlag(flag) => write!(f, "Unknown flag `{}`", flag),
+            Self::MissingValue(flag) => write!(f, "Flag `{}` expects a value", flag),
+            Self::InvalidValue { flag, value } => {
+                write!(f, "Invalid value `{}` for flag `{}`", value, flag)
+            }
+        }
+    }
+}
+
+///
+/// Command-line options used to launch the application
+///
+#[derive(Debug, Clone)]
+pub struct AppArgs {
+    pub test: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub vsync: bool,
+    pub gl_version: (u32, u32),
+    pub msaa: Option<u32>,
+    pub frames: Option<u64>,
+    pub dump_frame: Option<String>,
+    pub list_tests: bool,
+    pub help: bool,
+}
+
+impl Default for AppArgs {
+    fn default() -> Self {
+        Self {
+            test: None,
+            width: 1280,
+            height: 960,
+            vsync: true,
+            gl_version: (4, 5),
+            msaa: None,
+            frames: None,
+            dump_frame: None,
+            list_tests: false,
+            help: false,
+        }
+    }
+}
+
+impl AppArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut instance = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(flag) = args.next() {
+            match flag.as_str() {
+                "-h" | "--help" => instance.help = true,
+                "--list-tests" => instance.list_tests = true,
+                "--no-vsync" => instance.vsync = false,
+                "-t" | "--test" => instance.test = Some(Self::value(&flag, args.next())?),
+                "--size" => {
+                    let value = Self::value(&flag, args.next())?;
+                    let (width, height) = Self::parse_pair(&flag, &value, 'x')?;
+                    instance.width = width;
+                    instance.height = height;
+                }
+                "--width" => instance.width = Self::parse_value(&flag, args.next())?,
+                "--height" => instance.height = Self::parse_value(&flag, args.next())?,
+                "--vsync" => {
+                    let value = Self::value(&flag, args.next())?;
+                    instance.vsync = match value.as_str() {
+                        "on" | "true" | "1" => true,
+                        "off" | "false" | "0" => false,
+                        _ => return Err(ArgsError::InvalidValue { flag, value }),
+                    };
+                }
+                "--gl" => {
+                    let value = Self::value(&flag, args.next())?;
+                    instance.gl_version = Self::parse_pair(&flag, &value, '.')?;
+                }
+                "--msaa" => {
+                    let samples: u32 = Self::parse_value(&flag, args.next())?;
+                    instance.msaa = if samples > 0 { Some(samples) } else { None };
+                }
+                "--frames" => instance.frames = Some(Self::parse_value(&flag, args.next())?),
+                "--dump-frame" => instance.dump_frame = Some(Self::value(&flag, args.next())?),
+                _ => return Err(ArgsError::UnknownFlag(flag)),
+            }
+        }
+
+        Ok(instance)
+    }
+
+    pub fn usage() -> String {
+        [
+            "Usage: glfw-app [OPTIONS]",
+            "",
+            "Options:",
+            "  -t, --test <ID>          Launch directly into the test with the given id",
+            "      --list-tests         Print the available test ids and exit",
+            "      --size <W>x<H>       Window size (default: 1280x960)",
+            "      --width <W>          Window width",
+            "      --height <H>         Window height",
+            "      --vsync <on|off>     Enable or disable vsync (default: on)",
+            "      --no-vsync           Shorthand for `--vsync off`",
+            "      --gl <MAJOR.MINOR>   OpenGL context version (default: 4.5)",
+            "      --msaa <SAMPLES>     Multisample anti-aliasing sample count (0 disables)",
+            "      --frames <N>         Run for N frames and exit",
+            "      --dump-frame <PATH>  Save the last rendered frame as an image",
+            "  -h, --help               Print this message and exit",
+        ]
+        .join("\n")
+    }
+
+    /// The frame (1-based) whose contents should be written to `dump_frame`
+    pub fn dump_frame_index(&self) -> u64 {
+        self.frames.unwrap_or(1).max(1)
+    }
+
+    fn value(flag: &str, value: Option<String>) -> Result<String, ArgsError> {
+        value.ok_or_else(|| ArgsError::MissingValue(flag.into()))
+    }
+
+    fn parse_value<T: std::str::FromStr>(
+        flag: &str,
+        value: Option<String>,
+    ) -> Result<T, ArgsError> {
+        let value = Self::value(flag, value)?;
+        value.parse().map_err(|_| ArgsError::InvalidValue {
+            flag: flag.into(),
+            value,
+        })
+    }
+
+    fn parse_pair(flag: &str, value: &str, separator: char) -> Result<(u32, u32), ArgsError> {
+        let invalid = || ArgsError::InvalidValue {
+            flag: flag.into(),
+            value: value.into(),
+        };
+        let (first, second) = value.split_once(separator).ok_or_else(invalid)?;
+        let first = first.trim().parse().map_err(|_| invalid())?;
+        let second = second.trim().parse().map_err(|_| invalid())?;
+        Ok((first, second))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<AppArgs, ArgsError> {
+        AppArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn defaults_without_arguments() {
+        let args = parse(&[]).unwrap();
+        assert_eq!((args.width, args.height), (1280, 960));
+        assert_eq!(args.gl_version, (4, 5));
+        assert!(args.vsync);
+        assert!(args.test.is_none());
+    }
+
+    #[test]
+    fn size() {
+        let args = parse(&["--size", "800x600"]).unwrap();
+        assert_eq!((args.width, args.height), (800, 600));
+
+        let args = parse(&["--size", "800x600", "--height", "500"]).unwrap();
+        assert_eq!((args.width, args.height), (800, 500));
+
+        assert!(matches!(
+            parse(&["--size", "800"]),
+            Err(ArgsError::InvalidValue { flag, value }) if flag == "--size" && value == "800"
+        ));
+        assert!(matches!(
+            parse(&["--size", "800xabc"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn gl_version() {
+        assert_eq!(parse(&["--gl", "3.3"]).unwrap().gl_version, (3, 3));
+        assert!(matches!(
+            parse(&["--gl", "3"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_id() {
+        assert_eq!(
+            parse(&["--test", "3d_scene"]).unwrap().test.as_deref(),
+            Some("3d_scene")
+        );
+        assert_eq!(
+            parse(&["-t", "clear_color"]).unwrap().test.as_deref(),
+            Some("clear_color")
+        );
+    }
+
+    #[test]
+    fn flags() {
+        let args = parse(&["--no-vsync", "--list-tests", "--msaa", "0", "--frames", "3"]).unwrap();
+        assert!(!args.vsync);
+        assert!(args.list_tests);
+        assert_eq!(args.msaa, None);
+        assert_eq!(args.dump_frame_index(), 3);
+
+        assert!(parse(&["--vsync", "on"]).unwrap().vsync);
+        assert!(matches!(
+            parse(&["--vsync", "maybe"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_flag() {
+        assert!(matches!(
+            parse(&["--fullscreen"]),
+            Err(ArgsError::UnknownFlag(flag)) if flag == "--fullscreen"
+        ));
+    }
+
+    #[test]
+    fn missing_value() {
+        for flag in ["--test", "--size", "--gl", "--frames", "--dump-frame"] {
+            assert!(matches!(
+                parse(&[flag]),
+                Err(ArgsError::MissingValue(missing)) if missing == flag
+            ));
+        }
+    }
+}
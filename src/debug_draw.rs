@@ -0,0 +1,486 @@
+use std::{cell::RefCell, f32::consts::TAU, ffi::c_void, time::Duration};
+
+use glcall_macro::gl_call;
+use memoffset::offset_of;
+use nalgebra_glm as glm;
+
+use crate::{gl_component::GLComponent, shader::Shader, ShaderBuilder};
+
+const CIRCLE_SEGMENTS: usize = 32;
+
+/// How lines added to a `DebugDraw` are drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugOptions {
+    /// Hidden behind geometry, otherwise drawn on top of everything
+    pub depth_test: bool,
+    /// How long lines stay in simulated time, zero keeps them until the next update step
+    pub duration: Duration,
+}
+
+impl Default for DebugOptions {
+    fn default() -> Self {
+        Self {
+            depth_test: true,
+            duration: Duration::ZERO,
+        }
+    }
+}
+
+impl DebugOptions {
+    /// Drawn on top of everything for a single frame
+    pub fn overlay() -> Self {
+        Self::default().with_depth_test(false)
+    }
+
+    pub fn with_depth_test(mut self, depth_test: bool) -> Self {
+        self.depth_test = depth_test;
+        self
+    }
+
+    pub fn with_duration(mut self, seconds: f32) -> Self {
+        self.duration = Duration::from_secs_f32(seconds.max(0.0));
+        self
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct DebugVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DebugLine {
+    vertices: [DebugVertex; 2],
+    depth_test: bool,
+    /// Simulated time left, `None` for lines that only last until the next update step
+    remaining: Option<Duration>,
+}
+
+thread_local! {
+    static DEBUG_DRAW: RefCell<DebugDraw> = RefCell::new(DebugDraw::default());
+}
+
+///
+/// World space debug lines and gizmos, collected from anywhere during `update` and drawn
+/// once per frame by a `DebugDrawRenderer`
+///
+/// Lines use the current `DebugOptions`, `scoped` changes them for a few shapes. Lines
+/// without a duration are kept until `begin_update` starts the next update step, so they
+/// are drawn every frame while updates are paused. Durations count down with the update's
+/// delta time, which follows pausing and the time scale.
+///
+#[derive(Default)]
+pub struct DebugDraw {
+    lines: Vec<DebugLine>,
+    options: DebugOptions,
+}
+
+impl DebugDraw {
+    /// Runs `draw` with the debug draw of the current thread, which `DebugDrawRenderer::flush`
+    /// draws
+    pub fn with<R>(draw: impl FnOnce(&mut DebugDraw) -> R) -> R {
+        DEBUG_DRAW.with(|debug| draw(&mut debug.borrow_mut()))
+    }
+
+    pub fn options(&self) -> DebugOptions {
+        self.options
+    }
+
+    pub fn set_options(&mut self, options: DebugOptions) {
+        self.options = options;
+    }
+
+    /// Draws with `options`, restoring the previous ones afterwards
+    pub fn scoped(&mut self, options: DebugOptions, draw: impl FnOnce(&mut Self)) {
+        let previous = std::mem::replace(&mut self.options, options);
+        draw(self);
+        self.options = previous;
+    }
+
+    /// Number of lines waiting to be drawn
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Removes every line, including those with a duration left
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Drops the lines of the previous update step and those whose duration has passed,
+    /// call before every update step
+    pub fn begin_update(&mut self, delta_time: f32) {
+        let elapsed = Duration::from_secs_f32(delta_time.max(0.0));
+        self.lines.retain_mut(|line| match &mut line.remaining {
+            Some(remaining) if *remaining > elapsed => {
+                *remaining -= elapsed;
+                true
+            }
+            _ => false,
+        });
+    }
+
+    pub fn line(&mut self, start: &glm::Vec3, end: &glm::Vec3, color: glm::Vec4) {
+        let vertex = |position: &glm::Vec3| DebugVertex {
+            position: [position.x, position.y, position.z],
+            color: [color.x, color.y, color.z, color.w],
+        };
+        let duration = self.options.duration;
+        self.lines.push(DebugLine {
+            vertices: [vertex(start), vertex(end)],
+            depth_test: self.options.depth_test,
+            remaining: (!duration.is_zero()).then_some(duration),
+        });
+    }
+
+    /// Connected lines, `closed` also connects the last point to the first
+    pub fn polyline(&mut self, points: &[glm::Vec3], closed: bool, color: glm::Vec4) {
+        for pair in points.windows(2) {
+            self.line(&pair[0], &pair[1], color);
+        }
+        if closed && points.len() > 2 {
+            self.line(&points[points.len() - 1], &points[0], color);
+        }
+    }
+
+    /// A small three axis cross marking a point
+    pub fn point(&mut self, position: &glm::Vec3, size: f32, color: glm::Vec4) {
+        let half = size / 2.0;
+        for axis in [glm::Vec3::x(), glm::Vec3::y(), glm::Vec3::z()] {
+            self.line(&(position - axis * half), &(position + axis * half), color);
+        }
+    }
+
+    pub fn arrow(&mut self, start: &glm::Vec3, end: &glm::Vec3, color: glm::Vec4) {
+        self.line(start, end, color);
+
+        let direction = end - start;
+        let length = glm::length(&direction);
+        if length <= f32::EPSILON {
+            return;
+        }
+        let direction = direction / length;
+        let head = (length * 0.2).min(0.5);
+        let (side, up) = perpendiculars(&direction);
+        let base = end - direction * head;
+        for offset in [side, -side, up, -up] {
+            self.line(end, &(base + offset * head * 0.4), color);
+        }
+    }
+
+    pub fn aabb(&mut self, min: &glm::Vec3, max: &glm::Vec3, color: glm::Vec4) {
+        let corners: [glm::Vec3; 8] = std::array::from_fn(|i| {
+            glm::vec3(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        });
+        self.box_edges(&corners, color);
+    }
+
+    /// A unit cube from -1 to 1 transformed by `transform`, e.g. an oriented box
+    pub fn cube(&mut self, transform: &glm::Mat4, color: glm::Vec4) {
+        let corners: [glm::Vec3; 8] = std::array::from_fn(|i| {
+            let corner = glm::vec4(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+                1.0,
+            );
+            let corner = transform * corner;
+            corner.xyz() / corner.w
+        });
+        self.box_edges(&corners, color);
+    }
+
+    /// The volume a camera sees, from the inverse of its view projection
+    pub fn frustum(&mut self, view_projection: &glm::Mat4, color: glm::Vec4) {
+        if let Some(inverse) = view_projection.try_inverse() {
+            self.cube(&inverse, color);
+        }
+    }
+
+    /// Edges of a box whose corners are indexed by bits for x, y and z
+    fn box_edges(&mut self, corners: &[glm::Vec3; 8], color: glm::Vec4) {
+        for i in 0..8 {
+            for axis in [1, 2, 4] {
+                if i & axis == 0 {
+                    self.line(&corners[i], &corners[i | axis], color);
+                }
+            }
+        }
+    }
+
+    pub fn circle(
+        &mut self,
+        center: &glm::Vec3,
+        normal: &glm::Vec3,
+        radius: f32,
+        color: glm::Vec4,
+    ) {
+        let (side, up) = perpendiculars(&glm::normalize(normal));
+        let points: Vec<_> = (0..CIRCLE_SEGMENTS)
+            .map(|i| {
+                let angle = i as f32 / CIRCLE_SEGMENTS as f32 * TAU;
+                center + (side * angle.cos() + up * angle.sin()) * radius
+            })
+            .collect();
+        self.polyline(&points, true, color);
+    }
+
+    /// Three circles around the axes
+    pub fn sphere(&mut self, center: &glm::Vec3, radius: f32, color: glm::Vec4) {
+        for axis in [glm::Vec3::x(), glm::Vec3::y(), glm::Vec3::z()] {
+            self.circle(center, &axis, radius, color);
+        }
+    }
+
+    /// Red, green and blue arrows along the x, y and z axes of `transform`
+    pub fn axes(&mut self, transform: &glm::Mat4, size: f32) {
+        let origin = (transform * glm::vec4(0.0, 0.0, 0.0, 1.0)).xyz();
+        let colors = [
+            glm::vec4(1.0, 0.2, 0.2, 1.0),
+            glm::vec4(0.2, 1.0, 0.2, 1.0),
+            glm::vec4(0.3, 0.5, 1.0, 1.0),
+        ];
+        for (axis, color) in colors.into_iter().enumerate() {
+            let mut direction = glm::vec4(0.0, 0.0, 0.0, 0.0);
+            direction[axis] = size;
+            self.arrow(&origin, &(origin + (transform * direction).xyz()), color);
+        }
+    }
+
+    /// A grid on the xz plane around `center`, `cells` along each side of `size`
+    pub fn grid(&mut self, center: &glm::Vec3, size: f32, cells: usize, color: glm::Vec4) {
+        let cells = cells.max(1);
+        let half = size / 2.0;
+        for i in 0..=cells {
+            let offset = -half + size * i as f32 / cells as f32;
+            self.line(
+                &(center + glm::vec3(offset, 0.0, -half)),
+                &(center + glm::vec3(offset, 0.0, half)),
+                color,
+            );
+            self.line(
+                &(center + glm::vec3(-half, 0.0, offset)),
+                &(center + glm::vec3(half, 0.0, offset)),
+                color,
+            );
+        }
+    }
+
+    /// Vertices of the depth tested and overlay lines
+    fn frame_vertices(&self) -> (Vec<DebugVertex>, Vec<DebugVertex>) {
+        let mut depth_tested = Vec::new();
+        let mut overlay = Vec::new();
+        for line in &self.lines {
+            if line.depth_test {
+                depth_tested.extend(line.vertices);
+            } else {
+                overlay.extend(line.vertices);
+            }
+        }
+        (depth_tested, overlay)
+    }
+}
+
+/// Two unit vectors perpendicular to `direction` and each other
+fn perpendiculars(direction: &glm::Vec3) -> (glm::Vec3, glm::Vec3) {
+    let reference = if direction.y.abs() < 0.99 {
+        glm::Vec3::y()
+    } else {
+        glm::Vec3::x()
+    };
+    let side = glm::normalize(&glm::cross(direction, &reference));
+    let up = glm::cross(&side, direction);
+    (side, up)
+}
+
+///
+/// Draws the lines of a `DebugDraw`, the thread's one with `flush`
+///
+/// Depth tested lines are drawn first, overlay lines on top with the depth test disabled.
+/// The depth test is restored afterwards.
+///
+pub struct DebugDrawRenderer {
+    vao: u32,
+    vbo: u32,
+    shader: Shader,
+}
+
+impl Default for DebugDrawRenderer {
+    fn default() -> Self {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("res/shaders/DebugLine.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+
+        let mut vao = 0;
+        let mut vbo = 0;
+        let stride = std::mem::size_of::<DebugVertex>() as i32;
+        gl_call!({
+            gl::GenVertexArrays(1, &mut vao);
+            gl::BindVertexArray(vao);
+            gl::CreateBuffers(1, &mut vbo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(
+                0,
+                3,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset_of!(DebugVertex, position) as *const c_void,
+            );
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(
+                1,
+                4,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset_of!(DebugVertex, color) as *const c_void,
+            );
+            gl::BindVertexArray(0);
+        });
+
+        Self { vao, vbo, shader }
+    }
+}
+
+impl Drop for DebugDrawRenderer {
+    fn drop(&mut self) {
+        gl_call!({
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        });
+    }
+}
+
+impl DebugDrawRenderer {
+    /// Draws the lines collected on this thread with `DebugDraw::with`
+    pub fn flush(&self, view_projection: &glm::Mat4) {
+        DebugDraw::with(|debug| self.draw(debug, view_projection));
+    }
+
+    /// Draws the lines of `debug`, they stay until `DebugDraw::begin_update` drops them
+    pub fn draw(&self, debug: &DebugDraw, view_projection: &glm::Mat4) {
+        let (depth_tested, overlay) = debug.frame_vertices();
+        if depth_tested.is_empty() && overlay.is_empty() {
+            return;
+        }
+
+        let mut vertices = depth_tested;
+        let overlay_start = vertices.len();
+        vertices.extend(overlay);
+        gl_call!({
+            gl::NamedBufferData(
+                self.vbo,
+                std::mem::size_of_val(vertices.as_slice()) as isize,
+                vertices.as_ptr() as *const c_void,
+                gl::STREAM_DRAW,
+            );
+        });
+
+        let depth_test_enabled = unsafe { gl::IsEnabled(gl::DEPTH_TEST) } == gl::TRUE;
+        self.shader.bind();
+        self.shader
+            .uniform_mat4("u_ViewProjection", view_projection);
+        gl_call!({
+            gl::BindVertexArray(self.vao);
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DrawArrays(gl::LINES, 0, overlay_start as i32);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::DrawArrays(
+                gl::LINES,
+                overlay_start as i32,
+                (vertices.len() - overlay_start) as i32,
+            );
+            gl::BindVertexArray(0);
+        });
+        if depth_test_enabled {
+            gl_call!({
+                gl::Enable(gl::DEPTH_TEST);
+            });
+        }
+        self.shader.unbind();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(debug: &mut DebugDraw) {
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        debug.line(&glm::vec3(0.0, 0.0, 0.0), &glm::vec3(1.0, 0.0, 0.0), white);
+    }
+
+    #[test]
+    fn single_frame_lines_last_until_the_next_update() {
+        let mut debug = DebugDraw::default();
+        debug.begin_update(0.1);
+        line(&mut debug);
+
+        // Frames without an update step, e.g. while paused, draw the line again
+        assert_eq!(debug.frame_vertices().0.len(), 2);
+        assert_eq!(debug.frame_vertices().0.len(), 2);
+
+        debug.begin_update(0.1);
+        assert!(debug.is_empty());
+    }
+
+    #[test]
+    fn durations_count_down_with_updates() {
+        let mut debug = DebugDraw::default();
+        debug.set_options(DebugOptions::default().with_duration(0.25));
+        line(&mut debug);
+
+        debug.begin_update(0.0);
+        debug.begin_update(0.125);
+        assert_eq!(debug.len(), 1);
+        debug.begin_update(0.125);
+        assert!(debug.is_empty());
+    }
+
+    #[test]
+    fn overlay_lines_are_separate() {
+        let mut debug = DebugDraw::default();
+        line(&mut debug);
+        debug.scoped(DebugOptions::overlay(), line);
+        line(&mut debug);
+
+        let (depth_tested, overlay) = debug.frame_vertices();
+        assert_eq!((depth_tested.len(), overlay.len()), (4, 2));
+        // The options are restored after the scope
+        assert!(debug.options().depth_test);
+    }
+
+    #[test]
+    fn shapes_line_counts() {
+        let mut debug = DebugDraw::default();
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        debug.aabb(
+            &glm::vec3(-1.0, -1.0, -1.0),
+            &glm::vec3(1.0, 1.0, 1.0),
+            white,
+        );
+        assert_eq!(debug.len(), 12);
+
+        debug.clear();
+        debug.sphere(&glm::vec3(0.0, 0.0, 0.0), 1.0, white);
+        assert_eq!(debug.len(), 3 * CIRCLE_SEGMENTS);
+
+        debug.clear();
+        debug.grid(&glm::vec3(0.0, 0.0, 0.0), 10.0, 4, white);
+        assert_eq!(debug.len(), 2 * 5);
+    }
+}
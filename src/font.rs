@@ -0,0 +1,196 @@
+use std::{collections::BTreeMap, fmt::Display, path::Path};
+
+use rusttype::{Font, GlyphId};
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(std::io::Error),
+    /// The data is not a TrueType or OpenType font
+    Invalid(String),
+}
+
+impl Display for FontError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "IO error: {}", e),
+            Self::Invalid(name) => write!(f, "`{}` is not a valid TTF or OTF font", name),
+        }
+    }
+}
+
+impl From<std::io::Error> for FontError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Refers to a font of a `FontLibrary`, the default font is always `FontId::default()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct FontId(usize);
+
+///
+/// Loaded font faces addressed by `FontId`, with a chain of fallback fonts for characters
+/// a face has no glyph for, e.g. icons, emoji or CJK
+///
+/// The library starts out with the bundled font as the default. Glyphs of every face and
+/// size are rasterized once by the `GlyphCache`.
+///
+pub struct FontLibrary {
+    fonts: Vec<(String, Font<'static>)>,
+    names: BTreeMap<String, FontId>,
+    fallbacks: Vec<FontId>,
+    generation: u64,
+}
+
+impl Default for FontLibrary {
+    fn default() -> Self {
+        let mut instance = Self {
+            fonts: Vec::new(),
+            names: BTreeMap::new(),
+            fallbacks: Vec::new(),
+            generation: 0,
+        };
+        instance
+            .load_from_memory("default", include_bytes!("res/fonts/Default.ttf").to_vec())
+            .expect("Bundled font is invalid");
+        instance
+    }
+}
+
+impl FontLibrary {
+    /// Loads a TTF or OTF file, replacing a font of the same name keeps its id
+    pub fn load(
+        &mut self,
+        name: impl Into<String>,
+        path: impl AsRef<Path>,
+    ) -> Result<FontId, FontError> {
+        let data = std::fs::read(path)?;
+        self.load_from_memory(name, data)
+    }
+
+    /// Loads font data, e.g. embedded with `include_bytes!`
+    pub fn load_from_memory(
+        &mut self,
+        name: impl Into<String>,
+        data: Vec<u8>,
+    ) -> Result<FontId, FontError> {
+        let name = name.into();
+        let font = Font::try_from_vec(data).ok_or_else(|| FontError::Invalid(name.clone()))?;
+
+        if let Some(&id) = self.names.get(&name) {
+            self.fonts[id.0] = (name, font);
+            self.generation += 1;
+            return Ok(id);
+        }
+        let id = FontId(self.fonts.len());
+        self.names.insert(name.clone(), id);
+        self.fonts.push((name, font));
+        Ok(id)
+    }
+
+    /// Appends a font to the fallback chain, tried in order for missing glyphs
+    pub fn add_fallback(&mut self, id: FontId) {
+        if !self.fallbacks.contains(&id) {
+            self.fallbacks.push(id);
+        }
+    }
+
+    pub fn fallbacks(&self) -> &[FontId] {
+        &self.fallbacks
+    }
+
+    pub fn id(&self, name: &str) -> Option<FontId> {
+        self.names.get(name).copied()
+    }
+
+    pub fn name(&self, id: FontId) -> &str {
+        &self.fonts[id.0].0
+    }
+
+    /// The font of `id`, which must come from this library
+    pub fn font(&self, id: FontId) -> &Font<'static> {
+        &self.fonts[id.0].1
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = FontId> {
+        (0..self.fonts.len()).map(FontId)
+    }
+
+    /// Changes whenever a font is replaced, glyphs cached by `FontId` before are stale
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn has_glyph(&self, id: FontId, character: char) -> bool {
+        self.font(id).glyph(character).id() != GlyphId(0)
+    }
+
+    ///
+    /// The font to draw `character` with, `font` itself if it has the glyph, otherwise the
+    /// first fallback that does
+    ///
+    /// Characters no font has are drawn with `font`'s missing glyph box.
+    ///
+    pub fn resolve(&self, font: FontId, character: char) -> FontId {
+        if character.is_whitespace() || self.has_glyph(font, character) {
+            return font;
+        }
+        self.fallbacks
+            .iter()
+            .copied()
+            .find(|&fallback| self.has_glyph(fallback, character))
+            .unwrap_or(font)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: &[u8] = include_bytes!("res/fonts/Default.ttf");
+
+    #[test]
+    fn replacing_keeps_the_id() {
+        let mut fonts = FontLibrary::default();
+        assert_eq!(fonts.id("default"), Some(FontId::default()));
+
+        let mono = fonts.load_from_memory("mono", FONT.to_vec()).unwrap();
+        assert_ne!(mono, FontId::default());
+        assert_eq!(fonts.generation(), 0);
+
+        assert_eq!(fonts.load_from_memory("mono", FONT.to_vec()).unwrap(), mono);
+        assert_eq!(fonts.ids().count(), 2);
+        assert_eq!(fonts.name(mono), "mono");
+        // Glyphs cached for the old font have to go
+        assert_eq!(fonts.generation(), 1);
+    }
+
+    #[test]
+    fn invalid_fonts_are_rejected() {
+        let mut fonts = FontLibrary::default();
+        assert!(matches!(
+            fonts.load_from_memory("broken", vec![0; 16]),
+            Err(FontError::Invalid(name)) if name == "broken"
+        ));
+        assert_eq!(fonts.id("broken"), None);
+        assert_eq!(fonts.generation(), 0);
+    }
+
+    #[test]
+    fn fallbacks_resolve_in_order() {
+        let mut fonts = FontLibrary::default();
+        let mono = fonts.load_from_memory("mono", FONT.to_vec()).unwrap();
+        fonts.add_fallback(mono);
+        fonts.add_fallback(mono);
+        assert_eq!(fonts.fallbacks(), [mono]);
+
+        assert_eq!(fonts.resolve(FontId::default(), 'a'), FontId::default());
+        assert_eq!(fonts.resolve(FontId::default(), ' '), FontId::default());
+        // No font has the glyph, the requested font draws its missing glyph box
+        assert!(!fonts.has_glyph(mono, '\u{10FFFD}'));
+        assert_eq!(
+            fonts.resolve(FontId::default(), '\u{10FFFD}'),
+            FontId::default()
+        );
+    }
+}
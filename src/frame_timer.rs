@@ -0,0 +1,377 @@
+use imgui_glfw_rs::imgui::Ui;
+
+use crate::str_to_imstr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimestepMode {
+    Variable,
+    Fixed { step: f32 },
+}
+
+///
+/// Result of a single [`FrameTimer::tick`]
+///
+/// `steps` updates of `step_size` seconds should be run this frame, `alpha` is how far
+/// the simulation is between the last fixed step and the next one (always `1.0` for
+/// variable timesteps) and can be used to interpolate rendered state.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTick {
+    pub frame_time: f32,
+    pub delta_time: f32,
+    pub steps: u32,
+    pub step_size: f32,
+    pub alpha: f32,
+}
+
+///
+/// Measures real frame time and turns it into update steps
+///
+pub struct FrameTimer {
+    mode: TimestepMode,
+    time_scale: f32,
+    paused: bool,
+    pending_steps: u32,
+
+    max_frame_time: f32,
+    max_steps: u32,
+
+    last_time: Option<f64>,
+    accumulator: f32,
+    elapsed: f64,
+    frame_count: u64,
+    last_tick: FrameTick,
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new(TimestepMode::Variable)
+    }
+}
+
+impl FrameTimer {
+    pub const DEFAULT_STEP: f32 = 1.0 / 60.0;
+
+    pub fn new(mode: TimestepMode) -> Self {
+        Self {
+            mode,
+            time_scale: 1.0,
+            paused: false,
+            pending_steps: 0,
+            max_frame_time: 0.25,
+            max_steps: 8,
+            last_time: None,
+            accumulator: 0.0,
+            elapsed: 0.0,
+            frame_count: 0,
+            last_tick: FrameTick {
+                frame_time: 0.0,
+                delta_time: 0.0,
+                steps: 0,
+                step_size: 0.0,
+                alpha: 1.0,
+            },
+        }
+    }
+
+    /// Advances the timer to `now` (in seconds, e.g. from `glfw.get_time()`)
+    pub fn tick(&mut self, now: f64) -> FrameTick {
+        let frame_time = match self.last_time {
+            Some(last_time) => ((now - last_time).max(0.0) as f32).min(self.max_frame_time),
+            None => 0.0,
+        };
+        self.last_time = Some(now);
+        self.frame_count += 1;
+
+        let delta_time = if self.paused {
+            0.0
+        } else {
+            frame_time * self.time_scale
+        };
+
+        let tick = match self.mode {
+            TimestepMode::Variable => {
+                let (steps, step_size) = if self.paused {
+                    (self.take_pending_steps(), Self::DEFAULT_STEP)
+                } else {
+                    (1, delta_time)
+                };
+                FrameTick {
+                    frame_time,
+                    delta_time,
+                    steps,
+                    step_size,
+                    alpha: 1.0,
+                }
+            }
+            TimestepMode::Fixed { step } => {
+                let mut steps = self.take_pending_steps();
+                self.accumulator += delta_time;
+                while self.accumulator >= step && steps < self.max_steps {
+                    self.accumulator -= step;
+                    steps += 1;
+                }
+                // Drop the whole steps that could not be simulated instead of spiralling,
+                // only the fraction of a step is carried over
+                if steps >= self.max_steps {
+                    self.accumulator %= step;
+                }
+
+                FrameTick {
+                    frame_time,
+                    delta_time,
+                    steps,
+                    step_size: step,
+                    alpha: self.accumulator / step,
+                }
+            }
+        };
+
+        self.elapsed += (tick.steps as f32 * tick.step_size) as f64;
+        self.last_tick = tick;
+        tick
+    }
+
+    fn take_pending_steps(&mut self) -> u32 {
+        std::mem::take(&mut self.pending_steps)
+    }
+
+    pub fn mode(&self) -> TimestepMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: TimestepMode) {
+        self.mode = mode;
+        self.accumulator = 0.0;
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.accumulator = 0.0;
+    }
+
+    /// Runs a single update on the next tick while paused
+    pub fn step(&mut self) {
+        if self.paused {
+            self.pending_steps += 1;
+        }
+    }
+
+    pub fn set_max_frame_time(&mut self, max_frame_time: f32) {
+        self.max_frame_time = max_frame_time;
+    }
+
+    pub fn set_max_steps(&mut self, max_steps: u32) {
+        self.max_steps = max_steps.max(1);
+    }
+
+    /// Simulated time in seconds, affected by pausing and the time scale
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn last_tick(&self) -> FrameTick {
+        self.last_tick
+    }
+
+    pub fn fps(&self) -> f32 {
+        if self.last_tick.frame_time > 0.0 {
+            1.0 / self.last_tick.frame_time
+        } else {
+            0.0
+        }
+    }
+
+    pub fn imgui_render(&mut self, ui: &Ui) {
+        let tick = self.last_tick;
+        ui.text(format!(
+            "{:.1} FPS ({:.2} ms)",
+            self.fps(),
+            tick.frame_time * 1000.0
+        ));
+        ui.text(format!(
+            "{} update(s) of {:.2} ms, alpha {:.2}",
+            tick.steps,
+            tick.step_size * 1000.0,
+            tick.alpha
+        ));
+
+        let mut paused = self.paused;
+        if ui.checkbox(&str_to_imstr("Paused"), &mut paused) {
+            self.set_paused(paused);
+        }
+        if self.paused {
+            ui.same_line(0.0);
+            if ui.button(&str_to_imstr("Step"), [60.0, 20.0]) {
+                self.step();
+            }
+        }
+
+        ui.slider_float(&str_to_imstr("Time Scale"), &mut self.time_scale, 0.0, 4.0)
+            .build();
+
+        let mut fixed = matches!(self.mode, TimestepMode::Fixed { .. });
+        if ui.checkbox(&str_to_imstr("Fixed Timestep"), &mut fixed) {
+            self.set_mode(if fixed {
+                TimestepMode::Fixed {
+                    step: Self::DEFAULT_STEP,
+                }
+            } else {
+                TimestepMode::Variable
+            });
+        }
+        if let TimestepMode::Fixed { step } = &mut self.mode {
+            let mut rate = (1.0 / *step).round() as i32;
+            if ui
+                .slider_int(&str_to_imstr("Updates / Second"), &mut rate, 10, 240)
+                .build()
+            {
+                *step = 1.0 / rate.max(1) as f32;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: f32 = 0.25;
+
+    /// A fixed timer fed frames of `frame_time` seconds, its first tick already taken
+    fn fixed_timer(max_steps: u32) -> FrameTimer {
+        let mut timer = FrameTimer::new(TimestepMode::Fixed { step: STEP });
+        timer.set_max_frame_time(10.0);
+        timer.set_max_steps(max_steps);
+        timer.tick(0.0);
+        timer
+    }
+
+    #[test]
+    fn first_tick_has_no_frame_time() {
+        let mut timer = FrameTimer::default();
+        let tick = timer.tick(5.0);
+        assert_eq!(tick.frame_time, 0.0);
+        assert_eq!(tick.steps, 1);
+        assert_eq!(timer.frame_count(), 1);
+    }
+
+    #[test]
+    fn variable_runs_one_step_of_the_frame_time() {
+        let mut timer = FrameTimer::default();
+        timer.tick(1.0);
+        let tick = timer.tick(1.125);
+        assert_eq!(tick.steps, 1);
+        assert_eq!(tick.step_size, 0.125);
+        assert_eq!(tick.alpha, 1.0);
+
+        timer.set_time_scale(2.0);
+        assert_eq!(timer.tick(1.25).delta_time, 0.25);
+    }
+
+    #[test]
+    fn frame_time_is_clamped() {
+        let mut timer = FrameTimer::default();
+        timer.tick(0.0);
+        assert_eq!(timer.tick(10.0).frame_time, 0.25);
+    }
+
+    #[test]
+    fn accumulator_steps() {
+        let mut timer = fixed_timer(8);
+
+        // Half a step is kept for the next frame
+        let tick = timer.tick(0.125);
+        assert_eq!(tick.steps, 0);
+        assert_eq!(tick.alpha, 0.5);
+
+        let tick = timer.tick(0.375);
+        assert_eq!(tick.steps, 1);
+        assert_eq!(tick.step_size, STEP);
+        assert_eq!(tick.alpha, 0.5);
+
+        let tick = timer.tick(0.875);
+        assert_eq!(tick.steps, 2);
+        assert_eq!(tick.alpha, 0.5);
+        assert_eq!(timer.elapsed(), 0.75);
+    }
+
+    #[test]
+    fn max_steps_clamp() {
+        let mut timer = fixed_timer(2);
+
+        let tick = timer.tick(2.125);
+        assert_eq!(tick.steps, 2);
+        // The whole steps that could not be simulated are dropped, the fraction is kept
+        assert_eq!(tick.alpha, 0.5);
+
+        let tick = timer.tick(2.125);
+        assert_eq!(tick.steps, 0);
+        assert_eq!(tick.alpha, 0.5);
+    }
+
+    #[test]
+    fn interpolation_alpha() {
+        let mut timer = fixed_timer(8);
+        for (now, alpha) in [(0.0625, 0.25), (0.125, 0.5), (0.1875, 0.75), (0.25, 0.0)] {
+            assert_eq!(timer.tick(now).alpha, alpha);
+        }
+    }
+
+    #[test]
+    fn pause_stops_updates() {
+        let mut timer = fixed_timer(8);
+        timer.set_paused(true);
+
+        let tick = timer.tick(1.0);
+        assert_eq!(tick.delta_time, 0.0);
+        assert_eq!(tick.steps, 0);
+        assert_eq!(timer.elapsed(), 0.0);
+
+        timer.set_paused(false);
+        assert_eq!(timer.tick(1.25).steps, 1);
+    }
+
+    #[test]
+    fn single_step_while_paused() {
+        let mut timer = fixed_timer(8);
+        timer.set_paused(true);
+        timer.step();
+        timer.step();
+
+        let tick = timer.tick(0.5);
+        assert_eq!(tick.steps, 2);
+        assert_eq!(tick.step_size, STEP);
+        assert_eq!(timer.tick(1.0).steps, 0);
+
+        let mut timer = FrameTimer::default();
+        timer.set_paused(true);
+        timer.step();
+        let tick = timer.tick(0.0);
+        assert_eq!(tick.steps, 1);
+        assert_eq!(tick.step_size, FrameTimer::DEFAULT_STEP);
+    }
+
+    #[test]
+    fn step_is_ignored_while_running() {
+        let mut timer = fixed_timer(8);
+        timer.step();
+        assert_eq!(timer.tick(0.0).steps, 0);
+    }
+}
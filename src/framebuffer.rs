@@ -0,0 +1,109 @@
+use std::{fmt::Display, rc::Rc};
+
+use glcall_macro::gl_call;
+
+use crate::{gl_component::GLComponent, texture::Texture};
+
+#[derive(Debug)]
+pub struct FramebufferIncompleteError {
+    pub status: u32,
+}
+
+impl Display for FramebufferIncompleteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Framebuffer is incomplete (status 0x{:X})", self.status)
+    }
+}
+
+///
+/// An offscreen render target
+///
+/// Binding the framebuffer also sets the viewport to its size, the caller restores the
+/// screen viewport with `Renderer::set_viewport` once done.
+///
+pub struct Framebuffer {
+    renderer_id: u32,
+    width: usize,
+    height: usize,
+    depth_attachment: Option<Rc<Texture>>,
+}
+
+impl GLComponent for Framebuffer {
+    fn renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+
+    fn bind(&self) {
+        gl_call!({
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.renderer_id);
+            gl::Viewport(0, 0, self.width as i32, self.height as i32);
+        });
+    }
+
+    fn unbind(&self) {
+        gl_call!({
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        });
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        gl_call!({
+            gl::DeleteFramebuffers(1, &self.renderer_id);
+        });
+    }
+}
+
+impl Framebuffer {
+    /// Framebuffer with only a depth texture attached, as used for shadow maps
+    pub fn depth_only(width: usize, height: usize) -> Result<Self, FramebufferIncompleteError> {
+        let depth = Rc::new(Texture::depth(width, height));
+
+        let mut instance = Self {
+            renderer_id: 0,
+            width,
+            height,
+            depth_attachment: None,
+        };
+
+        gl_call!({
+            gl::GenFramebuffers(1, &mut instance.renderer_id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, instance.renderer_id);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_2D,
+                depth.renderer_id(),
+                0,
+            );
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+        });
+        instance.depth_attachment = Some(depth);
+
+        let status = instance.status();
+        instance.unbind();
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(FramebufferIncompleteError { status });
+        }
+        Ok(instance)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn depth_attachment(&self) -> Option<&Rc<Texture>> {
+        self.depth_attachment.as_ref()
+    }
+
+    fn status(&self) -> u32 {
+        unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) }
+    }
+}
@@ -0,0 +1,292 @@
+use std::rc::Rc;
+
+use glcall_macro::gl_call;
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::Camera,
+    gl_component::GLComponent,
+    index_buffer::IndexBuffer,
+    material::Material,
+    mesh::{GpuMesh, Mesh},
+    pbr::ToneMapping,
+    render_state::CompareFunc,
+    renderer::Renderer,
+    sampler::{TextureFilter, TextureSpec},
+    texture::{Texture, TextureError, TextureFormat, TextureObject},
+    texture_cube::{CubeCapture, TextureCube},
+    vertex_array::VertexArray,
+    vertex_buffer::VertexBuffer,
+    vertex_buffer_layout::VertexBufferLayout,
+    ShaderBuilder,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IblSettings {
+    /// Face size of the environment cube map the source is projected onto
+    pub environment_size: usize,
+    pub irradiance_size: usize,
+    pub prefilter_size: usize,
+    /// Mip levels of the prefiltered map, roughness 0.0 to 1.0 spread across them
+    pub prefilter_mip_levels: u32,
+    pub brdf_lut_size: usize,
+}
+
+impl Default for IblSettings {
+    fn default() -> Self {
+        Self {
+            environment_size: 512,
+            irradiance_size: 32,
+            prefilter_size: 128,
+            prefilter_mip_levels: 5,
+            brdf_lut_size: 512,
+        }
+    }
+}
+
+/// A simple analytic sky, used when no HDR environment is available
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkySettings {
+    /// Direction the sunlight travels in, pointing away from the sun
+    pub sun_direction: glm::Vec3,
+    pub sun_color: glm::Vec3,
+    pub sun_intensity: f32,
+    /// Angular radius of the sun disc, in degrees
+    pub sun_size: f32,
+    pub zenith_color: glm::Vec3,
+    pub horizon_color: glm::Vec3,
+    pub ground_color: glm::Vec3,
+}
+
+impl Default for SkySettings {
+    fn default() -> Self {
+        Self {
+            sun_direction: glm::vec3(-0.4, -0.6, -0.7),
+            sun_color: glm::vec3(1.0, 0.95, 0.85),
+            sun_intensity: 50.0,
+            sun_size: 2.0,
+            zenith_color: glm::vec3(0.15, 0.35, 0.8),
+            horizon_color: glm::vec3(0.8, 0.85, 0.9),
+            ground_color: glm::vec3(0.2, 0.18, 0.15),
+        }
+    }
+}
+
+///
+/// Precomputed image based lighting for `Pbr.glsl`
+///
+/// The source is projected onto a cube map, then convolved into a diffuse irradiance map
+/// and a specular map prefiltered per roughness level. Together with the BRDF lookup
+/// table this is the split sum approximation.
+///
+/// Building an environment renders into offscreen framebuffers, the viewport is restored
+/// but depth testing and face culling are left disabled, see `CubeCapture`.
+///
+pub struct Environment {
+    environment: Rc<TextureCube>,
+    irradiance: Rc<TextureCube>,
+    prefiltered: Rc<TextureCube>,
+    brdf_lut: Rc<Texture>,
+    prefilter_mip_levels: u32,
+}
+
+impl Environment {
+    /// Builds the environment from an equirectangular (latitude-longitude) HDR texture
+    pub fn from_equirectangular(
+        renderer: &Renderer,
+        equirectangular: Rc<Texture>,
+        settings: &IblSettings,
+    ) -> Result<Self, TextureError> {
+        let environment = TextureCube::from_equirectangular(
+            renderer,
+            &equirectangular,
+            settings.environment_size,
+        )?;
+        Self::from_cube_map(renderer, Rc::new(environment), settings)
+    }
+
+    pub fn from_sky(
+        renderer: &Renderer,
+        sky: &SkySettings,
+        settings: &IblSettings,
+    ) -> Result<Self, TextureError> {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("res/shaders/ProceduralSky.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        let source = Material::new(shader)
+            .with_uniform("u_SunDirection", sky.sun_direction)
+            .with_uniform("u_SunColor", sky.sun_color)
+            .with_uniform("u_SunIntensity", sky.sun_intensity)
+            .with_uniform("u_SunSize", sky.sun_size.to_radians())
+            .with_uniform("u_ZenithColor", sky.zenith_color)
+            .with_uniform("u_HorizonColor", sky.horizon_color)
+            .with_uniform("u_GroundColor", sky.ground_color);
+        let environment = TextureCube::from_material(renderer, settings.environment_size, &source)?;
+        Self::from_cube_map(renderer, Rc::new(environment), settings)
+    }
+
+    /// Builds the environment from a cube map, which should have mipmaps to avoid
+    /// aliasing in the prefiltered map
+    pub fn from_cube_map(
+        renderer: &Renderer,
+        environment: Rc<TextureCube>,
+        settings: &IblSettings,
+    ) -> Result<Self, TextureError> {
+        let capture = CubeCapture::new(renderer);
+
+        let irradiance = TextureCube::empty(settings.irradiance_size, TextureFormat::Rgb16F)?;
+        let irradiance_shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("res/shaders/IrradianceConvolution.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        let irradiance_material =
+            Material::new(irradiance_shader).with_texture("u_EnvironmentMap", environment.clone());
+        capture.render(renderer, &irradiance, 0, &irradiance_material)?;
+
+        // The whole mip chain is allocated up front so each level can be rendered into
+        let prefilter_mip_levels = settings.prefilter_mip_levels.max(1);
+        let prefiltered = TextureCube::empty(settings.prefilter_size, TextureFormat::Rgb16F)?
+            .with_spec(TextureSpec::default().with_mipmaps(TextureFilter::Linear));
+        let prefilter_shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("res/shaders/PrefilterEnvironment.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        let prefilter_material = Rc::new(
+            Material::new(prefilter_shader)
+                .with_texture("u_EnvironmentMap", environment.clone())
+                .with_uniform("u_EnvironmentResolution", environment.size() as f32),
+        );
+        for mip in 0..prefilter_mip_levels {
+            let roughness = mip as f32 / (prefilter_mip_levels - 1).max(1) as f32;
+            let material =
+                Material::instance(&prefilter_material).with_uniform("u_Roughness", roughness);
+            capture.render(renderer, &prefiltered, mip, &material)?;
+        }
+
+        let brdf_lut = generate_brdf_lut(renderer, &capture, settings.brdf_lut_size)?;
+
+        Ok(Self {
+            environment,
+            irradiance: Rc::new(irradiance),
+            prefiltered: Rc::new(prefiltered),
+            brdf_lut: Rc::new(brdf_lut),
+            prefilter_mip_levels,
+        })
+    }
+
+    pub fn environment(&self) -> &Rc<TextureCube> {
+        &self.environment
+    }
+
+    pub fn irradiance(&self) -> &Rc<TextureCube> {
+        &self.irradiance
+    }
+
+    pub fn prefiltered(&self) -> &Rc<TextureCube> {
+        &self.prefiltered
+    }
+
+    pub fn brdf_lut(&self) -> &Rc<Texture> {
+        &self.brdf_lut
+    }
+
+    pub fn prefilter_mip_levels(&self) -> u32 {
+        self.prefilter_mip_levels
+    }
+
+    /// Binds the maps and enables image based lighting in `Pbr.glsl`
+    pub fn apply(&self, material: Material) -> Material {
+        material
+            .with_texture("u_IrradianceMap", self.irradiance.clone())
+            .with_texture("u_PrefilterMap", self.prefiltered.clone())
+            .with_texture("u_BrdfLut", self.brdf_lut.clone())
+            .with_uniform("u_PrefilterMipLevels", self.prefilter_mip_levels as f32)
+            .with_uniform("u_UseIbl", 1)
+    }
+}
+
+///
+/// Draws a cube map around the camera, behind everything else in the scene
+///
+pub struct Skybox {
+    cube: GpuMesh,
+    material: Rc<Material>,
+}
+
+impl Default for Skybox {
+    fn default() -> Self {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("res/shaders/Skybox.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+
+        Self {
+            cube: Mesh::cube(2.0).upload(),
+            material: Rc::new(Material::new(shader)),
+        }
+    }
+}
+
+impl Skybox {
+    /// Draws `cube_map` at mip `lod`, call after the opaque geometry
+    pub fn draw(
+        &self,
+        renderer: &Renderer,
+        camera: &impl Camera,
+        cube_map: &Rc<TextureCube>,
+        lod: f32,
+        tone_mapping: &ToneMapping,
+    ) {
+        let material = Material::instance(&self.material)
+            .with_texture("u_EnvironmentMap", cube_map.clone())
+            .with_uniform("u_View", *camera.view())
+            .with_uniform("u_Projection", *camera.projection())
+            .with_uniform("u_Lod", lod);
+        let material = tone_mapping.apply(material);
+
+        renderer.set_face_culling(None);
+        let depth = renderer.depth_state();
+        renderer.set_depth_state(&depth.with_func(CompareFunc::LessEqual));
+        self.cube.draw(renderer, &material);
+        renderer.set_depth_state(&depth);
+    }
+}
+
+fn generate_brdf_lut(
+    renderer: &Renderer,
+    capture: &CubeCapture,
+    size: usize,
+) -> Result<Texture, TextureError> {
+    let lut = Texture::empty(size, size, TextureFormat::Rg16F)?;
+
+    #[rustfmt::skip]
+    let vertices: Vec<f32> = vec![
+        -1.0, -1.0,   0.0, 0.0,
+         1.0, -1.0,   1.0, 0.0,
+         1.0,  1.0,   1.0, 1.0,
+        -1.0,  1.0,   0.0, 1.0,
+    ];
+    let mut vao = VertexArray::new();
+    let vbo = VertexBuffer::new(&vertices);
+    let ibo = IndexBuffer::new(&vec![0, 1, 2, 2, 3, 0]);
+    vao.add_buffer(
+        &vbo,
+        &VertexBufferLayout::default().with_floats(2).with_floats(2),
+    );
+
+    let shader = ShaderBuilder::default()
+        .with_shader_source(include_str!("res/shaders/BrdfLut.glsl").into())
+        .expect("Failed to build shader from source")
+        .build();
+
+    capture.begin(size);
+    capture.attach(gl::TEXTURE_2D, lut.renderer_id(), 0)?;
+    renderer.clear();
+    renderer.draw(&vao, &ibo, &Material::new(shader));
+    gl_call!({
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+    });
+
+    Ok(lut)
+}
@@ -4,6 +4,7 @@ use std::ffi::c_void;
 
 pub struct IndexBuffer {
     renderer_id: u32,
+    count: usize,
 }
 
 impl GLComponent for IndexBuffer {
@@ -45,6 +46,13 @@ impl IndexBuffer {
                 gl::STATIC_DRAW,
             );
         });
-        Self { renderer_id }
+        Self {
+            renderer_id,
+            count: data.len(),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
     }
 }
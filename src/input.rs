@@ -0,0 +1,550 @@
+use std::collections::{HashMap, HashSet};
+
+use imgui_glfw_rs::glfw::{
+    self, Action, GamepadAxis, GamepadButton, Glfw, JoystickId, Key, MouseButton, WindowEvent,
+};
+
+const JOYSTICK_IDS: [JoystickId; 16] = [
+    JoystickId::Joystick1,
+    JoystickId::Joystick2,
+    JoystickId::Joystick3,
+    JoystickId::Joystick4,
+    JoystickId::Joystick5,
+    JoystickId::Joystick6,
+    JoystickId::Joystick7,
+    JoystickId::Joystick8,
+    JoystickId::Joystick9,
+    JoystickId::Joystick10,
+    JoystickId::Joystick11,
+    JoystickId::Joystick12,
+    JoystickId::Joystick13,
+    JoystickId::Joystick14,
+    JoystickId::Joystick15,
+    JoystickId::Joystick16,
+];
+
+const GAMEPAD_BUTTONS: [GamepadButton; 15] = [
+    GamepadButton::ButtonA,
+    GamepadButton::ButtonB,
+    GamepadButton::ButtonX,
+    GamepadButton::ButtonY,
+    GamepadButton::ButtonLeftBumper,
+    GamepadButton::ButtonRightBumper,
+    GamepadButton::ButtonBack,
+    GamepadButton::ButtonStart,
+    GamepadButton::ButtonGuide,
+    GamepadButton::ButtonLeftThumb,
+    GamepadButton::ButtonRightThumb,
+    GamepadButton::ButtonDpadUp,
+    GamepadButton::ButtonDpadRight,
+    GamepadButton::ButtonDpadDown,
+    GamepadButton::ButtonDpadLeft,
+];
+
+const GAMEPAD_AXES: [GamepadAxis; 6] = [
+    GamepadAxis::AxisLeftX,
+    GamepadAxis::AxisLeftY,
+    GamepadAxis::AxisRightX,
+    GamepadAxis::AxisRightY,
+    GamepadAxis::AxisLeftTrigger,
+    GamepadAxis::AxisRightTrigger,
+];
+
+#[derive(Default, Clone)]
+pub struct Gamepad {
+    name: Option<String>,
+    buttons: [bool; 15],
+    previous_buttons: [bool; 15],
+    axes: [f32; 6],
+}
+
+impl Gamepad {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn is_held(&self, button: GamepadButton) -> bool {
+        self.buttons[button as usize]
+    }
+
+    pub fn is_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons[button as usize] && !self.previous_buttons[button as usize]
+    }
+
+    pub fn is_released(&self, button: GamepadButton) -> bool {
+        !self.buttons[button as usize] && self.previous_buttons[button as usize]
+    }
+
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+}
+
+///
+/// Snapshot of keyboard, mouse and gamepad state for the current frame
+///
+/// Keyboard and mouse queries return `false` (and zeroed motion) while imgui wants to
+/// capture that device, so scenes do not react to input meant for a widget.
+///
+#[derive(Default)]
+pub struct Input {
+    keys: HashSet<Key>,
+    // Edges seen since the last update, so a tap between two updates is not lost
+    pressed_keys: HashSet<Key>,
+    released_keys: HashSet<Key>,
+
+    mouse_buttons: HashSet<MouseButton>,
+    pressed_mouse_buttons: HashSet<MouseButton>,
+    released_mouse_buttons: HashSet<MouseButton>,
+
+    cursor_position: (f32, f32),
+    previous_cursor_position: Option<(f32, f32)>,
+    scroll: (f32, f32),
+
+    gamepads: HashMap<JoystickId, Gamepad>,
+
+    keyboard_captured: bool,
+    mouse_captured: bool,
+}
+
+impl Input {
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::Key(key, _, Action::Press, _) => {
+                self.keys.insert(*key);
+                self.pressed_keys.insert(*key);
+            }
+            WindowEvent::Key(key, _, Action::Release, _) => {
+                self.keys.remove(key);
+                self.released_keys.insert(*key);
+            }
+            WindowEvent::MouseButton(button, Action::Press, _) => {
+                self.mouse_buttons.insert(*button);
+                self.pressed_mouse_buttons.insert(*button);
+            }
+            WindowEvent::MouseButton(button, Action::Release, _) => {
+                self.mouse_buttons.remove(button);
+                self.released_mouse_buttons.insert(*button);
+            }
+            WindowEvent::CursorPos(x, y) => self.cursor_position = (*x as f32, *y as f32),
+            WindowEvent::Scroll(x, y) => {
+                self.scroll.0 += *x as f32;
+                self.scroll.1 += *y as f32;
+            }
+            WindowEvent::Focus(false) => {
+                self.released_keys.extend(self.keys.drain());
+                self.released_mouse_buttons
+                    .extend(self.mouse_buttons.drain());
+            }
+            _ => {}
+        }
+    }
+
+    pub fn poll_gamepads(&mut self, glfw: &Glfw) {
+        for id in JOYSTICK_IDS {
+            let joystick = glfw.get_joystick(id);
+            let state = match joystick.get_gamepad_state() {
+                Some(state) if joystick.is_gamepad() => state,
+                _ => {
+                    self.gamepads.remove(&id);
+                    continue;
+                }
+            };
+
+            let gamepad = self.gamepads.entry(id).or_default();
+            if gamepad.name.is_none() {
+                gamepad.name = joystick.get_gamepad_name();
+            }
+            for button in GAMEPAD_BUTTONS {
+                gamepad.buttons[button as usize] =
+                    state.get_button_state(button) == glfw::Action::Press;
+            }
+            for axis in GAMEPAD_AXES {
+                gamepad.axes[axis as usize] = state.get_axis(axis);
+            }
+        }
+    }
+
+    /// Updates which devices imgui is currently capturing (`io.want_capture_*`)
+    pub fn set_captured(&mut self, keyboard: bool, mouse: bool) {
+        self.keyboard_captured = keyboard;
+        self.mouse_captured = mouse;
+    }
+
+    /// Forgets the presses, releases and motion an update has seen, call once it has run
+    pub fn end_frame(&mut self) {
+        self.pressed_keys.clear();
+        self.released_keys.clear();
+        self.pressed_mouse_buttons.clear();
+        self.released_mouse_buttons.clear();
+        self.previous_cursor_position = Some(self.cursor_position);
+        self.scroll = (0.0, 0.0);
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.previous_buttons = gamepad.buttons;
+        }
+    }
+
+    pub fn is_key_held(&self, key: Key) -> bool {
+        !self.keyboard_captured && self.keys.contains(&key)
+    }
+
+    pub fn is_key_pressed(&self, key: Key) -> bool {
+        !self.keyboard_captured && self.pressed_keys.contains(&key)
+    }
+
+    pub fn is_key_released(&self, key: Key) -> bool {
+        !self.keyboard_captured && self.released_keys.contains(&key)
+    }
+
+    pub fn is_mouse_held(&self, button: MouseButton) -> bool {
+        !self.mouse_captured && self.mouse_buttons.contains(&button)
+    }
+
+    pub fn is_mouse_pressed(&self, button: MouseButton) -> bool {
+        !self.mouse_captured && self.pressed_mouse_buttons.contains(&button)
+    }
+
+    pub fn is_mouse_released(&self, button: MouseButton) -> bool {
+        !self.mouse_captured && self.released_mouse_buttons.contains(&button)
+    }
+
+    /// Cursor position in window coordinates, origin in the top left
+    pub fn cursor_position(&self) -> (f32, f32) {
+        self.cursor_position
+    }
+
+    pub fn cursor_delta(&self) -> (f32, f32) {
+        match self.previous_cursor_position {
+            Some((x, y)) if !self.mouse_captured => {
+                (self.cursor_position.0 - x, self.cursor_position.1 - y)
+            }
+            _ => (0.0, 0.0),
+        }
+    }
+
+    pub fn scroll(&self) -> (f32, f32) {
+        if self.mouse_captured {
+            (0.0, 0.0)
+        } else {
+            self.scroll
+        }
+    }
+
+    pub fn gamepad(&self, id: JoystickId) -> Option<&Gamepad> {
+        self.gamepads.get(&id)
+    }
+
+    pub fn gamepads(&self) -> impl Iterator<Item = (&JoystickId, &Gamepad)> {
+        self.gamepads.iter()
+    }
+
+    pub fn is_keyboard_captured(&self) -> bool {
+        self.keyboard_captured
+    }
+
+    pub fn is_mouse_captured(&self) -> bool {
+        self.mouse_captured
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    Key(Key),
+    MouseButton(MouseButton),
+    GamepadButton(GamepadButton),
+    /// Treats a gamepad axis as a button once it passes `threshold`, a negative threshold
+    /// triggers on the negative side of the axis
+    GamepadAxis(GamepadAxis, f32),
+}
+
+impl Binding {
+    fn is_held(&self, input: &Input) -> bool {
+        match *self {
+            Binding::Key(key) => input.is_key_held(key),
+            Binding::MouseButton(button) => input.is_mouse_held(button),
+            Binding::GamepadButton(button) => input.gamepads().any(|(_, g)| g.is_held(button)),
+            Binding::GamepadAxis(axis, threshold) => input
+                .gamepads()
+                .any(|(_, g)| Self::past_threshold(g.axis(axis), threshold)),
+        }
+    }
+
+    fn is_pressed(&self, input: &Input) -> bool {
+        match *self {
+            Binding::Key(key) => input.is_key_pressed(key),
+            Binding::MouseButton(button) => input.is_mouse_pressed(button),
+            Binding::GamepadButton(button) => input.gamepads().any(|(_, g)| g.is_pressed(button)),
+            Binding::GamepadAxis(..) => false,
+        }
+    }
+
+    fn is_released(&self, input: &Input) -> bool {
+        match *self {
+            Binding::Key(key) => input.is_key_released(key),
+            Binding::MouseButton(button) => input.is_mouse_released(button),
+            Binding::GamepadButton(button) => input.gamepads().any(|(_, g)| g.is_released(button)),
+            Binding::GamepadAxis(..) => false,
+        }
+    }
+
+    fn past_threshold(value: f32, threshold: f32) -> bool {
+        if threshold < 0.0 {
+            value <= threshold
+        } else {
+            value >= threshold
+        }
+    }
+}
+
+///
+/// Maps named actions to one or more input bindings
+///
+/// # Usage
+/// ```ignore
+/// let actions = ActionMap::default()
+///     .with_binding("move_left", Binding::Key(Key::A))
+///     .with_binding("move_left", Binding::GamepadAxis(GamepadAxis::AxisLeftX, -0.5));
+///
+/// if actions.is_held(&input, "move_left") { /* ... */ }
+/// ```
+///
+#[derive(Default, Clone)]
+pub struct ActionMap {
+    actions: HashMap<String, Vec<Binding>>,
+}
+
+impl ActionMap {
+    pub fn with_binding(mut self, action: impl Into<String>, binding: Binding) -> Self {
+        self.bind(action, binding);
+        self
+    }
+
+    pub fn bind(&mut self, action: impl Into<String>, binding: Binding) {
+        self.actions.entry(action.into()).or_default().push(binding);
+    }
+
+    pub fn clear(&mut self, action: impl AsRef<str>) {
+        self.actions.remove(action.as_ref());
+    }
+
+    pub fn bindings(&self, action: impl AsRef<str>) -> &[Binding] {
+        self.actions
+            .get(action.as_ref())
+            .map(|bindings| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn is_held(&self, input: &Input, action: impl AsRef<str>) -> bool {
+        self.bindings(action).iter().any(|b| b.is_held(input))
+    }
+
+    pub fn is_pressed(&self, input: &Input, action: impl AsRef<str>) -> bool {
+        self.bindings(action).iter().any(|b| b.is_pressed(input))
+    }
+
+    pub fn is_released(&self, input: &Input, action: impl AsRef<str>) -> bool {
+        self.bindings(action).iter().any(|b| b.is_released(input))
+    }
+
+    /// `-1.0`, `0.0` or `1.0` depending on which of the two actions are held
+    pub fn axis(&self, input: &Input, negative: impl AsRef<str>, positive: impl AsRef<str>) -> f32 {
+        let mut value = 0.0;
+        if self.is_held(input, negative) {
+            value -= 1.0;
+        }
+        if self.is_held(input, positive) {
+            value += 1.0;
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use imgui_glfw_rs::glfw::Modifiers;
+
+    fn key(key: Key, action: Action) -> WindowEvent {
+        WindowEvent::Key(key, 0, action, Modifiers::empty())
+    }
+
+    fn mouse(button: MouseButton, action: Action) -> WindowEvent {
+        WindowEvent::MouseButton(button, action, Modifiers::empty())
+    }
+
+    /// Input with one gamepad whose `buttons` are held
+    fn with_gamepad(buttons: &[GamepadButton]) -> Input {
+        let mut input = Input::default();
+        let gamepad = input.gamepads.entry(JoystickId::Joystick1).or_default();
+        for &button in buttons {
+            gamepad.buttons[button as usize] = true;
+        }
+        input
+    }
+
+    #[test]
+    fn key_edges_last_until_end_frame() {
+        let mut input = Input::default();
+        input.handle_event(&key(Key::A, Action::Press));
+        assert!(input.is_key_held(Key::A));
+        assert!(input.is_key_pressed(Key::A));
+        assert!(!input.is_key_released(Key::A));
+
+        // Key repeats are neither presses nor releases
+        input.end_frame();
+        input.handle_event(&key(Key::A, Action::Repeat));
+        assert!(input.is_key_held(Key::A));
+        assert!(!input.is_key_pressed(Key::A));
+
+        input.handle_event(&key(Key::A, Action::Release));
+        assert!(!input.is_key_held(Key::A));
+        assert!(input.is_key_released(Key::A));
+        input.end_frame();
+        assert!(!input.is_key_released(Key::A));
+    }
+
+    #[test]
+    fn taps_between_updates_are_kept() {
+        let mut input = Input::default();
+        input.handle_event(&key(Key::Space, Action::Press));
+        input.handle_event(&key(Key::Space, Action::Release));
+        input.handle_event(&mouse(MouseButton::Button1, Action::Press));
+        input.handle_event(&mouse(MouseButton::Button1, Action::Release));
+
+        assert!(!input.is_key_held(Key::Space));
+        assert!(input.is_key_pressed(Key::Space));
+        assert!(input.is_key_released(Key::Space));
+        assert!(!input.is_mouse_held(MouseButton::Button1));
+        assert!(input.is_mouse_pressed(MouseButton::Button1));
+        assert!(input.is_mouse_released(MouseButton::Button1));
+
+        input.end_frame();
+        assert!(!input.is_key_pressed(Key::Space));
+        assert!(!input.is_mouse_released(MouseButton::Button1));
+    }
+
+    #[test]
+    fn losing_focus_releases_everything() {
+        let mut input = Input::default();
+        input.handle_event(&key(Key::W, Action::Press));
+        input.handle_event(&mouse(MouseButton::Button2, Action::Press));
+        input.end_frame();
+
+        input.handle_event(&WindowEvent::Focus(false));
+        assert!(!input.is_key_held(Key::W));
+        assert!(input.is_key_released(Key::W));
+        assert!(input.is_mouse_released(MouseButton::Button2));
+    }
+
+    #[test]
+    fn cursor_and_scroll() {
+        let mut input = Input::default();
+        input.handle_event(&WindowEvent::CursorPos(10.0, 20.0));
+        // Without a previous position there is no motion yet
+        assert_eq!(input.cursor_delta(), (0.0, 0.0));
+        input.end_frame();
+
+        input.handle_event(&WindowEvent::CursorPos(15.0, 10.0));
+        input.handle_event(&WindowEvent::Scroll(0.0, 1.0));
+        input.handle_event(&WindowEvent::Scroll(0.5, 1.0));
+        assert_eq!(input.cursor_position(), (15.0, 10.0));
+        assert_eq!(input.cursor_delta(), (5.0, -10.0));
+        assert_eq!(input.scroll(), (0.5, 2.0));
+
+        input.end_frame();
+        assert_eq!(input.cursor_delta(), (0.0, 0.0));
+        assert_eq!(input.scroll(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn captured_devices_read_as_idle() {
+        let mut input = Input::default();
+        input.handle_event(&key(Key::A, Action::Press));
+        input.handle_event(&mouse(MouseButton::Button1, Action::Press));
+        input.handle_event(&WindowEvent::Scroll(0.0, 1.0));
+
+        input.set_captured(true, false);
+        assert!(!input.is_key_held(Key::A));
+        assert!(!input.is_key_pressed(Key::A));
+        assert!(input.is_mouse_pressed(MouseButton::Button1));
+
+        input.set_captured(false, true);
+        assert!(input.is_key_pressed(Key::A));
+        assert!(!input.is_mouse_held(MouseButton::Button1));
+        assert_eq!(input.scroll(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn gamepad_edges_last_until_end_frame() {
+        let mut input = with_gamepad(&[GamepadButton::ButtonA]);
+        let gamepad = input.gamepad(JoystickId::Joystick1).unwrap();
+        assert!(gamepad.is_held(GamepadButton::ButtonA));
+        assert!(gamepad.is_pressed(GamepadButton::ButtonA));
+
+        input.end_frame();
+        let gamepad = input.gamepad(JoystickId::Joystick1).unwrap();
+        assert!(gamepad.is_held(GamepadButton::ButtonA));
+        assert!(!gamepad.is_pressed(GamepadButton::ButtonA));
+
+        input
+            .gamepads
+            .get_mut(&JoystickId::Joystick1)
+            .unwrap()
+            .buttons = [false; 15];
+        assert!(input
+            .gamepad(JoystickId::Joystick1)
+            .unwrap()
+            .is_released(GamepadButton::ButtonA));
+    }
+
+    #[test]
+    fn action_map() {
+        let actions = ActionMap::default()
+            .with_binding("jump", Binding::Key(Key::Space))
+            .with_binding("jump", Binding::GamepadButton(GamepadButton::ButtonA))
+            .with_binding("fire", Binding::MouseButton(MouseButton::Button1));
+        assert_eq!(actions.bindings("jump").len(), 2);
+        assert!(actions.bindings("crouch").is_empty());
+
+        let mut input = Input::default();
+        input.handle_event(&key(Key::Space, Action::Press));
+        assert!(actions.is_held(&input, "jump"));
+        assert!(actions.is_pressed(&input, "jump"));
+        assert!(!actions.is_held(&input, "fire"));
+        assert!(!actions.is_held(&input, "crouch"));
+
+        // Any of the bindings triggers the action
+        let input = with_gamepad(&[GamepadButton::ButtonA]);
+        assert!(actions.is_pressed(&input, "jump"));
+
+        let mut actions = actions;
+        actions.clear("jump");
+        assert!(!actions.is_held(&input, "jump"));
+    }
+
+    #[test]
+    fn action_axis() {
+        let actions = ActionMap::default()
+            .with_binding("left", Binding::Key(Key::A))
+            .with_binding("left", Binding::GamepadAxis(GamepadAxis::AxisLeftX, -0.5))
+            .with_binding("right", Binding::Key(Key::D))
+            .with_binding("right", Binding::GamepadAxis(GamepadAxis::AxisLeftX, 0.5));
+
+        let mut input = Input::default();
+        assert_eq!(actions.axis(&input, "left", "right"), 0.0);
+        input.handle_event(&key(Key::A, Action::Press));
+        assert_eq!(actions.axis(&input, "left", "right"), -1.0);
+        input.handle_event(&key(Key::D, Action::Press));
+        assert_eq!(actions.axis(&input, "left", "right"), 0.0);
+
+        let mut input = with_gamepad(&[]);
+        let gamepad = input.gamepads.get_mut(&JoystickId::Joystick1).unwrap();
+        gamepad.axes[GamepadAxis::AxisLeftX as usize] = 0.25;
+        assert_eq!(actions.axis(&input, "left", "right"), 0.0);
+        let gamepad = input.gamepads.get_mut(&JoystickId::Joystick1).unwrap();
+        gamepad.axes[GamepadAxis::AxisLeftX as usize] = 0.75;
+        assert_eq!(actions.axis(&input, "left", "right"), 1.0);
+        // Axes have no edges
+        assert!(!actions.is_pressed(&input, "right"));
+    }
+}
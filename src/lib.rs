@@ -1,17 +1,48 @@
 pub mod tests;
 
+pub mod application;
+pub mod camera;
+pub mod camera_controller;
+pub mod cli;
+pub mod frame_timer;
+pub mod input;
+
 pub mod gl_error;
 pub use gl_error::{gl_clear_errors, gl_log_errors};
 
 pub mod gl_component;
 
+pub mod framebuffer;
 pub mod index_buffer;
+pub mod uniform_buffer;
 pub mod vertex_array;
 pub mod vertex_buffer;
 pub mod vertex_buffer_layout;
 
+pub mod atlas;
+pub mod debug_draw;
+pub mod font;
+pub mod ibl;
+pub mod light;
+pub mod material;
+pub mod mesh;
+pub mod pbr;
+pub mod render_queue;
+pub mod render_state;
 pub mod renderer;
+pub mod sampler;
+pub mod sdf_text;
+pub mod shadow;
+pub mod shapes;
+pub mod sprite_sheet;
+pub mod state_cache;
+pub mod tessellation;
+pub mod text;
+pub mod text_layout;
 pub mod texture;
+pub mod texture_3d;
+pub mod texture_array;
+pub mod texture_cube;
 
 pub mod shader;
 pub use shader::ShaderBuilder;
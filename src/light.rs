@@ -0,0 +1,231 @@
+use nalgebra_glm as glm;
+
+use crate::uniform_buffer::UniformBuffer;
+
+pub const MAX_POINT_LIGHTS: usize = 8;
+pub const MAX_SPOT_LIGHTS: usize = 4;
+
+/// Binding point the `Lights` uniform block of `Lit.glsl` is attached to
+pub const LIGHTS_BINDING: u32 = 0;
+
+/// Distance falloff `1 / (constant + linear * d + quadratic * d^2)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Attenuation {
+    /// Falloff that has faded to roughly 1% of the light's intensity at `range`
+    pub fn from_range(range: f32) -> Self {
+        let range = range.max(0.01);
+        Self {
+            constant: 1.0,
+            linear: 4.5 / range,
+            quadratic: 75.0 / (range * range),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> f32 {
+        1.0 / (self.constant + self.linear * distance + self.quadratic * distance * distance)
+    }
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Self::from_range(20.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionalLight {
+    pub direction: glm::Vec3,
+    pub color: glm::Vec3,
+    pub intensity: f32,
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        Self {
+            direction: glm::vec3(-0.3, -1.0, -0.5),
+            color: glm::vec3(1.0, 1.0, 1.0),
+            intensity: 0.8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub position: glm::Vec3,
+    pub color: glm::Vec3,
+    pub intensity: f32,
+    pub attenuation: Attenuation,
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        Self {
+            position: glm::vec3(0.0, 1.0, 0.0),
+            color: glm::vec3(1.0, 1.0, 1.0),
+            intensity: 1.0,
+            attenuation: Attenuation::default(),
+        }
+    }
+}
+
+///
+/// Cone shaped light, full intensity inside `inner_angle` fading out towards `outer_angle`
+/// (both half angles in degrees)
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpotLight {
+    pub position: glm::Vec3,
+    pub direction: glm::Vec3,
+    pub color: glm::Vec3,
+    pub intensity: f32,
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    pub attenuation: Attenuation,
+}
+
+impl Default for SpotLight {
+    fn default() -> Self {
+        Self {
+            position: glm::vec3(0.0, 3.0, 0.0),
+            direction: glm::vec3(0.0, -1.0, 0.0),
+            color: glm::vec3(1.0, 1.0, 1.0),
+            intensity: 1.0,
+            inner_angle: 12.5,
+            outer_angle: 17.5,
+            attenuation: Attenuation::default(),
+        }
+    }
+}
+
+///
+/// Every light of a scene, uploaded to the `Lights` uniform block in one go
+///
+/// The block is laid out with std140 rules and only uses `vec4`s, so the buffer is a flat
+/// list of floats:
+///
+/// ```glsl
+/// layout(std140) uniform Lights {
+///     vec4 u_Ambient;                 // rgb, intensity
+///     vec4 u_LightCounts;             // point count, spot count, directional enabled
+///     vec4 u_DirectionalDirection;    // xyz
+///     vec4 u_DirectionalColor;        // rgb, intensity
+///     PointLight u_PointLights[8];    // position, color, attenuation
+///     SpotLight u_SpotLights[4];      // position, direction + cos inner, color, attenuation + cos outer
+/// };
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct LightEnvironment {
+    pub ambient_color: glm::Vec3,
+    pub ambient_intensity: f32,
+    pub directional: Option<DirectionalLight>,
+    pub point_lights: Vec<PointLight>,
+    pub spot_lights: Vec<SpotLight>,
+}
+
+impl Default for LightEnvironment {
+    fn default() -> Self {
+        Self {
+            ambient_color: glm::vec3(1.0, 1.0, 1.0),
+            ambient_intensity: 0.1,
+            directional: Some(DirectionalLight::default()),
+            point_lights: Vec::new(),
+            spot_lights: Vec::new(),
+        }
+    }
+}
+
+impl LightEnvironment {
+    const HEADER_VEC4S: usize = 4;
+    const POINT_LIGHT_VEC4S: usize = 3;
+    const SPOT_LIGHT_VEC4S: usize = 4;
+
+    /// Size of the `Lights` uniform block in bytes
+    pub fn std140_size() -> usize {
+        let vec4s = Self::HEADER_VEC4S
+            + MAX_POINT_LIGHTS * Self::POINT_LIGHT_VEC4S
+            + MAX_SPOT_LIGHTS * Self::SPOT_LIGHT_VEC4S;
+        vec4s * 4 * std::mem::size_of::<f32>()
+    }
+
+    /// Packs the lights into the `Lights` block layout, lights past the maximum are dropped
+    pub fn to_std140(&self) -> Vec<f32> {
+        let point_count = self.point_lights.len().min(MAX_POINT_LIGHTS);
+        let spot_count = self.spot_lights.len().min(MAX_SPOT_LIGHTS);
+
+        let mut data = Vec::with_capacity(Self::std140_size() / std::mem::size_of::<f32>());
+        let mut push = |v: glm::Vec3, w: f32| data.extend_from_slice(&[v.x, v.y, v.z, w]);
+
+        push(self.ambient_color, self.ambient_intensity);
+        push(
+            glm::vec3(
+                point_count as f32,
+                spot_count as f32,
+                self.directional.is_some() as i32 as f32,
+            ),
+            0.0,
+        );
+
+        let directional = self.directional.unwrap_or(DirectionalLight {
+            intensity: 0.0,
+            ..Default::default()
+        });
+        push(safe_normalize(&directional.direction), 0.0);
+        push(directional.color, directional.intensity);
+
+        for i in 0..MAX_POINT_LIGHTS {
+            let light = self.point_lights.get(i).copied().unwrap_or_default();
+            let attenuation = light.attenuation;
+            push(light.position, 1.0);
+            push(light.color, light.intensity);
+            push(attenuation_vec(&attenuation), 0.0);
+        }
+
+        for i in 0..MAX_SPOT_LIGHTS {
+            let light = self.spot_lights.get(i).copied().unwrap_or_default();
+            let inner = light.inner_angle.min(light.outer_angle);
+            push(light.position, 1.0);
+            push(safe_normalize(&light.direction), inner.to_radians().cos());
+            push(light.color, light.intensity);
+            push(
+                attenuation_vec(&light.attenuation),
+                light.outer_angle.to_radians().cos(),
+            );
+        }
+
+        data
+    }
+
+    pub fn upload(&self, buffer: &UniformBuffer) {
+        buffer.set_data(0, &self.to_std140());
+    }
+}
+
+/// Matrix that transforms normals by `model`, keeping them perpendicular under
+/// non-uniform scaling
+pub fn normal_matrix(model: &glm::Mat4) -> glm::Mat3 {
+    let upper = glm::mat4_to_mat3(model);
+    glm::transpose(&glm::inverse(&upper))
+}
+
+fn attenuation_vec(attenuation: &Attenuation) -> glm::Vec3 {
+    glm::vec3(
+        attenuation.constant,
+        attenuation.linear,
+        attenuation.quadratic,
+    )
+}
+
+fn safe_normalize(v: &glm::Vec3) -> glm::Vec3 {
+    if glm::length(v) > f32::EPSILON {
+        glm::normalize(v)
+    } else {
+        glm::vec3(0.0, -1.0, 0.0)
+    }
+}
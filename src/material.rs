@@ -0,0 +1,250 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use glcall_macro::gl_call;
+use nalgebra_glm as glm;
+
+use crate::{
+    gl_component::GLComponent, sampler::Sampler, shader::Shader, state_cache::GlStateCache,
+    texture::TextureObject,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UniformValue {
+    Int(i32),
+    Float(f32),
+    Vec2(glm::Vec2),
+    Vec3(glm::Vec3),
+    Vec4(glm::Vec4),
+    Mat3(glm::Mat3),
+    Mat4(glm::Mat4),
+    IntArray(Vec<i32>),
+}
+
+impl UniformValue {
+    pub fn apply(&self, shader: &Shader, name: &str) {
+        match self {
+            Self::Int(v) => shader.uniform_1i(name, *v),
+            Self::Float(v) => shader.uniform_1f(name, *v),
+            Self::Vec2(v) => shader.uniform_2f(name, (v.x, v.y)),
+            Self::Vec3(v) => shader.uniform_3f(name, (v.x, v.y, v.z)),
+            Self::Vec4(v) => shader.uniform_4f(name, (v.x, v.y, v.z, v.w)),
+            Self::Mat3(v) => shader.uniform_mat3(name, v),
+            Self::Mat4(v) => shader.uniform_mat4(name, v),
+            Self::IntArray(v) => shader.uniform_1iv(name, v.clone()),
+        }
+    }
+}
+
+impl From<i32> for UniformValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f32> for UniformValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<glm::Vec2> for UniformValue {
+    fn from(value: glm::Vec2) -> Self {
+        Self::Vec2(value)
+    }
+}
+
+impl From<glm::Vec3> for UniformValue {
+    fn from(value: glm::Vec3) -> Self {
+        Self::Vec3(value)
+    }
+}
+
+impl From<glm::Vec4> for UniformValue {
+    fn from(value: glm::Vec4) -> Self {
+        Self::Vec4(value)
+    }
+}
+
+impl From<glm::Mat3> for UniformValue {
+    fn from(value: glm::Mat3) -> Self {
+        Self::Mat3(value)
+    }
+}
+
+impl From<glm::Mat4> for UniformValue {
+    fn from(value: glm::Mat4) -> Self {
+        Self::Mat4(value)
+    }
+}
+
+impl From<Vec<i32>> for UniformValue {
+    fn from(value: Vec<i32>) -> Self {
+        Self::IntArray(value)
+    }
+}
+
+///
+/// A shader together with the textures and uniform values it is drawn with
+///
+/// Textures are assigned to texture units in order of their sampler name when the
+/// material is applied. An instance created with [`Material::instance`] shares its
+/// parent's shader and parameters and only stores the ones it overrides.
+///
+#[derive(Clone)]
+pub struct Material {
+    shader: Shader,
+    parent: Option<Rc<Material>>,
+    textures: BTreeMap<String, Rc<dyn TextureObject>>,
+    samplers: BTreeMap<String, Rc<Sampler>>,
+    uniforms: BTreeMap<String, UniformValue>,
+}
+
+impl Material {
+    pub fn new(shader: Shader) -> Self {
+        Self {
+            shader,
+            parent: None,
+            textures: BTreeMap::new(),
+            samplers: BTreeMap::new(),
+            uniforms: BTreeMap::new(),
+        }
+    }
+
+    pub fn instance(parent: &Rc<Material>) -> Self {
+        Self {
+            shader: parent.shader.clone(),
+            parent: Some(parent.clone()),
+            textures: BTreeMap::new(),
+            samplers: BTreeMap::new(),
+            uniforms: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_texture(
+        mut self,
+        sampler: impl Into<String>,
+        texture: Rc<dyn TextureObject>,
+    ) -> Self {
+        self.set_texture(sampler, texture);
+        self
+    }
+
+    /// Samples the texture of `sampler` with a sampler object instead of its own parameters
+    pub fn with_sampler(mut self, sampler: impl Into<String>, object: Rc<Sampler>) -> Self {
+        self.set_sampler(sampler, object);
+        self
+    }
+
+    pub fn with_uniform(mut self, name: impl Into<String>, value: impl Into<UniformValue>) -> Self {
+        self.set_uniform(name, value);
+        self
+    }
+
+    pub fn set_texture(&mut self, sampler: impl Into<String>, texture: Rc<dyn TextureObject>) {
+        self.textures.insert(sampler.into(), texture);
+    }
+
+    pub fn set_sampler(&mut self, sampler: impl Into<String>, object: Rc<Sampler>) {
+        self.samplers.insert(sampler.into(), object);
+    }
+
+    pub fn set_uniform(&mut self, name: impl Into<String>, value: impl Into<UniformValue>) {
+        self.uniforms.insert(name.into(), value.into());
+    }
+
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    pub fn parent(&self) -> Option<&Rc<Material>> {
+        self.parent.as_ref()
+    }
+
+    /// The value of `name` on this material or the closest parent that sets it
+    pub fn uniform(&self, name: &str) -> Option<&UniformValue> {
+        self.uniforms
+            .get(name)
+            .or_else(|| self.parent.as_ref()?.uniform(name))
+    }
+
+    pub fn texture(&self, sampler: &str) -> Option<&Rc<dyn TextureObject>> {
+        self.textures
+            .get(sampler)
+            .or_else(|| self.parent.as_ref()?.texture(sampler))
+    }
+
+    pub fn sampler(&self, sampler: &str) -> Option<&Rc<Sampler>> {
+        self.samplers
+            .get(sampler)
+            .or_else(|| self.parent.as_ref()?.sampler(sampler))
+    }
+
+    /// Every texture of this material and its parents, overrides replacing inherited ones
+    pub fn resolved_textures(&self) -> BTreeMap<&str, &Rc<dyn TextureObject>> {
+        let mut textures = match &self.parent {
+            Some(parent) => parent.resolved_textures(),
+            None => BTreeMap::new(),
+        };
+        textures.extend(self.textures.iter().map(|(k, v)| (k.as_str(), v)));
+        textures
+    }
+
+    pub fn resolved_uniforms(&self) -> BTreeMap<&str, &UniformValue> {
+        let mut uniforms = match &self.parent {
+            Some(parent) => parent.resolved_uniforms(),
+            None => BTreeMap::new(),
+        };
+        uniforms.extend(self.uniforms.iter().map(|(k, v)| (k.as_str(), v)));
+        uniforms
+    }
+
+    /// Binds the shader, its textures and uploads every uniform
+    pub fn apply(&self) {
+        self.shader.bind();
+
+        for (unit, (sampler, texture)) in self.resolved_textures().into_iter().enumerate() {
+            texture.bind_unit(unit as u32);
+            match self.sampler(sampler) {
+                Some(object) => object.bind(unit as u32),
+                // Falls back to the texture's own parameters
+                None => {
+                    gl_call!({
+                        gl::BindSampler(unit as u32, 0);
+                    });
+                }
+            }
+            self.shader.uniform_1i(sampler, unit as i32);
+        }
+
+        for (name, value) in self.resolved_uniforms() {
+            value.apply(&self.shader, name);
+        }
+    }
+
+    /// Like `apply`, skipping the shader, texture and sampler binds `cache` has already made
+    pub fn apply_cached(&self, cache: &GlStateCache) {
+        cache.use_program(self.shader.renderer_id());
+
+        for (unit, (sampler, texture)) in self.resolved_textures().into_iter().enumerate() {
+            cache.bind_texture(unit as u32, texture.renderer_id());
+            cache.bind_sampler(
+                unit as u32,
+                self.sampler(sampler)
+                    .map_or(0, |object| object.renderer_id()),
+            );
+            self.shader.uniform_1i(sampler, unit as i32);
+        }
+
+        for (name, value) in self.resolved_uniforms() {
+            value.apply(&self.shader, name);
+        }
+    }
+
+    /// The material this one was instanced from, or itself, so instances sort together
+    pub fn root(&self) -> &Material {
+        match &self.parent {
+            Some(parent) => parent.root(),
+            None => self,
+        }
+    }
+}
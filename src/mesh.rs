@@ -0,0 +1,1124 @@
+use std::{
+    fmt::Display,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use image::RgbaImage;
+use nalgebra_glm as glm;
+
+use crate::{
+    gl_component::GLComponent, index_buffer::IndexBuffer, material::Material, renderer::Renderer,
+    vertex_array::VertexArray, vertex_buffer::VertexBuffer,
+    vertex_buffer_layout::VertexBufferLayout,
+};
+
+#[derive(Debug)]
+pub enum MeshError {
+    Io(std::io::Error),
+    Obj(tobj::LoadError),
+    Gltf(gltf::Error),
+    UnsupportedFormat(String),
+    Empty,
+}
+
+impl Display for MeshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "IO error: {}", e),
+            Self::Obj(e) => write!(f, "Failed to load OBJ: {}", e),
+            Self::Gltf(e) => write!(f, "Failed to load glTF: {}", e),
+            Self::UnsupportedFormat(extension) => {
+                write!(f, "Unsupported mesh format `{}`", extension)
+            }
+            Self::Empty => write!(f, "Mesh does not contain any triangles"),
+        }
+    }
+}
+
+impl From<std::io::Error> for MeshError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<tobj::LoadError> for MeshError {
+    fn from(value: tobj::LoadError) -> Self {
+        Self::Obj(value)
+    }
+}
+
+impl From<gltf::Error> for MeshError {
+    fn from(value: gltf::Error) -> Self {
+        Self::Gltf(value)
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+    /// xyz is the tangent, w the handedness of the bitangent
+    pub tangent: [f32; 4],
+}
+
+impl MeshVertex {
+    pub fn layout() -> VertexBufferLayout {
+        VertexBufferLayout::default()
+            .with_floats(3)
+            .with_floats(3)
+            .with_floats(2)
+            .with_floats(4)
+    }
+}
+
+/// A range of the index buffer drawn with a single material
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submesh {
+    pub name: String,
+    pub index_offset: usize,
+    pub index_count: usize,
+    pub material: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MaterialTexture {
+    /// Image file on disk, resolved relative to the mesh file
+    Path(PathBuf),
+    /// Index into [`Mesh::images`]
+    Image(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshMaterial {
+    pub name: String,
+    pub base_color: [f32; 4],
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: [f32; 3],
+
+    pub base_color_texture: Option<MaterialTexture>,
+    pub normal_texture: Option<MaterialTexture>,
+    pub metallic_roughness_texture: Option<MaterialTexture>,
+    pub occlusion_texture: Option<MaterialTexture>,
+    pub emissive_texture: Option<MaterialTexture>,
+}
+
+impl Default for MeshMaterial {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            base_color: [1.0, 1.0, 1.0, 1.0],
+            metallic: 0.0,
+            roughness: 1.0,
+            emissive: [0.0, 0.0, 0.0],
+            base_color_texture: None,
+            normal_texture: None,
+            metallic_roughness_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+        }
+    }
+}
+
+///
+/// CPU side mesh data, loaded from disk or generated, that can be uploaded to the GPU
+///
+/// Texture coordinates follow the OpenGL convention with `(0, 0)` in the bottom left,
+/// glTF coordinates and embedded images are flipped on import to match.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+    pub submeshes: Vec<Submesh>,
+    pub materials: Vec<MeshMaterial>,
+    pub images: Vec<RgbaImage>,
+    /// Problems that did not stop the mesh from loading, e.g. a missing material library
+    pub warnings: Vec<String>,
+}
+
+impl Mesh {
+    /// Loads an `.obj`, `.gltf` or `.glb` file depending on its extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MeshError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "obj" => Self::load_obj(path),
+            "gltf" | "glb" => Self::load_gltf(path),
+            _ => Err(MeshError::UnsupportedFormat(extension)),
+        }
+    }
+
+    pub fn load_obj(path: impl AsRef<Path>) -> Result<Self, MeshError> {
+        let path = path.as_ref();
+        let (models, materials) = tobj::load_obj(path, &Self::obj_load_options())?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let (materials, warning) = match materials {
+            Ok(materials) => (materials, None),
+            Err(e) => (vec![], Some(format!("Failed to load materials: {}", e))),
+        };
+
+        let mut instance = Self::from_obj_models(models, materials, base_dir)?;
+        instance.warnings.extend(warning);
+        Ok(instance)
+    }
+
+    /// Parses OBJ source, material libraries are ignored
+    pub fn from_obj_str(source: &str) -> Result<Self, MeshError> {
+        let mut reader = BufReader::new(source.as_bytes());
+        let (models, _) = tobj::load_obj_buf(&mut reader, &Self::obj_load_options(), |_| {
+            Err(tobj::LoadError::OpenFileFailed)
+        })?;
+        Self::from_obj_models(models, vec![], Path::new(""))
+    }
+
+    pub fn load_gltf(path: impl AsRef<Path>) -> Result<Self, MeshError> {
+        let (document, buffers, images) = gltf::import(path)?;
+        Self::from_gltf(document, buffers, images)
+    }
+
+    /// Loads a binary (`.glb`) or self contained `.gltf` file with embedded buffers
+    pub fn from_gltf_slice(bytes: &[u8]) -> Result<Self, MeshError> {
+        let (document, buffers, images) = gltf::import_slice(bytes)?;
+        Self::from_gltf(document, buffers, images)
+    }
+
+    fn obj_load_options() -> tobj::LoadOptions {
+        tobj::LoadOptions {
+            single_index: true,
+            triangulate: true,
+            ignore_points: true,
+            ignore_lines: true,
+        }
+    }
+
+    fn from_obj_models(
+        models: Vec<tobj::Model>,
+        materials: Vec<tobj::Material>,
+        base_dir: &Path,
+    ) -> Result<Self, MeshError> {
+        let mut instance = Self::default();
+        let mut has_normals = true;
+        let mut has_uvs = true;
+
+        for model in models {
+            let mesh = model.mesh;
+            let base_vertex = instance.vertices.len() as u32;
+            let vertex_count = mesh.positions.len() / 3;
+
+            has_normals &= mesh.normals.len() == vertex_count * 3;
+            has_uvs &= mesh.texcoords.len() == vertex_count * 2;
+
+            instance
+                .vertices
+                .extend((0..vertex_count).map(|i| MeshVertex {
+                    position: [
+                        mesh.positions[i * 3],
+                        mesh.positions[i * 3 + 1],
+                        mesh.positions[i * 3 + 2],
+                    ],
+                    normal: match mesh.normals.get(i * 3..i * 3 + 3) {
+                        Some(n) => [n[0], n[1], n[2]],
+                        None => [0.0, 0.0, 0.0],
+                    },
+                    uv: match mesh.texcoords.get(i * 2..i * 2 + 2) {
+                        Some(uv) => [uv[0], uv[1]],
+                        None => [0.0, 0.0],
+                    },
+                    tangent: [0.0, 0.0, 0.0, 1.0],
+                }));
+
+            instance.push_submesh(
+                model.name,
+                mesh.indices.iter().map(|i| i + base_vertex),
+                mesh.material_id,
+            );
+        }
+
+        instance.materials = materials
+            .into_iter()
+            .map(|material| {
+                let texture = |name: Option<String>| {
+                    name.filter(|name| !name.is_empty())
+                        .map(|name| MaterialTexture::Path(base_dir.join(name)))
+                };
+                let diffuse = material.diffuse.unwrap_or([1.0, 1.0, 1.0]);
+                let shininess = material.shininess.unwrap_or(0.0);
+
+                MeshMaterial {
+                    name: material.name,
+                    base_color: [
+                        diffuse[0],
+                        diffuse[1],
+                        diffuse[2],
+                        material.dissolve.unwrap_or(1.0),
+                    ],
+                    // Rough approximation of the Blinn-Phong exponent as a roughness value
+                    roughness: (2.0 / (shininess + 2.0)).sqrt(),
+                    base_color_texture: texture(material.diffuse_texture),
+                    normal_texture: texture(material.normal_texture),
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        instance.finish(!has_normals, has_uvs)
+    }
+
+    fn from_gltf(
+        document: gltf::Document,
+        buffers: Vec<gltf::buffer::Data>,
+        images: Vec<gltf::image::Data>,
+    ) -> Result<Self, MeshError> {
+        let mut instance = Self::default();
+        let mut has_normals = true;
+        let mut has_uvs = true;
+        let mut has_tangents = true;
+
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next());
+        let mut nodes: Vec<(gltf::Node, glm::Mat4)> = match scene {
+            Some(scene) => scene
+                .nodes()
+                .map(|node| (node, glm::Mat4::identity()))
+                .collect(),
+            None => vec![],
+        };
+
+        while let Some((node, parent_transform)) = nodes.pop() {
+            let transform = parent_transform * Self::node_transform(&node);
+            let normal_matrix = glm::transpose(&glm::inverse(&glm::mat4_to_mat3(&transform)));
+            // A negative scale mirrors the mesh, which flips its winding and bitangents
+            let mirrored = glm::determinant(&transform) < 0.0;
+
+            if let Some(mesh) = node.mesh() {
+                for primitive in mesh.primitives() {
+                    if primitive.mode() != gltf::mesh::Mode::Triangles {
+                        instance.warnings.push(format!(
+                            "Skipped a {:?} primitive of mesh '{}', only triangles are supported",
+                            primitive.mode(),
+                            mesh.name().unwrap_or_default()
+                        ));
+                        continue;
+                    }
+
+                    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+                    let positions: Vec<[f32; 3]> = match reader.read_positions() {
+                        Some(positions) => positions.collect(),
+                        None => continue,
+                    };
+                    let normals: Option<Vec<[f32; 3]>> = reader.read_normals().map(|n| n.collect());
+                    let uvs: Option<Vec<[f32; 2]>> =
+                        reader.read_tex_coords(0).map(|uv| uv.into_f32().collect());
+                    let tangents: Option<Vec<[f32; 4]>> =
+                        reader.read_tangents().map(|t| t.collect());
+
+                    has_normals &= normals.is_some();
+                    has_uvs &= uvs.is_some();
+                    has_tangents &= tangents.is_some();
+
+                    let base_vertex = instance.vertices.len() as u32;
+                    for (i, position) in positions.iter().enumerate() {
+                        let position =
+                            transform * glm::vec4(position[0], position[1], position[2], 1.0);
+                        let normal = normals
+                            .as_ref()
+                            .map(|n| glm::normalize(&(normal_matrix * glm::Vec3::from(n[i]))))
+                            .unwrap_or_else(glm::Vec3::zeros);
+                        let uv = uvs.as_ref().map(|uv| uv[i]).unwrap_or([0.0, 0.0]);
+                        let tangent = tangents
+                            .as_ref()
+                            .map(|t| {
+                                let xyz = glm::mat4_to_mat3(&transform)
+                                    * glm::vec3(t[i][0], t[i][1], t[i][2]);
+                                let xyz = glm::normalize(&xyz);
+                                let w = if mirrored { -t[i][3] } else { t[i][3] };
+                                [xyz.x, xyz.y, xyz.z, w]
+                            })
+                            .unwrap_or([0.0, 0.0, 0.0, 1.0]);
+
+                        instance.vertices.push(MeshVertex {
+                            position: [position.x, position.y, position.z],
+                            normal: normal.into(),
+                            uv: [uv[0], 1.0 - uv[1]],
+                            tangent,
+                        });
+                    }
+
+                    let mut indices: Vec<u32> = match reader.read_indices() {
+                        Some(indices) => indices.into_u32().collect(),
+                        None => (0..positions.len() as u32).collect(),
+                    };
+                    if mirrored {
+                        indices.reverse();
+                    }
+                    instance.push_submesh(
+                        mesh.name().unwrap_or_default().to_string(),
+                        indices.into_iter().map(|i| i + base_vertex),
+                        primitive.material().index(),
+                    );
+                }
+            }
+
+            nodes.extend(node.children().map(|child| (child, transform)));
+        }
+
+        instance.materials = document
+            .materials()
+            .map(|material| {
+                let pbr = material.pbr_metallic_roughness();
+                let image =
+                    |texture: gltf::Texture| MaterialTexture::Image(texture.source().index());
+
+                MeshMaterial {
+                    name: material.name().unwrap_or_default().to_string(),
+                    base_color: pbr.base_color_factor(),
+                    metallic: pbr.metallic_factor(),
+                    roughness: pbr.roughness_factor(),
+                    emissive: material.emissive_factor(),
+                    base_color_texture: pbr.base_color_texture().map(|t| image(t.texture())),
+                    normal_texture: material.normal_texture().map(|t| image(t.texture())),
+                    metallic_roughness_texture: pbr
+                        .metallic_roughness_texture()
+                        .map(|t| image(t.texture())),
+                    occlusion_texture: material.occlusion_texture().map(|t| image(t.texture())),
+                    emissive_texture: material.emissive_texture().map(|t| image(t.texture())),
+                }
+            })
+            .collect();
+
+        instance.images = images.into_iter().map(Self::gltf_image_to_rgba).collect();
+
+        let recompute_tangents = has_uvs && !(has_tangents && has_normals);
+        instance.finish(!has_normals, recompute_tangents)
+    }
+
+    fn node_transform(node: &gltf::Node) -> glm::Mat4 {
+        let columns = node.transform().matrix();
+        glm::Mat4::from_fn(|row, column| columns[column][row])
+    }
+
+    fn gltf_image_to_rgba(data: gltf::image::Data) -> RgbaImage {
+        use gltf::image::Format;
+
+        let (channels, bytes_per_channel) = match data.format {
+            Format::R8 => (1, 1),
+            Format::R8G8 => (2, 1),
+            Format::R8G8B8 => (3, 1),
+            Format::R8G8B8A8 => (4, 1),
+            Format::R16 => (1, 2),
+            Format::R16G16 => (2, 2),
+            Format::R16G16B16 => (3, 2),
+            Format::R16G16B16A16 => (4, 2),
+            Format::R32G32B32FLOAT => (3, 4),
+            Format::R32G32B32A32FLOAT => (4, 4),
+        };
+
+        let channel = |pixel: &[u8], c: usize| -> u8 {
+            let bytes = &pixel[c * bytes_per_channel..(c + 1) * bytes_per_channel];
+            match bytes_per_channel {
+                1 => bytes[0],
+                // Little endian, keep the most significant byte
+                2 => bytes[1],
+                _ => {
+                    let value = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                    (value.clamp(0.0, 1.0) * 255.0) as u8
+                }
+            }
+        };
+
+        let pixels = data
+            .pixels
+            .chunks_exact(channels * bytes_per_channel)
+            .flat_map(|pixel| match channels {
+                1 => {
+                    let r = channel(pixel, 0);
+                    [r, r, r, 255]
+                }
+                2 => [channel(pixel, 0), channel(pixel, 1), 0, 255],
+                3 => [channel(pixel, 0), channel(pixel, 1), channel(pixel, 2), 255],
+                _ => [
+                    channel(pixel, 0),
+                    channel(pixel, 1),
+                    channel(pixel, 2),
+                    channel(pixel, 3),
+                ],
+            })
+            .collect();
+
+        let image = RgbaImage::from_raw(data.width, data.height, pixels)
+            .unwrap_or_else(|| RgbaImage::new(data.width, data.height));
+        image::imageops::flip_vertical(&image)
+    }
+
+    fn push_submesh(
+        &mut self,
+        name: String,
+        indices: impl Iterator<Item = u32>,
+        material: Option<usize>,
+    ) {
+        let index_offset = self.indices.len();
+        self.indices.extend(indices);
+        let index_count = self.indices.len() - index_offset;
+
+        if index_count > 0 {
+            self.submeshes.push(Submesh {
+                name,
+                index_offset,
+                index_count,
+                material,
+            });
+        }
+    }
+
+    fn finish(mut self, compute_normals: bool, compute_tangents: bool) -> Result<Self, MeshError> {
+        if self.indices.is_empty() {
+            return Err(MeshError::Empty);
+        }
+        if compute_normals {
+            self.compute_normals();
+        }
+        if compute_tangents {
+            self.compute_tangents();
+        }
+        Ok(self)
+    }
+
+    /// Smooth, area weighted vertex normals from the triangle faces
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![glm::Vec3::zeros(); self.vertices.len()];
+
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| i as usize);
+            let (pa, pb, pc) = (
+                glm::Vec3::from(self.vertices[a].position),
+                glm::Vec3::from(self.vertices[b].position),
+                glm::Vec3::from(self.vertices[c].position),
+            );
+            let face_normal = glm::cross(&(pb - pa), &(pc - pa));
+            for i in [a, b, c] {
+                normals[i] += face_normal;
+            }
+        }
+
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            let normal = if glm::length(&normal) > 0.0 {
+                glm::normalize(&normal)
+            } else {
+                glm::vec3(0.0, 1.0, 0.0)
+            };
+            vertex.normal = normal.into();
+        }
+    }
+
+    /// Per vertex tangents from positions and texture coordinates, orthogonalized against the normal
+    pub fn compute_tangents(&mut self) {
+        let mut tangents = vec![glm::Vec3::zeros(); self.vertices.len()];
+        let mut bitangents = vec![glm::Vec3::zeros(); self.vertices.len()];
+
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| i as usize);
+            let (va, vb, vc) = (self.vertices[a], self.vertices[b], self.vertices[c]);
+
+            let edge_1 = glm::Vec3::from(vb.position) - glm::Vec3::from(va.position);
+            let edge_2 = glm::Vec3::from(vc.position) - glm::Vec3::from(va.position);
+            let delta_uv_1 = glm::Vec2::from(vb.uv) - glm::Vec2::from(va.uv);
+            let delta_uv_2 = glm::Vec2::from(vc.uv) - glm::Vec2::from(va.uv);
+
+            let determinant = delta_uv_1.x * delta_uv_2.y - delta_uv_2.x * delta_uv_1.y;
+            if determinant.abs() <= f32::EPSILON {
+                continue;
+            }
+            let r = 1.0 / determinant;
+            let tangent = (edge_1 * delta_uv_2.y - edge_2 * delta_uv_1.y) * r;
+            let bitangent = (edge_2 * delta_uv_1.x - edge_1 * delta_uv_2.x) * r;
+
+            for i in [a, b, c] {
+                tangents[i] += tangent;
+                bitangents[i] += bitangent;
+            }
+        }
+
+        for (i, vertex) in self.vertices.iter_mut().enumerate() {
+            let normal = glm::Vec3::from(vertex.normal);
+            let tangent = tangents[i] - normal * glm::dot(&normal, &tangents[i]);
+            let tangent = if glm::length(&tangent) > f32::EPSILON {
+                glm::normalize(&tangent)
+            } else {
+                // Any vector perpendicular to the normal keeps normal mapping well defined
+                let axis = if normal.x.abs() < 0.9 {
+                    glm::vec3(1.0, 0.0, 0.0)
+                } else {
+                    glm::vec3(0.0, 1.0, 0.0)
+                };
+                glm::normalize(&glm::cross(&normal, &axis))
+            };
+            let handedness = if glm::dot(&glm::cross(&normal, &tangent), &bitangents[i]) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            vertex.tangent = [tangent.x, tangent.y, tangent.z, handedness];
+        }
+    }
+
+    /// Axis aligned bounding box as `(min, max)`
+    pub fn bounds(&self) -> (glm::Vec3, glm::Vec3) {
+        let mut min = glm::vec3(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = glm::vec3(f32::MIN, f32::MIN, f32::MIN);
+        for vertex in &self.vertices {
+            let position = glm::Vec3::from(vertex.position);
+            min = glm::min2(&min, &position);
+            max = glm::max2(&max, &position);
+        }
+        (min, max)
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Axis aligned cube centered on the origin with per face normals and texture coordinates
+    pub fn cube(size: f32) -> Self {
+        let h = size / 2.0;
+        let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ];
+
+        let mut instance = Self::default();
+        for (normal, right, up) in faces {
+            let (normal, right, up) = (
+                glm::Vec3::from(normal),
+                glm::Vec3::from(right),
+                glm::Vec3::from(up),
+            );
+            let base_vertex = instance.vertices.len() as u32;
+            for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                let position = (normal + right * (u * 2.0 - 1.0) + up * (v * 2.0 - 1.0)) * h;
+                instance.vertices.push(MeshVertex {
+                    position: position.into(),
+                    normal: normal.into(),
+                    uv: [u, v],
+                    tangent: [right.x, right.y, right.z, 1.0],
+                });
+            }
+            instance
+                .indices
+                .extend([0, 1, 2, 2, 3, 0].map(|i| i + base_vertex));
+        }
+
+        instance.submeshes.push(Submesh {
+            name: "cube".into(),
+            index_offset: 0,
+            index_count: instance.indices.len(),
+            material: None,
+        });
+        instance
+    }
+
+    /// Flat square on the xz plane facing up, `uv_scale` repeats the texture across it
+    pub fn plane(size: f32, uv_scale: f32) -> Self {
+        let h = size / 2.0;
+        let vertex = |x: f32, z: f32, u: f32, v: f32| MeshVertex {
+            position: [x, 0.0, z],
+            normal: [0.0, 1.0, 0.0],
+            uv: [u * uv_scale, v * uv_scale],
+            tangent: [1.0, 0.0, 0.0, 1.0],
+        };
+
+        Self {
+            vertices: vec![
+                vertex(-h, h, 0.0, 0.0),
+                vertex(h, h, 1.0, 0.0),
+                vertex(h, -h, 1.0, 1.0),
+                vertex(-h, -h, 0.0, 1.0),
+            ],
+            indices: vec![0, 1, 2, 2, 3, 0],
+            submeshes: vec![Submesh {
+                name: "plane".into(),
+                index_offset: 0,
+                index_count: 6,
+                material: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// UV sphere centered on the origin, `segments` around the equator and `rings` from pole
+    /// to pole
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let (segments, rings) = (segments.max(3), rings.max(2));
+        let mut instance = Self::default();
+
+        for ring in 0..=rings {
+            let phi = std::f32::consts::PI * ring as f32 / rings as f32;
+            for segment in 0..=segments {
+                let theta = std::f32::consts::TAU * segment as f32 / segments as f32;
+                let normal = glm::vec3(theta.cos() * phi.sin(), phi.cos(), theta.sin() * phi.sin());
+                instance.vertices.push(MeshVertex {
+                    position: (normal * radius).into(),
+                    normal: normal.into(),
+                    // u runs against theta so the texture isn't mirrored seen from outside
+                    uv: [
+                        1.0 - segment as f32 / segments as f32,
+                        1.0 - ring as f32 / rings as f32,
+                    ],
+                    tangent: [theta.sin(), 0.0, -theta.cos(), 1.0],
+                });
+            }
+        }
+
+        let stride = segments + 1;
+        for ring in 0..rings {
+            for segment in 0..segments {
+                let a = ring * stride + segment;
+                let b = a + stride;
+                instance.indices.extend([a, a + 1, b, a + 1, b + 1, b]);
+            }
+        }
+
+        instance.submeshes.push(Submesh {
+            name: "sphere".into(),
+            index_offset: 0,
+            index_count: instance.indices.len(),
+            material: None,
+        });
+        instance
+    }
+
+    pub fn upload(&self) -> GpuMesh {
+        let mut vao = VertexArray::new();
+        let vbo = VertexBuffer::new(&self.vertices);
+        let ibo = IndexBuffer::new(&self.indices);
+        vao.add_buffer(&vbo, &MeshVertex::layout());
+
+        vao.unbind();
+        vbo.unbind();
+        ibo.unbind();
+
+        GpuMesh {
+            vao,
+            vbo,
+            ibo,
+            submeshes: self.submeshes.clone(),
+        }
+    }
+}
+
+///
+/// A [`Mesh`] uploaded into a [`VertexArray`] with its vertex and index buffers
+///
+/// The vertex layout is position (location 0), normal (1), uv (2) and tangent (3).
+///
+pub struct GpuMesh {
+    vao: VertexArray,
+    #[allow(dead_code)]
+    vbo: VertexBuffer,
+    ibo: IndexBuffer,
+    submeshes: Vec<Submesh>,
+}
+
+impl GpuMesh {
+    pub fn vertex_array(&self) -> &VertexArray {
+        &self.vao
+    }
+
+    pub fn index_buffer(&self) -> &IndexBuffer {
+        &self.ibo
+    }
+
+    pub fn submeshes(&self) -> &[Submesh] {
+        &self.submeshes
+    }
+
+    pub fn draw(&self, renderer: &Renderer, material: &Material) {
+        renderer.draw(&self.vao, &self.ibo, material);
+    }
+
+    pub fn draw_submesh(&self, renderer: &Renderer, material: &Material, index: usize) {
+        let submesh = &self.submeshes[index];
+        renderer.draw_range(
+            &self.vao,
+            &self.ibo,
+            material,
+            submesh.index_offset,
+            submesh.index_count,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUAD_AND_TRIANGLE: &str = "
+mtllib materials.mtl
+o quad
+v -1 -1 0
+v 1 -1 0
+v 1 1 0
+v -1 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+usemtl red
+f 1/1/1 2/2/1 3/3/1 4/4/1
+o triangle
+v 0 0 1
+v 1 0 1
+v 0 1 1
+usemtl blue
+f 5/1/1 6/2/1 7/4/1
+";
+
+    const MATERIALS: &str = "
+newmtl red
+Kd 1 0 0
+newmtl blue
+Kd 0 0 1
+d 0.5
+map_Kd blue.png
+";
+
+    /// One triangle drawn by two primitives, the second without indices, in a node moved
+    /// along z. Positions and uvs only, normals and tangents are computed
+    const TRIANGLE_GLTF: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0, "translation": [0, 0, 2] }],
+        "meshes": [{
+            "name": "triangle",
+            "primitives": [
+                { "attributes": { "POSITION": 0, "TEXCOORD_0": 1 }, "indices": 2, "material": 0 },
+                { "attributes": { "POSITION": 0, "TEXCOORD_0": 1 }, "material": 1 }
+            ]
+        }],
+        "materials": [
+            { "name": "red", "pbrMetallicRoughness": { "baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0.5 } },
+            { "name": "blue", "pbrMetallicRoughness": { "baseColorFactor": [0, 0, 1, 1], "roughnessFactor": 0.25 } }
+        ],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] },
+            { "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2" },
+            { "bufferView": 2, "componentType": 5123, "count": 3, "type": "SCALAR" }
+        ],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 36, "byteLength": 24 },
+            { "buffer": 0, "byteOffset": 60, "byteLength": 6 }
+        ],
+        "buffers": [{
+            "byteLength": 68,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAABAAIAAAA="
+        }]
+    }"#;
+
+    /// The triangle with normals and tangents, once as is, once mirrored along x, and a
+    /// point cloud that is skipped
+    const MIRRORED_GLTF: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0, 1, 2] }],
+        "nodes": [
+            { "mesh": 0 },
+            { "mesh": 0, "scale": [-1, 1, 1] },
+            { "mesh": 1 }
+        ],
+        "meshes": [
+            {
+                "name": "triangle",
+                "primitives": [{
+                    "attributes": { "POSITION": 0, "TEXCOORD_0": 1, "NORMAL": 3, "TANGENT": 4 },
+                    "indices": 2
+                }]
+            },
+            { "name": "points", "primitives": [{ "attributes": { "POSITION": 0 }, "mode": 0 }] }
+        ],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] },
+            { "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2" },
+            { "bufferView": 2, "componentType": 5123, "count": 3, "type": "SCALAR" },
+            { "bufferView": 3, "componentType": 5126, "count": 3, "type": "VEC3" },
+            { "bufferView": 4, "componentType": 5126, "count": 3, "type": "VEC4" }
+        ],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 36, "byteLength": 24 },
+            { "buffer": 0, "byteOffset": 60, "byteLength": 6 },
+            { "buffer": 0, "byteOffset": 68, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 104, "byteLength": 48 }
+        ],
+        "buffers": [{
+            "byteLength": 152,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAABAAIAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAgD8AAAAAAAAAAAAAgD8AAIA/AAAAAAAAAAAAAIC/AACAPwAAAAAAAAAAAACAvwAAgD8AAAAAAAAAAAAAgL8="
+        }]
+    }"#;
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        assert!(
+            a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    /// Every triangle turns counter clockwise seen from the side its normals point to
+    fn assert_ccw(mesh: &Mesh) {
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]]
+                .map(|i| glm::Vec3::from(mesh.vertices[i as usize].position));
+            let face = glm::cross(&(b - a), &(c - a));
+            // The poles of a sphere have degenerate triangles
+            if glm::length(&face) < 1e-6 {
+                continue;
+            }
+            let normal = triangle
+                .iter()
+                .map(|&i| glm::Vec3::from(mesh.vertices[i as usize].normal))
+                .sum::<glm::Vec3>();
+            assert!(glm::dot(&face, &normal) > 0.0, "{:?}", triangle);
+        }
+    }
+
+    /// Tangents are unit length, perpendicular to the normal and point along increasing u
+    fn assert_tangents(mesh: &Mesh) {
+        let mut computed = mesh.clone();
+        computed.compute_tangents();
+        for (vertex, expected) in mesh.vertices.iter().zip(&computed.vertices) {
+            let tangent = glm::vec3(vertex.tangent[0], vertex.tangent[1], vertex.tangent[2]);
+            assert!((glm::length(&tangent) - 1.0).abs() < 1e-5);
+            assert!(glm::dot(&tangent, &glm::Vec3::from(vertex.normal)).abs() < 1e-5);
+            assert!(
+                glm::dot(
+                    &tangent,
+                    &glm::vec3(
+                        expected.tangent[0],
+                        expected.tangent[1],
+                        expected.tangent[2]
+                    )
+                ) > 0.99
+            );
+            assert_eq!(vertex.tangent[3], expected.tangent[3]);
+        }
+    }
+
+    #[test]
+    fn obj_objects_become_submeshes() {
+        let mesh = Mesh::from_obj_str(QUAD_AND_TRIANGLE).unwrap();
+
+        assert_eq!(mesh.vertices.len(), 7);
+        assert_eq!(mesh.indices.len(), 9);
+        assert_eq!(mesh.triangle_count(), 3);
+        let submeshes: Vec<_> = mesh
+            .submeshes
+            .iter()
+            .map(|s| (s.name.as_str(), s.index_offset, s.index_count))
+            .collect();
+        assert_eq!(submeshes, [("quad", 0, 6), ("triangle", 6, 3)]);
+        // Material libraries are not loaded from source
+        assert!(mesh.materials.is_empty());
+
+        assert_close(&mesh.vertices[2].uv, &[1.0, 1.0]);
+        assert_close(&mesh.vertices[6].uv, &[0.0, 1.0]);
+        for vertex in &mesh.vertices {
+            assert_close(&vertex.normal, &[0.0, 0.0, 1.0]);
+        }
+        assert_ccw(&mesh);
+        assert_tangents(&mesh);
+        assert_close(&mesh.vertices[0].tangent, &[1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn obj_normals_are_computed_when_missing() {
+        let mesh = Mesh::from_obj_str("v 0 0 0\nv 0 0 1\nv 1 0 0\nf 1 2 3\n").unwrap();
+        for vertex in &mesh.vertices {
+            assert_close(&vertex.normal, &[0.0, 1.0, 0.0]);
+        }
+    }
+
+    #[test]
+    fn obj_without_faces_is_empty() {
+        assert!(matches!(
+            Mesh::from_obj_str("v 0 0 0\n"),
+            Err(MeshError::Empty)
+        ));
+    }
+
+    #[test]
+    fn obj_file_with_materials() {
+        let dir = std::env::temp_dir().join(format!("mesh_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("model.obj"), QUAD_AND_TRIANGLE).unwrap();
+        std::fs::write(dir.join("materials.mtl"), MATERIALS).unwrap();
+        let mesh = Mesh::load(dir.join("model.obj"));
+        std::fs::write(
+            dir.join("missing.obj"),
+            QUAD_AND_TRIANGLE.replace("materials.mtl", "missing.mtl"),
+        )
+        .unwrap();
+        let missing = Mesh::load(dir.join("missing.obj"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let mesh = mesh.unwrap();
+        assert!(mesh.warnings.is_empty());
+        let material = |submesh: usize| &mesh.materials[mesh.submeshes[submesh].material.unwrap()];
+        assert_eq!(material(0).name, "red");
+        assert_eq!(material(0).base_color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(material(1).name, "blue");
+        assert_eq!(material(1).base_color, [0.0, 0.0, 1.0, 0.5]);
+        assert_eq!(
+            material(1).base_color_texture,
+            Some(MaterialTexture::Path(dir.join("blue.png")))
+        );
+
+        // A missing library still loads the geometry
+        let missing = missing.unwrap();
+        assert_eq!(missing.triangle_count(), 3);
+        assert!(missing.materials.is_empty());
+        assert_eq!(missing.warnings.len(), 1);
+    }
+
+    #[test]
+    fn unsupported_format() {
+        assert!(matches!(
+            Mesh::load("model.fbx"),
+            Err(MeshError::UnsupportedFormat(extension)) if extension == "fbx"
+        ));
+    }
+
+    #[test]
+    fn gltf_primitives_become_submeshes() {
+        let mesh = Mesh::from_gltf_slice(TRIANGLE_GLTF.as_bytes()).unwrap();
+
+        assert_eq!(mesh.vertices.len(), 6);
+        assert_eq!(mesh.indices, [0, 1, 2, 3, 4, 5]);
+        let submeshes: Vec<_> = mesh
+            .submeshes
+            .iter()
+            .map(|s| (s.index_offset, s.index_count, s.material))
+            .collect();
+        assert_eq!(submeshes, [(0, 3, Some(0)), (3, 3, Some(1))]);
+        assert!(mesh.submeshes.iter().all(|s| s.name == "triangle"));
+
+        assert_eq!(mesh.materials.len(), 2);
+        assert_eq!(mesh.materials[0].name, "red");
+        assert_eq!(mesh.materials[0].base_color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(mesh.materials[0].metallic, 0.5);
+        assert_eq!(mesh.materials[1].roughness, 0.25);
+    }
+
+    #[test]
+    fn gltf_vertices() {
+        let mesh = Mesh::from_gltf_slice(TRIANGLE_GLTF.as_bytes()).unwrap();
+
+        // The node translation is applied and v is flipped
+        assert_close(&mesh.vertices[1].position, &[1.0, 0.0, 2.0]);
+        assert_close(&mesh.vertices[0].uv, &[0.0, 1.0]);
+        assert_close(&mesh.vertices[2].uv, &[0.0, 0.0]);
+        for vertex in &mesh.vertices {
+            assert_close(&vertex.normal, &[0.0, 0.0, 1.0]);
+            assert_close(&vertex.tangent[..3], &[1.0, 0.0, 0.0]);
+        }
+        assert_ccw(&mesh);
+        assert_tangents(&mesh);
+    }
+
+    #[test]
+    fn gltf_mirrored_nodes() {
+        let mesh = Mesh::from_gltf_slice(MIRRORED_GLTF.as_bytes()).unwrap();
+
+        assert_eq!(mesh.submeshes.len(), 2);
+        let corner = |x: f32| {
+            mesh.vertices
+                .iter()
+                .find(|vertex| vertex.position == [x, 0.0, 0.0])
+                .unwrap()
+        };
+        let (original, mirrored) = (corner(1.0), corner(-1.0));
+        assert_close(&mirrored.tangent, &[-1.0, 0.0, 0.0, -original.tangent[3]]);
+        // The winding is reversed so the mirrored triangle still faces its normals
+        assert_ccw(&mesh);
+        assert_tangents(&mesh);
+
+        assert_eq!(mesh.warnings.len(), 1);
+        assert!(mesh.warnings[0].contains("points"));
+    }
+
+    #[test]
+    fn gltf_invalid_input() {
+        assert!(matches!(
+            Mesh::from_gltf_slice(b"{ not gltf"),
+            Err(MeshError::Gltf(_))
+        ));
+    }
+
+    #[test]
+    fn cube() {
+        let mesh = Mesh::cube(2.0);
+        assert_eq!(mesh.vertices.len(), 24);
+        assert_eq!(mesh.triangle_count(), 12);
+        assert_eq!(
+            mesh.bounds(),
+            (glm::vec3(-1.0, -1.0, -1.0), glm::vec3(1.0, 1.0, 1.0))
+        );
+        for vertex in &mesh.vertices {
+            // Normals point away from the center
+            assert!(glm::dot(&vertex.position.into(), &vertex.normal.into()) > 0.0);
+        }
+        assert_ccw(&mesh);
+        assert_tangents(&mesh);
+    }
+
+    #[test]
+    fn plane() {
+        let mesh = Mesh::plane(4.0, 2.0);
+        assert_eq!(mesh.triangle_count(), 2);
+        assert_eq!(
+            mesh.bounds(),
+            (glm::vec3(-2.0, 0.0, -2.0), glm::vec3(2.0, 0.0, 2.0))
+        );
+        assert_close(&mesh.vertices[2].uv, &[2.0, 2.0]);
+        assert_ccw(&mesh);
+        assert_tangents(&mesh);
+    }
+
+    #[test]
+    fn uv_sphere() {
+        let mesh = Mesh::uv_sphere(2.0, 16, 8);
+        assert_eq!(mesh.vertices.len(), 17 * 9);
+        assert_eq!(mesh.indices.len(), 16 * 8 * 6);
+        for vertex in &mesh.vertices {
+            assert!((glm::length(&vertex.position.into()) - 2.0).abs() < 1e-5);
+            assert_close(
+                &vertex.normal,
+                &[
+                    vertex.position[0] / 2.0,
+                    vertex.position[1] / 2.0,
+                    vertex.position[2] / 2.0,
+                ],
+            );
+        }
+        assert_ccw(&mesh);
+
+        // Fewer segments and rings than a closed shape needs are raised
+        let mesh = Mesh::uv_sphere(1.0, 1, 1);
+        assert_eq!(mesh.vertices.len(), 4 * 3);
+    }
+}
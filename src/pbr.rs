@@ -0,0 +1,147 @@
+use std::rc::Rc;
+
+use enum_iterator::Sequence;
+use nalgebra_glm as glm;
+
+use crate::{
+    material::Material,
+    mesh::{MaterialTexture, MeshMaterial},
+    texture::Texture,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence)]
+pub enum ToneMapOperator {
+    /// Only exposure and gamma correction, values above 1.0 clip
+    None,
+    Reinhard,
+    Aces,
+}
+
+impl ToneMapOperator {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToneMapOperator::None => "None",
+            ToneMapOperator::Reinhard => "Reinhard",
+            ToneMapOperator::Aces => "ACES",
+        }
+    }
+}
+
+///
+/// Maps the HDR output of `Pbr.glsl` and `Skybox.glsl` to the screen
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    pub operator: ToneMapOperator,
+    pub exposure: f32,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        Self {
+            operator: ToneMapOperator::Aces,
+            exposure: 1.0,
+        }
+    }
+}
+
+impl ToneMapping {
+    pub fn apply(&self, material: Material) -> Material {
+        material
+            .with_uniform("u_Exposure", self.exposure)
+            .with_uniform("u_ToneMapping", self.operator as i32)
+    }
+}
+
+///
+/// Parameters of the metallic-roughness model used by `Pbr.glsl`, matching glTF materials
+///
+/// Every factor multiplies its map when one is set. The metallic-roughness map stores
+/// roughness in the green and metalness in the blue channel, as in glTF.
+///
+#[derive(Clone)]
+pub struct PbrMaterial {
+    pub albedo: glm::Vec4,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub ambient_occlusion: f32,
+    pub emissive: glm::Vec3,
+    pub normal_scale: f32,
+
+    pub albedo_map: Option<Rc<Texture>>,
+    pub metallic_roughness_map: Option<Rc<Texture>>,
+    pub normal_map: Option<Rc<Texture>>,
+    pub occlusion_map: Option<Rc<Texture>>,
+    pub emissive_map: Option<Rc<Texture>>,
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        Self {
+            albedo: glm::vec4(1.0, 1.0, 1.0, 1.0),
+            metallic: 0.0,
+            roughness: 0.5,
+            ambient_occlusion: 1.0,
+            emissive: glm::vec3(0.0, 0.0, 0.0),
+            normal_scale: 1.0,
+            albedo_map: None,
+            metallic_roughness_map: None,
+            normal_map: None,
+            occlusion_map: None,
+            emissive_map: None,
+        }
+    }
+}
+
+impl PbrMaterial {
+    /// Converts an imported material, `resolve` uploads or looks up each referenced texture
+    pub fn from_mesh_material(
+        material: &MeshMaterial,
+        mut resolve: impl FnMut(&MaterialTexture) -> Option<Rc<Texture>>,
+    ) -> Self {
+        let mut resolve =
+            |texture: &Option<MaterialTexture>| texture.as_ref().and_then(&mut resolve);
+
+        Self {
+            albedo: glm::Vec4::from(material.base_color),
+            metallic: material.metallic,
+            roughness: material.roughness,
+            emissive: glm::Vec3::from(material.emissive),
+            albedo_map: resolve(&material.base_color_texture),
+            metallic_roughness_map: resolve(&material.metallic_roughness_texture),
+            normal_map: resolve(&material.normal_texture),
+            occlusion_map: resolve(&material.occlusion_texture),
+            emissive_map: resolve(&material.emissive_texture),
+            ..Default::default()
+        }
+    }
+
+    pub fn apply(&self, material: Material) -> Material {
+        let mut material = material
+            .with_uniform("u_Albedo", self.albedo)
+            .with_uniform("u_Metallic", self.metallic)
+            .with_uniform("u_Roughness", self.roughness)
+            .with_uniform("u_AmbientOcclusion", self.ambient_occlusion)
+            .with_uniform("u_Emissive", self.emissive)
+            .with_uniform("u_NormalScale", self.normal_scale);
+
+        let maps = [
+            ("u_AlbedoMap", "u_HasAlbedoMap", &self.albedo_map),
+            (
+                "u_MetallicRoughnessMap",
+                "u_HasMetallicRoughnessMap",
+                &self.metallic_roughness_map,
+            ),
+            ("u_NormalMap", "u_HasNormalMap", &self.normal_map),
+            ("u_OcclusionMap", "u_HasOcclusionMap", &self.occlusion_map),
+            ("u_EmissiveMap", "u_HasEmissiveMap", &self.emissive_map),
+        ];
+        for (sampler, flag, texture) in maps {
+            material.set_uniform(flag, texture.is_some() as i32);
+            if let Some(texture) = texture {
+                material.set_texture(sampler, texture.clone());
+            }
+        }
+        material
+    }
+}
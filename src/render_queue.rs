@@ -0,0 +1,338 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    ffi::c_void,
+};
+
+use glcall_macro::gl_call;
+
+use crate::{
+    gl_component::GLComponent,
+    index_buffer::IndexBuffer,
+    material::Material,
+    state_cache::{GlStateCache, StateCacheStats},
+    vertex_array::VertexArray,
+};
+
+const DEPTH_BITS: u32 = 20;
+const TEXTURE_BITS: u32 = 12;
+const MATERIAL_BITS: u32 = 12;
+const SHADER_BITS: u32 = 12;
+
+const DEPTH_SHIFT: u32 = 0;
+const TEXTURE_SHIFT: u32 = DEPTH_SHIFT + DEPTH_BITS;
+const MATERIAL_SHIFT: u32 = TEXTURE_SHIFT + TEXTURE_BITS;
+const SHADER_SHIFT: u32 = MATERIAL_SHIFT + MATERIAL_BITS;
+const LAYER_SHIFT: u32 = SHADER_SHIFT + SHADER_BITS;
+
+/// The bits of `value` that fit in a field of `bits`
+fn field(value: u32, bits: u32) -> u64 {
+    (value & ((1 << bits) - 1)) as u64
+}
+
+///
+/// Packs the layer, shader, material, texture and depth of a draw into one integer, so sorting
+/// the keys orders draws by layer first and groups those sharing state
+///
+/// Ids wider than their field are truncated, which only makes the grouping less exact. Depth
+/// is expected in `0.0..=1.0` and sorts front to back unless `back_to_front` is set, which also
+/// moves it before the shader so blended draws are ordered correctly.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct SortKey(u64);
+
+impl SortKey {
+    pub fn with_layer(self, layer: u8) -> Self {
+        self.with_field(layer as u32, LAYER_SHIFT, 8)
+    }
+
+    pub fn with_shader(self, shader: u32) -> Self {
+        self.with_field(shader, SHADER_SHIFT, SHADER_BITS)
+    }
+
+    pub fn with_material(self, material: u32) -> Self {
+        self.with_field(material, MATERIAL_SHIFT, MATERIAL_BITS)
+    }
+
+    pub fn with_texture(self, texture: u32) -> Self {
+        self.with_field(texture, TEXTURE_SHIFT, TEXTURE_BITS)
+    }
+
+    pub fn with_depth(self, depth: f32) -> Self {
+        let max = (1 << DEPTH_BITS) - 1;
+        let depth = (depth.clamp(0.0, 1.0) * max as f32) as u32;
+        self.with_field(depth, DEPTH_SHIFT, DEPTH_BITS)
+    }
+
+    ///
+    /// Farthest first, for blended draws. The depth takes the place of the shader, material
+    /// and texture, which are only used to order draws at the same depth
+    ///
+    pub fn back_to_front(self) -> Self {
+        let depth =
+            field(u32::MAX, DEPTH_BITS) - (self.0 >> DEPTH_SHIFT & field(u32::MAX, DEPTH_BITS));
+        let state = self.0 >> TEXTURE_SHIFT & ((1 << (LAYER_SHIFT - TEXTURE_SHIFT)) - 1);
+        let layer = self.0 >> LAYER_SHIFT;
+        Self(layer << LAYER_SHIFT | depth << (LAYER_SHIFT - DEPTH_BITS) | state)
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    fn with_field(self, value: u32, shift: u32, bits: u32) -> Self {
+        let mask = field(u32::MAX, bits) << shift;
+        Self(self.0 & !mask | field(value, bits) << shift)
+    }
+}
+
+/// One draw of a `RenderQueue`, `range` is the first index and count, `None` draws every index
+struct DrawCommand<'a> {
+    key: SortKey,
+    vertex_array: &'a VertexArray,
+    index_buffer: &'a IndexBuffer,
+    material: &'a Material,
+    range: Option<(usize, usize)>,
+}
+
+/// What executing a `RenderQueue` cost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderStats {
+    pub draw_calls: usize,
+    /// How often a different material had to be applied
+    pub material_changes: usize,
+    pub binds: StateCacheStats,
+}
+
+///
+/// Collects draws for a frame, sorts them by their `SortKey` and executes them with a
+/// `GlStateCache`, so draws sharing a shader, vertex array or texture do not bind it again
+///
+/// Keys are built from the layer, shader, material, first texture and depth of each draw.
+/// Instances of the same material sort together. Layers marked `back_to_front` are ordered
+/// by depth first, for blended geometry.
+///
+#[derive(Default)]
+pub struct RenderQueue<'a> {
+    commands: Vec<DrawCommand<'a>>,
+    materials: HashMap<*const Material, u32>,
+    back_to_front: BTreeSet<u8>,
+}
+
+impl<'a> RenderQueue<'a> {
+    /// Draws of `layer` are ordered farthest first instead of by state
+    pub fn with_back_to_front(mut self, layer: u8) -> Self {
+        self.back_to_front.insert(layer);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+        self.materials.clear();
+    }
+
+    /// The key `submit` gives a draw of `material` at `depth` in `layer`
+    pub fn key(&mut self, layer: u8, depth: f32, material: &Material) -> SortKey {
+        let next = self.materials.len() as u32;
+        let material_id = *self
+            .materials
+            .entry(material.root() as *const Material)
+            .or_insert(next);
+        let texture = material
+            .resolved_textures()
+            .values()
+            .next()
+            .map_or(0, |texture| texture.renderer_id());
+
+        let key = SortKey::default()
+            .with_layer(layer)
+            .with_shader(material.shader().renderer_id())
+            .with_material(material_id)
+            .with_texture(texture)
+            .with_depth(depth);
+        if self.back_to_front.contains(&layer) {
+            key.back_to_front()
+        } else {
+            key
+        }
+    }
+
+    /// Draws every index of `ib`, `depth` is in `0.0..=1.0` with zero nearest to the camera
+    pub fn submit(
+        &mut self,
+        layer: u8,
+        depth: f32,
+        va: &'a VertexArray,
+        ib: &'a IndexBuffer,
+        material: &'a Material,
+    ) {
+        let key = self.key(layer, depth, material);
+        self.submit_with_key(key, va, ib, material, None);
+    }
+
+    /// Draws `count` indices of `ib` starting at `first_index`
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit_range(
+        &mut self,
+        layer: u8,
+        depth: f32,
+        va: &'a VertexArray,
+        ib: &'a IndexBuffer,
+        material: &'a Material,
+        first_index: usize,
+        count: usize,
+    ) {
+        let key = self.key(layer, depth, material);
+        self.submit_with_key(key, va, ib, material, Some((first_index, count)));
+    }
+
+    /// Draws with a key built by the caller, `range` as in `submit_range`
+    pub fn submit_with_key(
+        &mut self,
+        key: SortKey,
+        va: &'a VertexArray,
+        ib: &'a IndexBuffer,
+        material: &'a Material,
+        range: Option<(usize, usize)>,
+    ) {
+        self.commands.push(DrawCommand {
+            key,
+            vertex_array: va,
+            index_buffer: ib,
+            material,
+            range,
+        });
+    }
+
+    ///
+    /// Sorts and draws every submitted command, leaving the queue empty. Draws with equal keys
+    /// keep the order they were submitted in
+    ///
+    pub fn execute(&mut self, cache: &GlStateCache) -> RenderStats {
+        self.commands.sort_by_key(|command| command.key);
+        cache.invalidate_bindings();
+        cache.reset_stats();
+
+        let mut stats = RenderStats::default();
+        let mut applied: Option<*const Material> = None;
+        for command in self.commands.drain(..) {
+            // Same material as the last draw, so its uniforms are still set
+            if applied != Some(command.material as *const Material) {
+                command.material.apply_cached(cache);
+                applied = Some(command.material);
+                stats.material_changes += 1;
+            }
+            cache.bind_vertex_array(command.vertex_array.renderer_id());
+            cache.bind_index_buffer(command.index_buffer.renderer_id());
+
+            let (first_index, count) = command.range.unwrap_or((0, command.index_buffer.count()));
+            gl_call!({
+                gl::DrawElements(
+                    gl::TRIANGLES,
+                    count as i32,
+                    gl::UNSIGNED_INT,
+                    (first_index * std::mem::size_of::<u32>()) as *const c_void,
+                );
+            });
+            stats.draw_calls += 1;
+        }
+        self.materials.clear();
+
+        stats.binds = cache.stats();
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut keys: Vec<(SortKey, &str)>) -> Vec<&str> {
+        keys.sort_by_key(|&(key, _)| key);
+        keys.into_iter().map(|(_, name)| name).collect()
+    }
+
+    #[test]
+    fn layer_dominates() {
+        let background = SortKey::default()
+            .with_layer(0)
+            .with_shader(4095)
+            .with_material(4095)
+            .with_texture(4095)
+            .with_depth(1.0);
+        let foreground = SortKey::default().with_layer(1);
+        assert!(background < foreground);
+        assert!(background.back_to_front() < foreground.back_to_front());
+    }
+
+    #[test]
+    fn state_groups_before_depth() {
+        let key = |shader, depth| SortKey::default().with_shader(shader).with_depth(depth);
+        let keys = vec![
+            (key(2, 0.1), "b near"),
+            (key(1, 0.9), "a far"),
+            (key(2, 0.5), "b mid"),
+            (key(1, 0.2), "a near"),
+        ];
+        assert_eq!(sorted(keys), ["a near", "a far", "b near", "b mid"]);
+    }
+
+    #[test]
+    fn depth_front_to_back() {
+        let key = |depth| SortKey::default().with_layer(2).with_depth(depth);
+        assert!(key(0.1) < key(0.2));
+        assert!(key(0.0) < key(1.0));
+        // Outside 0..1 is clamped
+        assert_eq!(key(-1.0), key(0.0));
+        assert_eq!(key(2.0), key(1.0));
+    }
+
+    #[test]
+    fn back_to_front_reverses_depth() {
+        let key = |shader, depth| {
+            SortKey::default()
+                .with_layer(3)
+                .with_shader(shader)
+                .with_depth(depth)
+                .back_to_front()
+        };
+        let keys = vec![
+            (key(1, 0.2), "a near"),
+            (key(2, 0.9), "b far"),
+            (key(1, 0.5), "a mid"),
+            (key(2, 0.5), "b mid"),
+        ];
+        // Depth comes before the state, which only orders equal depths
+        assert_eq!(sorted(keys), ["b far", "a mid", "b mid", "a near"]);
+        assert_eq!(key(1, 0.5).value() >> LAYER_SHIFT, 3);
+    }
+
+    #[test]
+    fn wide_ids_are_truncated() {
+        let key = SortKey::default()
+            .with_layer(1)
+            .with_shader(1 << SHADER_BITS | 5);
+        assert_eq!(key, SortKey::default().with_layer(1).with_shader(5));
+
+        // Truncated ids don't spill into the neighbouring fields
+        let key = SortKey::default()
+            .with_texture(u32::MAX)
+            .with_material(u32::MAX);
+        assert_eq!(key.value() >> SHADER_SHIFT, 0);
+        assert_eq!(key.value() & field(u32::MAX, DEPTH_BITS), 0);
+    }
+
+    #[test]
+    fn with_field_replaces_the_previous_value() {
+        let key = SortKey::default().with_material(7).with_material(3);
+        assert_eq!(key, SortKey::default().with_material(3));
+        assert_eq!(key.value(), 3 << MATERIAL_SHIFT);
+    }
+}
@@ -0,0 +1,532 @@
+use glcall_macro::gl_call;
+
+use crate::renderer::CullFace;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendFactor {
+    Zero,
+    One,
+    SrcColor,
+    OneMinusSrcColor,
+    DstColor,
+    OneMinusDstColor,
+    SrcAlpha,
+    OneMinusSrcAlpha,
+    DstAlpha,
+    OneMinusDstAlpha,
+}
+
+impl BlendFactor {
+    fn gl_enum(&self) -> u32 {
+        match self {
+            BlendFactor::Zero => gl::ZERO,
+            BlendFactor::One => gl::ONE,
+            BlendFactor::SrcColor => gl::SRC_COLOR,
+            BlendFactor::OneMinusSrcColor => gl::ONE_MINUS_SRC_COLOR,
+            BlendFactor::DstColor => gl::DST_COLOR,
+            BlendFactor::OneMinusDstColor => gl::ONE_MINUS_DST_COLOR,
+            BlendFactor::SrcAlpha => gl::SRC_ALPHA,
+            BlendFactor::OneMinusSrcAlpha => gl::ONE_MINUS_SRC_ALPHA,
+            BlendFactor::DstAlpha => gl::DST_ALPHA,
+            BlendFactor::OneMinusDstAlpha => gl::ONE_MINUS_DST_ALPHA,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendOp {
+    Add,
+    Subtract,
+    ReverseSubtract,
+    Min,
+    Max,
+}
+
+impl BlendOp {
+    fn gl_enum(&self) -> u32 {
+        match self {
+            BlendOp::Add => gl::FUNC_ADD,
+            BlendOp::Subtract => gl::FUNC_SUBTRACT,
+            BlendOp::ReverseSubtract => gl::FUNC_REVERSE_SUBTRACT,
+            BlendOp::Min => gl::MIN,
+            BlendOp::Max => gl::MAX,
+        }
+    }
+}
+
+///
+/// How fragment colors are combined with the framebuffer, `source * src + destination * dst`
+/// for the default `Add` operation
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlendState {
+    pub enabled: bool,
+    pub src_color: BlendFactor,
+    pub dst_color: BlendFactor,
+    pub src_alpha: BlendFactor,
+    pub dst_alpha: BlendFactor,
+    pub op: BlendOp,
+}
+
+impl Default for BlendState {
+    fn default() -> Self {
+        Self::alpha()
+    }
+}
+
+impl BlendState {
+    /// Blending disabled, fragments replace what is in the framebuffer
+    pub fn opaque() -> Self {
+        Self {
+            enabled: false,
+            ..Self::new(BlendFactor::One, BlendFactor::Zero)
+        }
+    }
+
+    /// Straight alpha, the default
+    pub fn alpha() -> Self {
+        Self::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha)
+    }
+
+    /// Adds the color weighted by its alpha, e.g. for glows and particles
+    pub fn additive() -> Self {
+        Self::new(BlendFactor::SrcAlpha, BlendFactor::One)
+    }
+
+    /// For colors already multiplied by their alpha
+    pub fn premultiplied() -> Self {
+        Self::new(BlendFactor::One, BlendFactor::OneMinusSrcAlpha)
+    }
+
+    /// Enabled blending with the same factors for color and alpha
+    pub fn new(src: BlendFactor, dst: BlendFactor) -> Self {
+        Self {
+            enabled: true,
+            src_color: src,
+            dst_color: dst,
+            src_alpha: src,
+            dst_alpha: dst,
+            op: BlendOp::Add,
+        }
+    }
+
+    pub fn with_alpha_factors(mut self, src: BlendFactor, dst: BlendFactor) -> Self {
+        self.src_alpha = src;
+        self.dst_alpha = dst;
+        self
+    }
+
+    pub fn with_op(mut self, op: BlendOp) -> Self {
+        self.op = op;
+        self
+    }
+
+    pub(crate) fn apply(&self) {
+        if !self.enabled {
+            gl_call!({
+                gl::Disable(gl::BLEND);
+            });
+            return;
+        }
+        gl_call!({
+            gl::Enable(gl::BLEND);
+            gl::BlendFuncSeparate(
+                self.src_color.gl_enum(),
+                self.dst_color.gl_enum(),
+                self.src_alpha.gl_enum(),
+                self.dst_alpha.gl_enum(),
+            );
+            gl::BlendEquation(self.op.gl_enum());
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareFunc {
+    Never,
+    Less,
+    Equal,
+    LessEqual,
+    Greater,
+    NotEqual,
+    GreaterEqual,
+    Always,
+}
+
+impl CompareFunc {
+    fn gl_enum(&self) -> u32 {
+        match self {
+            CompareFunc::Never => gl::NEVER,
+            CompareFunc::Less => gl::LESS,
+            CompareFunc::Equal => gl::EQUAL,
+            CompareFunc::LessEqual => gl::LEQUAL,
+            CompareFunc::Greater => gl::GREATER,
+            CompareFunc::NotEqual => gl::NOTEQUAL,
+            CompareFunc::GreaterEqual => gl::GEQUAL,
+            CompareFunc::Always => gl::ALWAYS,
+        }
+    }
+}
+
+///
+/// The depth test and whether passing fragments write their depth
+///
+/// The default disables the test, like the application does at startup.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthState {
+    pub test: bool,
+    pub write: bool,
+    pub func: CompareFunc,
+}
+
+impl Default for DepthState {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl DepthState {
+    pub fn disabled() -> Self {
+        Self {
+            test: false,
+            write: true,
+            func: CompareFunc::Less,
+        }
+    }
+
+    /// Nearer fragments pass and write their depth
+    pub fn enabled() -> Self {
+        Self {
+            test: true,
+            ..Self::disabled()
+        }
+    }
+
+    /// Tested but not written
+    pub fn read_only() -> Self {
+        Self::enabled().with_write(false)
+    }
+
+    pub fn with_func(mut self, func: CompareFunc) -> Self {
+        self.func = func;
+        self
+    }
+
+    pub fn with_write(mut self, write: bool) -> Self {
+        self.write = write;
+        self
+    }
+
+    pub(crate) fn apply(&self) {
+        gl_call!({
+            if self.test {
+                gl::Enable(gl::DEPTH_TEST);
+            } else {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+            gl::DepthFunc(self.func.gl_enum());
+            gl::DepthMask(if self.write { gl::TRUE } else { gl::FALSE });
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StencilOp {
+    Keep,
+    Zero,
+    Replace,
+    Increment,
+    IncrementWrap,
+    Decrement,
+    DecrementWrap,
+    Invert,
+}
+
+impl StencilOp {
+    fn gl_enum(&self) -> u32 {
+        match self {
+            StencilOp::Keep => gl::KEEP,
+            StencilOp::Zero => gl::ZERO,
+            StencilOp::Replace => gl::REPLACE,
+            StencilOp::Increment => gl::INCR,
+            StencilOp::IncrementWrap => gl::INCR_WRAP,
+            StencilOp::Decrement => gl::DECR,
+            StencilOp::DecrementWrap => gl::DECR_WRAP,
+            StencilOp::Invert => gl::INVERT,
+        }
+    }
+}
+
+///
+/// The stencil test, the same for front and back faces
+///
+/// A fragment passes if `func(reference & read_mask, stencil & read_mask)` holds. The ops
+/// update the stencil buffer when the stencil test fails, the depth test fails or both pass.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StencilState {
+    pub enabled: bool,
+    pub func: CompareFunc,
+    pub reference: i32,
+    pub read_mask: u32,
+    pub write_mask: u32,
+    pub stencil_fail: StencilOp,
+    pub depth_fail: StencilOp,
+    pub pass: StencilOp,
+}
+
+impl Default for StencilState {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl StencilState {
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            func: CompareFunc::Always,
+            reference: 0,
+            read_mask: 0xFF,
+            write_mask: 0xFF,
+            stencil_fail: StencilOp::Keep,
+            depth_fail: StencilOp::Keep,
+            pass: StencilOp::Keep,
+        }
+    }
+
+    /// Writes `reference` wherever a fragment is drawn
+    pub fn write(reference: i32) -> Self {
+        Self {
+            enabled: true,
+            reference,
+            pass: StencilOp::Replace,
+            ..Self::disabled()
+        }
+    }
+
+    /// Only draws where the stencil is not `reference`, leaving the stencil buffer unchanged
+    pub fn not_equal(reference: i32) -> Self {
+        Self {
+            enabled: true,
+            func: CompareFunc::NotEqual,
+            reference,
+            write_mask: 0x00,
+            ..Self::disabled()
+        }
+    }
+
+    /// Only draws where the stencil is `reference`, leaving the stencil buffer unchanged
+    pub fn equal(reference: i32) -> Self {
+        Self {
+            func: CompareFunc::Equal,
+            ..Self::not_equal(reference)
+        }
+    }
+
+    pub fn with_masks(mut self, read_mask: u32, write_mask: u32) -> Self {
+        self.read_mask = read_mask;
+        self.write_mask = write_mask;
+        self
+    }
+
+    pub fn with_ops(
+        mut self,
+        stencil_fail: StencilOp,
+        depth_fail: StencilOp,
+        pass: StencilOp,
+    ) -> Self {
+        self.stencil_fail = stencil_fail;
+        self.depth_fail = depth_fail;
+        self.pass = pass;
+        self
+    }
+
+    pub(crate) fn apply(&self) {
+        // The write mask also applies to clearing, so it is set even when the test is disabled
+        gl_call!({
+            gl::StencilMask(self.write_mask);
+        });
+        if !self.enabled {
+            gl_call!({
+                gl::Disable(gl::STENCIL_TEST);
+            });
+            return;
+        }
+        gl_call!({
+            gl::Enable(gl::STENCIL_TEST);
+            gl::StencilFunc(self.func.gl_enum(), self.reference, self.read_mask);
+            gl::StencilOp(
+                self.stencil_fail.gl_enum(),
+                self.depth_fail.gl_enum(),
+                self.pass.gl_enum(),
+            );
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolygonMode {
+    Fill,
+    Line,
+    Point,
+}
+
+impl PolygonMode {
+    fn gl_enum(&self) -> u32 {
+        match self {
+            PolygonMode::Fill => gl::FILL,
+            PolygonMode::Line => gl::LINE,
+            PolygonMode::Point => gl::POINT,
+        }
+    }
+}
+
+///
+/// How triangles are turned into fragments: culling, polygon mode, scissor rectangle and
+/// depth bias
+///
+/// Front faces wind counter-clockwise. The scissor rectangle is `(x, y, width, height)` in
+/// pixels from the bottom left. The depth bias is `(factor, units)` as in `glPolygonOffset`.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RasterizerState {
+    pub cull: Option<CullFace>,
+    pub polygon_mode: PolygonMode,
+    pub scissor: Option<(i32, i32, i32, i32)>,
+    pub depth_bias: Option<(f32, f32)>,
+}
+
+impl Default for RasterizerState {
+    fn default() -> Self {
+        Self {
+            cull: None,
+            polygon_mode: PolygonMode::Fill,
+            scissor: None,
+            depth_bias: None,
+        }
+    }
+}
+
+impl RasterizerState {
+    pub fn wireframe() -> Self {
+        Self::default().with_polygon_mode(PolygonMode::Line)
+    }
+
+    pub fn with_cull(mut self, cull: Option<CullFace>) -> Self {
+        self.cull = cull;
+        self
+    }
+
+    pub fn with_polygon_mode(mut self, polygon_mode: PolygonMode) -> Self {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+
+    pub fn with_scissor(mut self, x: i32, y: i32, width: i32, height: i32) -> Self {
+        self.scissor = Some((x, y, width, height));
+        self
+    }
+
+    pub fn with_depth_bias(mut self, factor: f32, units: f32) -> Self {
+        self.depth_bias = Some((factor, units));
+        self
+    }
+
+    pub(crate) fn apply(&self) {
+        match self.cull {
+            Some(cull_face) => {
+                gl_call!({
+                    gl::Enable(gl::CULL_FACE);
+                    gl::FrontFace(gl::CCW);
+                    gl::CullFace(cull_face.gl_enum());
+                });
+            }
+            None => {
+                gl_call!({
+                    gl::Disable(gl::CULL_FACE);
+                });
+            }
+        }
+
+        gl_call!({
+            gl::PolygonMode(gl::FRONT_AND_BACK, self.polygon_mode.gl_enum());
+        });
+
+        match self.scissor {
+            Some((x, y, width, height)) => {
+                gl_call!({
+                    gl::Enable(gl::SCISSOR_TEST);
+                    gl::Scissor(x, y, width, height);
+                });
+            }
+            None => {
+                gl_call!({
+                    gl::Disable(gl::SCISSOR_TEST);
+                });
+            }
+        }
+
+        let offset_modes = [
+            gl::POLYGON_OFFSET_FILL,
+            gl::POLYGON_OFFSET_LINE,
+            gl::POLYGON_OFFSET_POINT,
+        ];
+        match self.depth_bias {
+            Some((factor, units)) => {
+                gl_call!({
+                    gl::PolygonOffset(factor, units);
+                });
+                for mode in offset_modes {
+                    gl_call!({
+                        gl::Enable(mode);
+                    });
+                }
+            }
+            None => {
+                for mode in offset_modes {
+                    gl_call!({
+                        gl::Disable(mode);
+                    });
+                }
+            }
+        }
+    }
+}
+
+///
+/// Every fixed function state a draw depends on, applied together by `Renderer::set_render_state`
+///
+/// The default matches the state the application starts every frame with: alpha blending, no
+/// depth or stencil test, no culling and filled polygons.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RenderState {
+    pub blend: BlendState,
+    pub depth: DepthState,
+    pub stencil: StencilState,
+    pub rasterizer: RasterizerState,
+}
+
+impl RenderState {
+    pub fn with_blend(mut self, blend: BlendState) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_depth(mut self, depth: DepthState) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_stencil(mut self, stencil: StencilState) -> Self {
+        self.stencil = stencil;
+        self
+    }
+
+    pub fn with_rasterizer(mut self, rasterizer: RasterizerState) -> Self {
+        self.rasterizer = rasterizer;
+        self
+    }
+}
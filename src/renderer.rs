@@ -1,14 +1,41 @@
 use std::ffi::c_void;
 
 use glcall_macro::gl_call;
+use image::RgbaImage;
 use nalgebra_glm::Vec4;
 
 use crate::{
-    gl_component::GLComponent, index_buffer::IndexBuffer, shader::Shader, vertex_array::VertexArray,
+    gl_component::GLComponent,
+    index_buffer::IndexBuffer,
+    material::Material,
+    render_queue::{RenderQueue, RenderStats},
+    render_state::{
+        BlendState, CompareFunc, DepthState, RasterizerState, RenderState, StencilState,
+    },
+    state_cache::GlStateCache,
+    vertex_array::VertexArray,
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CullFace {
+    Front,
+    Back,
+    FrontAndBack,
+}
+
+impl CullFace {
+    pub(crate) fn gl_enum(&self) -> u32 {
+        match self {
+            CullFace::Front => gl::FRONT,
+            CullFace::Back => gl::BACK,
+            CullFace::FrontAndBack => gl::FRONT_AND_BACK,
+        }
+    }
+}
+
 pub struct Renderer {
     clear_color: (f32, f32, f32, f32),
+    state_cache: GlStateCache,
 }
 
 impl From<Vec4> for Renderer {
@@ -24,31 +51,176 @@ impl From<Vec4> for Renderer {
 
 impl Renderer {
     pub fn new(clear_color: (f32, f32, f32, f32)) -> Self {
-        Self { clear_color }
+        Self {
+            clear_color,
+            state_cache: GlStateCache::default(),
+        }
+    }
+
+    /// Bindings made by `execute`, shared by every queue drawn with this renderer
+    pub fn state_cache(&self) -> &GlStateCache {
+        &self.state_cache
     }
 
     pub fn clear(&self) {
         let (r, g, b, a) = self.clear_color;
         gl_call!({
             gl::ClearColor(r, g, b, a);
-            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+        });
+    }
+
+    pub fn clear_depth(&self) {
+        gl_call!({
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+        });
+    }
+
+    pub fn set_viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        gl_call!({
+            gl::Viewport(x, y, width, height);
         });
     }
 
-    pub fn draw(&self, va: &VertexArray, ib: &IndexBuffer, shader: &Shader) {
-        shader.bind();
+    /// Enables the depth test with `CompareFunc::Less`, or disables it, keeping the depth writes
+    pub fn set_depth_test(&self, enabled: bool) {
+        let depth = if enabled {
+            self.depth_state().with_func(CompareFunc::Less)
+        } else {
+            self.depth_state()
+        };
+        self.set_depth_state(&DepthState {
+            test: enabled,
+            ..depth
+        });
+    }
+
+    /// Culls the given faces, `None` disables face culling. Front faces wind counter-clockwise
+    pub fn set_face_culling(&self, cull_face: Option<CullFace>) {
+        self.set_rasterizer_state(&self.rasterizer_state().with_cull(cull_face));
+    }
+
+    pub fn set_blend_state(&self, blend: &BlendState) {
+        self.state_cache.set_blend(blend);
+    }
+
+    pub fn set_depth_state(&self, depth: &DepthState) {
+        self.state_cache.set_depth(depth);
+    }
+
+    pub fn set_stencil_state(&self, stencil: &StencilState) {
+        self.state_cache.set_stencil(stencil);
+    }
+
+    pub fn set_rasterizer_state(&self, rasterizer: &RasterizerState) {
+        self.state_cache.set_rasterizer(rasterizer);
+    }
+
+    /// Sets every state at once, only those that differ from the current ones reach the driver
+    pub fn set_render_state(&self, state: &RenderState) {
+        self.set_blend_state(&state.blend);
+        self.set_depth_state(&state.depth);
+        self.set_stencil_state(&state.stencil);
+        self.set_rasterizer_state(&state.rasterizer);
+    }
+
+    pub fn blend_state(&self) -> BlendState {
+        self.state_cache.blend().unwrap_or_default()
+    }
+
+    pub fn depth_state(&self) -> DepthState {
+        self.state_cache.depth().unwrap_or_default()
+    }
+
+    pub fn stencil_state(&self) -> StencilState {
+        self.state_cache.stencil().unwrap_or_default()
+    }
+
+    pub fn rasterizer_state(&self) -> RasterizerState {
+        self.state_cache.rasterizer().unwrap_or_default()
+    }
+
+    pub fn render_state(&self) -> RenderState {
+        RenderState {
+            blend: self.blend_state(),
+            depth: self.depth_state(),
+            stencil: self.stencil_state(),
+            rasterizer: self.rasterizer_state(),
+        }
+    }
+
+    ///
+    /// Forgets the cached state and applies the default `RenderState`. Called before every
+    /// frame, since imgui changes the state behind the renderer's back
+    ///
+    pub fn reset_state(&self) {
+        self.state_cache.invalidate();
+        self.set_render_state(&RenderState::default());
+    }
+
+    /// Applies `material` and draws every index of `ib`
+    pub fn draw(&self, va: &VertexArray, ib: &IndexBuffer, material: &Material) {
+        material.apply();
         va.bind();
         ib.bind();
         gl_call!({
             gl::DrawElements(
                 gl::TRIANGLES,
-                6,
+                ib.count() as i32,
                 gl::UNSIGNED_INT,
                 std::ptr::null::<c_void>(),
             );
         });
     }
 
+    /// Sorts and draws the commands of `queue`, skipping redundant binds between them
+    pub fn execute(&self, queue: &mut RenderQueue) -> RenderStats {
+        queue.execute(&self.state_cache)
+    }
+
+    /// Reads back the currently bound framebuffer, flipped so the first row is the top of the screen
+    pub fn read_pixels(&self, width: u32, height: u32) -> RgbaImage {
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        gl_call!({
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut c_void,
+            );
+        });
+
+        let image = RgbaImage::from_raw(width, height, pixels)
+            .expect("Framebuffer readback size does not match the requested dimensions");
+        image::imageops::flip_vertical(&image)
+    }
+
+    /// Draws `count` indices starting at `first_index` of the index buffer
+    pub fn draw_range(
+        &self,
+        va: &VertexArray,
+        ib: &IndexBuffer,
+        material: &Material,
+        first_index: usize,
+        count: usize,
+    ) {
+        material.apply();
+        va.bind();
+        ib.bind();
+        gl_call!({
+            gl::DrawElements(
+                gl::TRIANGLES,
+                count as i32,
+                gl::UNSIGNED_INT,
+                (first_index * std::mem::size_of::<u32>()) as *const c_void,
+            );
+        });
+    }
+
     pub fn set_clear_color(&mut self, r: f32, g: f32, b: f32, a: f32) {
         self.clear_color = (r, g, b, a);
     }
@@ -0,0 +1,95 @@
+#shader vertex
+#version 330 core
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 texture_Coord;
+
+out vec2 v_TextureCoord;
+
+void main() {
+    v_TextureCoord = texture_Coord;
+    gl_Position = vec4(position, 0.0, 1.0);
+}
+
+#shader fragment
+#version 330 core
+
+in vec2 v_TextureCoord;
+
+out vec2 Color;
+
+const float PI = 3.14159265359;
+
+float radical_inverse_vdc(uint bits) {
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return float(bits) * 2.3283064365386963e-10;
+}
+
+vec2 hammersley(uint i, uint count) {
+    return vec2(float(i) / float(count), radical_inverse_vdc(i));
+}
+
+vec3 importance_sample_ggx(vec2 xi, vec3 normal, float roughness) {
+    float a = roughness * roughness;
+    float phi = 2.0 * PI * xi.x;
+    float cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    float sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+
+    vec3 halfway = vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+    vec3 up = abs(normal.z) < 0.999 ? vec3(0.0, 0.0, 1.0) : vec3(1.0, 0.0, 0.0);
+    vec3 tangent = normalize(cross(up, normal));
+    vec3 bitangent = cross(normal, tangent);
+    return normalize(tangent * halfway.x + bitangent * halfway.y + normal * halfway.z);
+}
+
+float distribution_ggx(float n_dot_h, float roughness) {
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denominator * denominator);
+}
+
+const uint SAMPLE_COUNT = 1024u;
+
+float geometry_schlick_ggx(float n_dot_v, float roughness) {
+    // IBL uses k = a^2 / 2 rather than the (a + 1)^2 / 8 of direct lighting
+    float k = (roughness * roughness) / 2.0;
+    return n_dot_v / (n_dot_v * (1.0 - k) + k);
+}
+
+// Scale and bias to F0 of the specular BRDF, indexed by n.v and roughness
+void main() {
+    float n_dot_v = v_TextureCoord.x;
+    float roughness = v_TextureCoord.y;
+
+    vec3 view = vec3(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+    vec3 normal = vec3(0.0, 0.0, 1.0);
+
+    float scale = 0.0;
+    float bias = 0.0;
+    for (uint i = 0u; i < SAMPLE_COUNT; i++) {
+        vec2 xi = hammersley(i, SAMPLE_COUNT);
+        vec3 halfway = importance_sample_ggx(xi, normal, roughness);
+        vec3 light = normalize(2.0 * dot(view, halfway) * halfway - view);
+
+        float n_dot_l = max(light.z, 0.0);
+        float n_dot_h = max(halfway.z, 0.0);
+        float v_dot_h = max(dot(view, halfway), 0.0);
+
+        if (n_dot_l > 0.0) {
+            float geometry = geometry_schlick_ggx(n_dot_v, roughness)
+                * geometry_schlick_ggx(n_dot_l, roughness);
+            float visibility = (geometry * v_dot_h) / (n_dot_h * n_dot_v);
+            float fresnel = pow(1.0 - v_dot_h, 5.0);
+
+            scale += (1.0 - fresnel) * visibility;
+            bias += fresnel * visibility;
+        }
+    }
+
+    Color = vec2(scale, bias) / float(SAMPLE_COUNT);
+}
@@ -0,0 +1,31 @@
+#shader vertex
+#version 330 core
+
+layout(location = 0) in vec3 position;
+
+out vec3 v_LocalPosition;
+
+uniform mat4 u_Projection;
+uniform mat4 u_View;
+
+void main() {
+    v_LocalPosition = position;
+    gl_Position = u_Projection * u_View * vec4(position, 1.0);
+}
+
+#shader fragment
+#version 330 core
+
+in vec3 v_LocalPosition;
+
+uniform sampler2D u_EquirectangularMap;
+
+out vec4 Color;
+
+const vec2 INV_ATAN = vec2(0.1591, 0.3183);
+
+void main() {
+    vec3 direction = normalize(v_LocalPosition);
+    vec2 uv = vec2(atan(direction.z, direction.x), asin(direction.y)) * INV_ATAN + 0.5;
+    Color = vec4(texture(u_EquirectangularMap, uv).rgb, 1.0);
+}
@@ -0,0 +1,21 @@
+#shader vertex
+#version 330 core
+
+layout(location = 0) in vec4 position;
+
+uniform mat4 u_MVP;
+
+void main() {
+    gl_Position = u_MVP * position;
+}
+
+#shader fragment
+#version 330 core
+
+uniform vec4 u_Color;
+
+out vec4 Color;
+
+void main() {
+   Color = u_Color;
+}
@@ -0,0 +1,33 @@
+#shader vertex
+#version 450 core
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec4 color;
+layout(location = 2) in vec2 textureCoord;
+
+out vec4 v_Color;
+out vec2 v_TextureCoord;
+
+uniform mat4 u_ViewProjection;
+
+void main() {
+    gl_Position = u_ViewProjection * vec4(position, 0.0, 1.0);
+    v_Color = color;
+    v_TextureCoord = textureCoord;
+}
+
+#shader fragment
+#version 450 core
+
+in vec4 v_Color;
+in vec2 v_TextureCoord;
+
+out vec4 Color;
+
+// Single channel glyph coverage
+uniform sampler2D u_GlyphCache;
+
+void main() {
+    float coverage = texture(u_GlyphCache, v_TextureCoord).r;
+    Color = vec4(v_Color.rgb, v_Color.a * coverage);
+}
@@ -0,0 +1,48 @@
+#shader vertex
+#version 330 core
+
+layout(location = 0) in vec3 position;
+
+out vec3 v_LocalPosition;
+
+uniform mat4 u_Projection;
+uniform mat4 u_View;
+
+void main() {
+    v_LocalPosition = position;
+    gl_Position = u_Projection * u_View * vec4(position, 1.0);
+}
+
+#shader fragment
+#version 330 core
+
+in vec3 v_LocalPosition;
+
+uniform samplerCube u_EnvironmentMap;
+
+out vec4 Color;
+
+const float PI = 3.14159265359;
+
+// Cosine weighted average of the environment over the hemisphere around the normal
+void main() {
+    vec3 normal = normalize(v_LocalPosition);
+    vec3 up = abs(normal.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(0.0, 0.0, 1.0);
+    vec3 right = normalize(cross(up, normal));
+    up = normalize(cross(normal, right));
+
+    vec3 irradiance = vec3(0.0);
+    float sample_delta = 0.025;
+    float samples = 0.0;
+    for (float phi = 0.0; phi < 2.0 * PI; phi += sample_delta) {
+        for (float theta = 0.0; theta < 0.5 * PI; theta += sample_delta) {
+            vec3 tangent_sample = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            vec3 sample_direction = tangent_sample.x * right + tangent_sample.y * up
+                + tangent_sample.z * normal;
+            irradiance += texture(u_EnvironmentMap, sample_direction).rgb * cos(theta) * sin(theta);
+            samples++;
+        }
+    }
+
+    Color = vec4(PI * irradiance / samples, 1.0);
+}
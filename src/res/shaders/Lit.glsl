@@ -0,0 +1,217 @@
+#shader vertex
+#version 330 core
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 texture_Coord;
+
+out vec3 v_WorldPosition;
+out vec3 v_Normal;
+out vec2 v_TextureCoord;
+out float v_ViewDepth;
+
+uniform mat4 u_Model;
+uniform mat4 u_View;
+uniform mat4 u_ViewProjection;
+uniform mat3 u_NormalMatrix;
+
+void main() {
+    vec4 world_position = u_Model * vec4(position, 1.0);
+    gl_Position = u_ViewProjection * world_position;
+
+    v_WorldPosition = world_position.xyz;
+    v_Normal = u_NormalMatrix * normal;
+    v_TextureCoord = texture_Coord;
+    v_ViewDepth = -(u_View * world_position).z;
+}
+
+#shader fragment
+#version 330 core
+
+#define MAX_POINT_LIGHTS 8
+#define MAX_SPOT_LIGHTS 4
+#define MAX_CASCADES 4
+
+struct PointLight {
+    vec4 position;
+    vec4 color;         // rgb, intensity
+    vec4 attenuation;   // constant, linear, quadratic
+};
+
+struct SpotLight {
+    vec4 position;
+    vec4 direction;     // xyz, cos inner angle
+    vec4 color;         // rgb, intensity
+    vec4 attenuation;   // constant, linear, quadratic, cos outer angle
+};
+
+layout(std140) uniform Lights {
+    vec4 u_Ambient;
+    vec4 u_LightCounts;
+    vec4 u_DirectionalDirection;
+    vec4 u_DirectionalColor;
+    PointLight u_PointLights[MAX_POINT_LIGHTS];
+    SpotLight u_SpotLights[MAX_SPOT_LIGHTS];
+};
+
+in vec3 v_WorldPosition;
+in vec3 v_Normal;
+in vec2 v_TextureCoord;
+in float v_ViewDepth;
+
+uniform vec3 u_ViewPosition;
+
+uniform vec4 u_DiffuseColor;
+uniform sampler2D u_DiffuseTexture;
+uniform int u_HasDiffuseTexture;
+uniform vec3 u_SpecularColor;
+uniform float u_Shininess;
+uniform vec3 u_Emissive;
+uniform int u_BlinnPhong;
+
+// Shadows of the directional light, split into cascades by view depth
+uniform int u_ShadowsEnabled;
+uniform int u_CascadeCount;
+uniform vec4 u_CascadeSplits;
+uniform mat4 u_CascadeLightSpace[MAX_CASCADES];
+uniform sampler2D u_CascadeMap0;
+uniform sampler2D u_CascadeMap1;
+uniform sampler2D u_CascadeMap2;
+uniform sampler2D u_CascadeMap3;
+uniform int u_ShowCascades;
+
+// Shadows of the first spot light
+uniform int u_SpotShadowEnabled;
+uniform mat4 u_SpotLightSpace;
+uniform sampler2D u_SpotShadowMap;
+
+uniform float u_ShadowBias;
+uniform float u_ShadowNormalBias;
+uniform int u_PcfRadius;
+
+out vec4 Color;
+
+float specular_term(vec3 light_dir, vec3 normal, vec3 view_dir) {
+    if (u_BlinnPhong != 0) {
+        vec3 halfway = normalize(light_dir + view_dir);
+        return pow(max(dot(normal, halfway), 0.0), u_Shininess * 4.0);
+    }
+    vec3 reflected = reflect(-light_dir, normal);
+    return pow(max(dot(view_dir, reflected), 0.0), u_Shininess);
+}
+
+vec3 shade(vec3 light_dir, vec3 radiance, vec3 normal, vec3 view_dir, vec3 albedo) {
+    float diffuse = max(dot(normal, light_dir), 0.0);
+    float specular = diffuse > 0.0 ? specular_term(light_dir, normal, view_dir) : 0.0;
+    return radiance * (albedo * diffuse + u_SpecularColor * specular);
+}
+
+// 0.0 when fully lit, 1.0 when fully in shadow
+float sample_shadow(sampler2D shadow_map, mat4 light_space, vec3 normal, vec3 light_dir) {
+    vec3 world_position = v_WorldPosition + normal * u_ShadowNormalBias;
+    vec4 light_position = light_space * vec4(world_position, 1.0);
+    vec3 coords = light_position.xyz / light_position.w * 0.5 + 0.5;
+    if (coords.z > 1.0) {
+        return 0.0;
+    }
+
+    float bias = max(u_ShadowBias * (1.0 - dot(normal, light_dir)), u_ShadowBias * 0.1);
+    vec2 texel = 1.0 / vec2(textureSize(shadow_map, 0));
+
+    float shadow = 0.0;
+    for (int x = -u_PcfRadius; x <= u_PcfRadius; x++) {
+        for (int y = -u_PcfRadius; y <= u_PcfRadius; y++) {
+            float closest = texture(shadow_map, coords.xy + vec2(x, y) * texel).r;
+            shadow += coords.z - bias > closest ? 1.0 : 0.0;
+        }
+    }
+    float kernel = float(2 * u_PcfRadius + 1);
+    return shadow / (kernel * kernel);
+}
+
+int select_cascade() {
+    for (int i = 0; i < u_CascadeCount - 1; i++) {
+        if (v_ViewDepth < u_CascadeSplits[i]) {
+            return i;
+        }
+    }
+    return u_CascadeCount - 1;
+}
+
+float directional_shadow(vec3 normal, vec3 light_dir) {
+    if (u_ShadowsEnabled == 0 || v_ViewDepth > u_CascadeSplits[u_CascadeCount - 1]) {
+        return 0.0;
+    }
+
+    // Sampler arrays can only be indexed with constants in GLSL 3.30
+    int cascade = select_cascade();
+    mat4 light_space = u_CascadeLightSpace[cascade];
+    if (cascade == 0) {
+        return sample_shadow(u_CascadeMap0, light_space, normal, light_dir);
+    } else if (cascade == 1) {
+        return sample_shadow(u_CascadeMap1, light_space, normal, light_dir);
+    } else if (cascade == 2) {
+        return sample_shadow(u_CascadeMap2, light_space, normal, light_dir);
+    }
+    return sample_shadow(u_CascadeMap3, light_space, normal, light_dir);
+}
+
+float attenuate(vec3 attenuation, float distance) {
+    return 1.0 / (attenuation.x + attenuation.y * distance + attenuation.z * distance * distance);
+}
+
+void main() {
+    vec4 albedo = u_DiffuseColor;
+    if (u_HasDiffuseTexture != 0) {
+        albedo *= texture(u_DiffuseTexture, v_TextureCoord);
+    }
+
+    vec3 normal = normalize(v_Normal);
+    vec3 view_dir = normalize(u_ViewPosition - v_WorldPosition);
+
+    vec3 result = u_Ambient.rgb * u_Ambient.w * albedo.rgb;
+
+    if (u_LightCounts.z > 0.5) {
+        vec3 light_dir = normalize(-u_DirectionalDirection.xyz);
+        vec3 radiance = u_DirectionalColor.rgb * u_DirectionalColor.w
+            * (1.0 - directional_shadow(normal, light_dir));
+        result += shade(light_dir, radiance, normal, view_dir, albedo.rgb);
+    }
+
+    int point_count = int(u_LightCounts.x);
+    for (int i = 0; i < point_count; i++) {
+        vec3 to_light = u_PointLights[i].position.xyz - v_WorldPosition;
+        float distance = length(to_light);
+        vec3 radiance = u_PointLights[i].color.rgb * u_PointLights[i].color.w
+            * attenuate(u_PointLights[i].attenuation.xyz, distance);
+        result += shade(to_light / distance, radiance, normal, view_dir, albedo.rgb);
+    }
+
+    int spot_count = int(u_LightCounts.y);
+    for (int i = 0; i < spot_count; i++) {
+        vec3 to_light = u_SpotLights[i].position.xyz - v_WorldPosition;
+        float distance = length(to_light);
+        vec3 light_dir = to_light / distance;
+
+        float theta = dot(light_dir, normalize(-u_SpotLights[i].direction.xyz));
+        float cos_inner = u_SpotLights[i].direction.w;
+        float cos_outer = u_SpotLights[i].attenuation.w;
+        float cone = clamp((theta - cos_outer) / max(cos_inner - cos_outer, 0.0001), 0.0, 1.0);
+
+        vec3 radiance = u_SpotLights[i].color.rgb * u_SpotLights[i].color.w
+            * attenuate(u_SpotLights[i].attenuation.xyz, distance) * cone;
+        if (i == 0 && u_SpotShadowEnabled != 0) {
+            radiance *= 1.0 - sample_shadow(u_SpotShadowMap, u_SpotLightSpace, normal, light_dir);
+        }
+        result += shade(light_dir, radiance, normal, view_dir, albedo.rgb);
+    }
+
+    if (u_ShowCascades != 0 && u_ShadowsEnabled != 0) {
+        vec3 tints[MAX_CASCADES] = vec3[](
+            vec3(1.0, 0.4, 0.4), vec3(0.4, 1.0, 0.4), vec3(0.4, 0.4, 1.0), vec3(1.0, 1.0, 0.4)
+        );
+        result *= tints[select_cascade()];
+    }
+
+    Color = vec4(result + u_Emissive, albedo.a);
+}
@@ -0,0 +1,245 @@
+#shader vertex
+#version 330 core
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 texture_Coord;
+layout(location = 3) in vec4 tangent;
+
+out vec3 v_WorldPosition;
+out vec2 v_TextureCoord;
+out mat3 v_TBN;
+
+uniform mat4 u_Model;
+uniform mat4 u_ViewProjection;
+uniform mat3 u_NormalMatrix;
+
+void main() {
+    vec4 world_position = u_Model * vec4(position, 1.0);
+    gl_Position = u_ViewProjection * world_position;
+
+    vec3 n = normalize(u_NormalMatrix * normal);
+    vec3 t = normalize(mat3(u_Model) * tangent.xyz);
+    t = normalize(t - dot(t, n) * n);
+    vec3 b = cross(n, t) * tangent.w;
+
+    v_WorldPosition = world_position.xyz;
+    v_TextureCoord = texture_Coord;
+    v_TBN = mat3(t, b, n);
+}
+
+#shader fragment
+#version 330 core
+
+#define MAX_POINT_LIGHTS 8
+#define MAX_SPOT_LIGHTS 4
+
+struct PointLight {
+    vec4 position;
+    vec4 color;
+    vec4 attenuation;
+};
+
+struct SpotLight {
+    vec4 position;
+    vec4 direction;
+    vec4 color;
+    vec4 attenuation;
+};
+
+layout(std140) uniform Lights {
+    vec4 u_Ambient;
+    vec4 u_LightCounts;
+    vec4 u_DirectionalDirection;
+    vec4 u_DirectionalColor;
+    PointLight u_PointLights[MAX_POINT_LIGHTS];
+    SpotLight u_SpotLights[MAX_SPOT_LIGHTS];
+};
+
+in vec3 v_WorldPosition;
+in vec2 v_TextureCoord;
+in mat3 v_TBN;
+
+uniform vec3 u_ViewPosition;
+
+// Metallic-roughness model, factors multiply the maps as in glTF
+uniform vec4 u_Albedo;
+uniform float u_Metallic;
+uniform float u_Roughness;
+uniform float u_AmbientOcclusion;
+uniform vec3 u_Emissive;
+uniform float u_NormalScale;
+
+uniform sampler2D u_AlbedoMap;
+uniform sampler2D u_MetallicRoughnessMap;
+uniform sampler2D u_NormalMap;
+uniform sampler2D u_OcclusionMap;
+uniform sampler2D u_EmissiveMap;
+uniform int u_HasAlbedoMap;
+uniform int u_HasMetallicRoughnessMap;
+uniform int u_HasNormalMap;
+uniform int u_HasOcclusionMap;
+uniform int u_HasEmissiveMap;
+
+// Image based lighting
+uniform int u_UseIbl;
+uniform float u_EnvironmentIntensity;
+uniform samplerCube u_IrradianceMap;
+uniform samplerCube u_PrefilterMap;
+uniform sampler2D u_BrdfLut;
+uniform float u_PrefilterMipLevels;
+
+out vec4 Color;
+
+uniform float u_Exposure;
+uniform int u_ToneMapping;
+
+vec3 tone_map(vec3 color) {
+    color *= u_Exposure;
+    if (u_ToneMapping == 1) {
+        color = color / (color + vec3(1.0));
+    } else if (u_ToneMapping == 2) {
+        // Narkowicz's fit of the ACES filmic curve
+        color = clamp((color * (2.51 * color + 0.03)) / (color * (2.43 * color + 0.59) + 0.14), 0.0, 1.0);
+    }
+    return pow(color, vec3(1.0 / 2.2));
+}
+
+const float PI = 3.14159265359;
+
+float distribution_ggx(float n_dot_h, float roughness) {
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denominator * denominator);
+}
+
+float geometry_schlick_ggx(float n_dot_v, float roughness) {
+    float r = roughness + 1.0;
+    float k = (r * r) / 8.0;
+    return n_dot_v / (n_dot_v * (1.0 - k) + k);
+}
+
+float geometry_smith(float n_dot_v, float n_dot_l, float roughness) {
+    return geometry_schlick_ggx(n_dot_v, roughness) * geometry_schlick_ggx(n_dot_l, roughness);
+}
+
+vec3 fresnel_schlick(float cos_theta, vec3 f0) {
+    return f0 + (1.0 - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+vec3 fresnel_schlick_roughness(float cos_theta, vec3 f0, float roughness) {
+    return f0 + (max(vec3(1.0 - roughness), f0) - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// Cook-Torrance BRDF for a single light
+vec3 radiance_from(vec3 light_dir, vec3 radiance, vec3 normal, vec3 view_dir, vec3 albedo,
+                   float metallic, float roughness, vec3 f0) {
+    vec3 halfway = normalize(view_dir + light_dir);
+    float n_dot_l = max(dot(normal, light_dir), 0.0);
+    float n_dot_v = max(dot(normal, view_dir), 0.0001);
+
+    float d = distribution_ggx(max(dot(normal, halfway), 0.0), roughness);
+    float g = geometry_smith(n_dot_v, n_dot_l, roughness);
+    vec3 f = fresnel_schlick(max(dot(halfway, view_dir), 0.0), f0);
+
+    vec3 specular = d * g * f / (4.0 * n_dot_v * n_dot_l + 0.0001);
+    vec3 diffuse = (vec3(1.0) - f) * (1.0 - metallic);
+    return (diffuse * albedo / PI + specular) * radiance * n_dot_l;
+}
+
+float attenuate(vec3 attenuation, float distance) {
+    return 1.0 / (attenuation.x + attenuation.y * distance + attenuation.z * distance * distance);
+}
+
+void main() {
+    vec4 albedo = u_Albedo;
+    if (u_HasAlbedoMap != 0) {
+        vec4 texel = texture(u_AlbedoMap, v_TextureCoord);
+        albedo *= vec4(pow(texel.rgb, vec3(2.2)), texel.a);
+    }
+
+    float metallic = u_Metallic;
+    float roughness = u_Roughness;
+    if (u_HasMetallicRoughnessMap != 0) {
+        vec4 texel = texture(u_MetallicRoughnessMap, v_TextureCoord);
+        roughness *= texel.g;
+        metallic *= texel.b;
+    }
+    roughness = clamp(roughness, 0.04, 1.0);
+
+    float occlusion = u_AmbientOcclusion;
+    if (u_HasOcclusionMap != 0) {
+        occlusion *= texture(u_OcclusionMap, v_TextureCoord).r;
+    }
+
+    vec3 emissive = u_Emissive;
+    if (u_HasEmissiveMap != 0) {
+        emissive *= pow(texture(u_EmissiveMap, v_TextureCoord).rgb, vec3(2.2));
+    }
+
+    vec3 normal = normalize(v_TBN[2]);
+    if (u_HasNormalMap != 0) {
+        vec3 tangent_normal = texture(u_NormalMap, v_TextureCoord).xyz * 2.0 - 1.0;
+        tangent_normal.xy *= u_NormalScale;
+        normal = normalize(v_TBN * tangent_normal);
+    }
+
+    vec3 view_dir = normalize(u_ViewPosition - v_WorldPosition);
+    vec3 f0 = mix(vec3(0.04), albedo.rgb, metallic);
+
+    vec3 result = vec3(0.0);
+
+    if (u_LightCounts.z > 0.5) {
+        vec3 radiance = u_DirectionalColor.rgb * u_DirectionalColor.w;
+        result += radiance_from(normalize(-u_DirectionalDirection.xyz), radiance, normal, view_dir,
+                                albedo.rgb, metallic, roughness, f0);
+    }
+
+    int point_count = int(u_LightCounts.x);
+    for (int i = 0; i < point_count; i++) {
+        vec3 to_light = u_PointLights[i].position.xyz - v_WorldPosition;
+        float distance = length(to_light);
+        vec3 radiance = u_PointLights[i].color.rgb * u_PointLights[i].color.w
+            * attenuate(u_PointLights[i].attenuation.xyz, distance);
+        result += radiance_from(to_light / distance, radiance, normal, view_dir, albedo.rgb,
+                                metallic, roughness, f0);
+    }
+
+    int spot_count = int(u_LightCounts.y);
+    for (int i = 0; i < spot_count; i++) {
+        vec3 to_light = u_SpotLights[i].position.xyz - v_WorldPosition;
+        float distance = length(to_light);
+        vec3 light_dir = to_light / distance;
+
+        float theta = dot(light_dir, normalize(-u_SpotLights[i].direction.xyz));
+        float cos_inner = u_SpotLights[i].direction.w;
+        float cos_outer = u_SpotLights[i].attenuation.w;
+        float cone = clamp((theta - cos_outer) / max(cos_inner - cos_outer, 0.0001), 0.0, 1.0);
+
+        vec3 radiance = u_SpotLights[i].color.rgb * u_SpotLights[i].color.w
+            * attenuate(u_SpotLights[i].attenuation.xyz, distance) * cone;
+        result += radiance_from(light_dir, radiance, normal, view_dir, albedo.rgb, metallic,
+                                roughness, f0);
+    }
+
+    vec3 ambient;
+    if (u_UseIbl != 0) {
+        float n_dot_v = max(dot(normal, view_dir), 0.0);
+        vec3 f = fresnel_schlick_roughness(n_dot_v, f0, roughness);
+        vec3 k_diffuse = (1.0 - f) * (1.0 - metallic);
+
+        vec3 diffuse = texture(u_IrradianceMap, normal).rgb * albedo.rgb;
+
+        vec3 reflected = reflect(-view_dir, normal);
+        vec3 prefiltered = textureLod(u_PrefilterMap, reflected, roughness * (u_PrefilterMipLevels - 1.0)).rgb;
+        vec2 brdf = texture(u_BrdfLut, vec2(n_dot_v, roughness)).rg;
+        vec3 specular = prefiltered * (f * brdf.x + brdf.y);
+
+        ambient = (k_diffuse * diffuse + specular) * u_EnvironmentIntensity * occlusion;
+    } else {
+        ambient = u_Ambient.rgb * u_Ambient.w * albedo.rgb * occlusion;
+    }
+
+    Color = vec4(tone_map(result + ambient + emissive), albedo.a);
+}
@@ -0,0 +1,91 @@
+#shader vertex
+#version 330 core
+
+layout(location = 0) in vec3 position;
+
+out vec3 v_LocalPosition;
+
+uniform mat4 u_Projection;
+uniform mat4 u_View;
+
+void main() {
+    v_LocalPosition = position;
+    gl_Position = u_Projection * u_View * vec4(position, 1.0);
+}
+
+#shader fragment
+#version 330 core
+
+in vec3 v_LocalPosition;
+
+uniform samplerCube u_EnvironmentMap;
+uniform float u_Roughness;
+uniform float u_EnvironmentResolution;
+
+out vec4 Color;
+
+const float PI = 3.14159265359;
+
+float radical_inverse_vdc(uint bits) {
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return float(bits) * 2.3283064365386963e-10;
+}
+
+vec2 hammersley(uint i, uint count) {
+    return vec2(float(i) / float(count), radical_inverse_vdc(i));
+}
+
+vec3 importance_sample_ggx(vec2 xi, vec3 normal, float roughness) {
+    float a = roughness * roughness;
+    float phi = 2.0 * PI * xi.x;
+    float cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    float sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+
+    vec3 halfway = vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+    vec3 up = abs(normal.z) < 0.999 ? vec3(0.0, 0.0, 1.0) : vec3(1.0, 0.0, 0.0);
+    vec3 tangent = normalize(cross(up, normal));
+    vec3 bitangent = cross(normal, tangent);
+    return normalize(tangent * halfway.x + bitangent * halfway.y + normal * halfway.z);
+}
+
+float distribution_ggx(float n_dot_h, float roughness) {
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denominator * denominator);
+}
+
+const uint SAMPLE_COUNT = 1024u;
+
+// Split sum approximation, the environment convolved with the GGX lobe of `u_Roughness`
+void main() {
+    vec3 normal = normalize(v_LocalPosition);
+    vec3 view = normal;
+
+    vec3 prefiltered = vec3(0.0);
+    float total_weight = 0.0;
+    for (uint i = 0u; i < SAMPLE_COUNT; i++) {
+        vec2 xi = hammersley(i, SAMPLE_COUNT);
+        vec3 halfway = importance_sample_ggx(xi, normal, u_Roughness);
+        vec3 light = normalize(2.0 * dot(view, halfway) * halfway - view);
+
+        float n_dot_l = max(dot(normal, light), 0.0);
+        if (n_dot_l > 0.0) {
+            // Sample a blurrier mip where few samples cover a large solid angle
+            float n_dot_h = max(dot(normal, halfway), 0.0);
+            float pdf = distribution_ggx(n_dot_h, u_Roughness) * 0.25 + 0.0001;
+            float texel_angle = 4.0 * PI / (6.0 * u_EnvironmentResolution * u_EnvironmentResolution);
+            float sample_angle = 1.0 / (float(SAMPLE_COUNT) * pdf + 0.0001);
+            float mip = u_Roughness == 0.0 ? 0.0 : 0.5 * log2(sample_angle / texel_angle);
+
+            prefiltered += textureLod(u_EnvironmentMap, light, mip).rgb * n_dot_l;
+            total_weight += n_dot_l;
+        }
+    }
+
+    Color = vec4(prefiltered / total_weight, 1.0);
+}
@@ -0,0 +1,44 @@
+#shader vertex
+#version 330 core
+
+layout(location = 0) in vec3 position;
+
+out vec3 v_LocalPosition;
+
+uniform mat4 u_Projection;
+uniform mat4 u_View;
+
+void main() {
+    v_LocalPosition = position;
+    gl_Position = u_Projection * u_View * vec4(position, 1.0);
+}
+
+#shader fragment
+#version 330 core
+
+in vec3 v_LocalPosition;
+
+uniform vec3 u_SunDirection;
+uniform vec3 u_SunColor;
+uniform float u_SunIntensity;
+uniform float u_SunSize;
+uniform vec3 u_ZenithColor;
+uniform vec3 u_HorizonColor;
+uniform vec3 u_GroundColor;
+
+out vec4 Color;
+
+void main() {
+    vec3 direction = normalize(v_LocalPosition);
+
+    vec3 sky = mix(u_HorizonColor, u_ZenithColor, pow(max(direction.y, 0.0), 0.5));
+    vec3 ground = mix(u_HorizonColor, u_GroundColor, pow(max(-direction.y, 0.0), 0.3));
+    vec3 color = direction.y >= 0.0 ? sky : ground;
+
+    // A hard disc for the sun itself plus a soft glow around it
+    float sun = dot(direction, normalize(-u_SunDirection));
+    color += u_SunColor * u_SunIntensity * smoothstep(cos(u_SunSize), cos(u_SunSize * 0.9), sun);
+    color += u_SunColor * pow(max(sun, 0.0), 64.0) * 0.5;
+
+    Color = vec4(color, 1.0);
+}
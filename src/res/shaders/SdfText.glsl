@@ -0,0 +1,77 @@
+#shader vertex
+#version 450 core
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec4 color;
+layout(location = 2) in vec2 textureCoord;
+
+out vec4 v_Color;
+out vec2 v_TextureCoord;
+
+uniform mat4 u_ViewProjection;
+
+void main() {
+    gl_Position = u_ViewProjection * vec4(position, 0.0, 1.0);
+    v_Color = color;
+    v_TextureCoord = textureCoord;
+}
+
+#shader fragment
+#version 450 core
+
+in vec4 v_Color;
+in vec2 v_TextureCoord;
+
+out vec4 Color;
+
+// 0.5 on the outline, 0 and 1 at u_Spread pixels outside and inside
+uniform sampler2D u_DistanceField;
+uniform float u_Spread;
+
+uniform float u_OutlineWidth;
+uniform vec4 u_OutlineColor;
+uniform float u_GlowWidth;
+uniform vec4 u_GlowColor;
+uniform vec2 u_ShadowOffset;
+uniform float u_ShadowSoftness;
+uniform vec4 u_ShadowColor;
+
+// Signed distance to the outline in pixels of the generated size, positive inside
+float distanceAt(vec2 textureCoord) {
+    return (texture(u_DistanceField, textureCoord).r - 0.5) * 2.0 * u_Spread;
+}
+
+// Antialiased coverage over one screen pixel, `width` being a screen pixel in field units
+float coverage(float distance, float width) {
+    return clamp(distance / width + 0.5, 0.0, 1.0);
+}
+
+// Non premultiplied `top` composited over `bottom`
+vec4 over(vec4 top, vec4 bottom) {
+    float alpha = top.a + bottom.a * (1.0 - top.a);
+    vec3 color = (top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a)) / max(alpha, 1e-4);
+    return vec4(color, alpha);
+}
+
+void main() {
+    float distance = distanceAt(v_TextureCoord);
+    float width = max(fwidth(distance), 1e-4);
+
+    // Fill inside the outline, which extends the glyph outwards
+    float fill = coverage(distance, width);
+    float outlined = coverage(distance + u_OutlineWidth, width);
+    vec4 outlineColor = u_OutlineWidth > 0.0 ? u_OutlineColor : v_Color;
+    vec4 text = mix(outlineColor, v_Color, fill);
+    text.a *= outlined * v_Color.a;
+
+    // Glow fades out over its width beyond the outline
+    float outside = -(distance + u_OutlineWidth);
+    float glow = u_GlowWidth > 0.0 ? 1.0 - smoothstep(0.0, u_GlowWidth, outside) : 0.0;
+
+    float shadowDistance = distanceAt(v_TextureCoord - u_ShadowOffset) + u_OutlineWidth;
+    float shadow = smoothstep(-u_ShadowSoftness - width, u_ShadowSoftness + width, shadowDistance);
+
+    vec4 result = vec4(u_ShadowColor.rgb, u_ShadowColor.a * shadow * v_Color.a);
+    result = over(vec4(u_GlowColor.rgb, u_GlowColor.a * glow * v_Color.a), result);
+    Color = over(text, result);
+}
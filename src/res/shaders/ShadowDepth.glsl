@@ -0,0 +1,17 @@
+#shader vertex
+#version 330 core
+
+layout(location = 0) in vec3 position;
+
+uniform mat4 u_LightSpace;
+uniform mat4 u_Model;
+
+void main() {
+    gl_Position = u_LightSpace * u_Model * vec4(position, 1.0);
+}
+
+#shader fragment
+#version 330 core
+
+void main() {
+}
@@ -0,0 +1,25 @@
+#shader vertex
+#version 450 core
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec4 color;
+
+out vec4 v_Color;
+
+uniform mat4 u_ViewProjection;
+
+void main() {
+    gl_Position = u_ViewProjection * vec4(position, 0.0, 1.0);
+    v_Color = color;
+}
+
+#shader fragment
+#version 450 core
+
+in vec4 v_Color;
+
+out vec4 Color;
+
+void main() {
+    Color = v_Color;
+}
@@ -0,0 +1,45 @@
+#shader vertex
+#version 330 core
+
+layout(location = 0) in vec3 position;
+
+out vec3 v_LocalPosition;
+
+uniform mat4 u_Projection;
+uniform mat4 u_View;
+
+void main() {
+    v_LocalPosition = position;
+    // Drop the translation so the sky stays at infinity, and force the depth to 1.0
+    vec4 clip_position = u_Projection * mat4(mat3(u_View)) * vec4(position, 1.0);
+    gl_Position = clip_position.xyww;
+}
+
+#shader fragment
+#version 330 core
+
+in vec3 v_LocalPosition;
+
+uniform samplerCube u_EnvironmentMap;
+uniform float u_Lod;
+
+out vec4 Color;
+
+uniform float u_Exposure;
+uniform int u_ToneMapping;
+
+vec3 tone_map(vec3 color) {
+    color *= u_Exposure;
+    if (u_ToneMapping == 1) {
+        color = color / (color + vec3(1.0));
+    } else if (u_ToneMapping == 2) {
+        // Narkowicz's fit of the ACES filmic curve
+        color = clamp((color * (2.51 * color + 0.03)) / (color * (2.43 * color + 0.59) + 0.14), 0.0, 1.0);
+    }
+    return pow(color, vec3(1.0 / 2.2));
+}
+
+void main() {
+    vec3 color = textureLod(u_EnvironmentMap, normalize(v_LocalPosition), u_Lod).rgb;
+    Color = vec4(tone_map(color), 1.0);
+}
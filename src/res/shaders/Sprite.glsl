@@ -0,0 +1,31 @@
+#shader vertex
+#version 450 core
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec4 color;
+layout(location = 2) in vec2 textureCoord;
+
+out vec4 v_Color;
+out vec2 v_TextureCoord;
+
+uniform mat4 u_ViewProjection;
+
+void main() {
+    gl_Position = u_ViewProjection * vec4(position, 1.0);
+    v_Color = color;
+    v_TextureCoord = textureCoord;
+}
+
+#shader fragment
+#version 450 core
+
+in vec4 v_Color;
+in vec2 v_TextureCoord;
+
+out vec4 Color;
+
+uniform sampler2D u_Texture;
+
+void main() {
+    Color = texture(u_Texture, v_TextureCoord) * v_Color;
+}
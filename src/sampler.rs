@@ -0,0 +1,273 @@
+use std::{cell::OnceCell, ffi::CStr};
+
+use glcall_macro::gl_call;
+
+/// From `EXT_texture_filter_anisotropic`, not part of the core profile the bindings target
+const TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+}
+
+impl TextureFilter {
+    fn gl_enum(&self) -> u32 {
+        match self {
+            TextureFilter::Nearest => gl::NEAREST,
+            TextureFilter::Linear => gl::LINEAR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    /// Samples outside the texture return `TextureSpec::border_color`
+    ClampToBorder,
+}
+
+impl WrapMode {
+    fn gl_enum(&self) -> u32 {
+        match self {
+            WrapMode::Repeat => gl::REPEAT,
+            WrapMode::MirroredRepeat => gl::MIRRORED_REPEAT,
+            WrapMode::ClampToEdge => gl::CLAMP_TO_EDGE,
+            WrapMode::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+///
+/// How a texture is filtered and wrapped when sampled
+///
+/// A spec is either applied to a texture with `Texture::with_spec`, or turned into a
+/// `Sampler` which overrides the texture's own parameters on the unit it is bound to.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureSpec {
+    pub min_filter: TextureFilter,
+    pub mag_filter: TextureFilter,
+    /// Filtering between mip levels, `None` only samples the base level and skips
+    /// generating mipmaps
+    pub mipmap_filter: Option<TextureFilter>,
+    pub wrap_s: WrapMode,
+    pub wrap_t: WrapMode,
+    pub wrap_r: WrapMode,
+    pub border_color: [f32; 4],
+    /// Maximum anisotropy, 1.0 disables it. Clamped to what the driver supports and
+    /// ignored without `EXT_texture_filter_anisotropic`
+    pub anisotropy: f32,
+    /// Offset added to the mip level picked when sampling, positive values blur
+    pub lod_bias: f32,
+}
+
+impl Default for TextureSpec {
+    fn default() -> Self {
+        Self {
+            min_filter: TextureFilter::Linear,
+            mag_filter: TextureFilter::Linear,
+            mipmap_filter: None,
+            wrap_s: WrapMode::ClampToEdge,
+            wrap_t: WrapMode::ClampToEdge,
+            wrap_r: WrapMode::ClampToEdge,
+            border_color: [0.0, 0.0, 0.0, 0.0],
+            anisotropy: 1.0,
+            lod_bias: 0.0,
+        }
+    }
+}
+
+impl TextureSpec {
+    /// Crisp texels when magnified, no mipmaps
+    pub fn pixel_art() -> Self {
+        Self::default().with_filter(TextureFilter::Nearest)
+    }
+
+    /// Repeating, mipmapped and anisotropically filtered, for surfaces seen at grazing angles
+    pub fn tiled() -> Self {
+        Self::default()
+            .with_wrap(WrapMode::Repeat)
+            .with_mipmaps(TextureFilter::Linear)
+            .with_anisotropy(16.0)
+    }
+
+    /// Sets both the minification and magnification filter
+    pub fn with_filter(mut self, filter: TextureFilter) -> Self {
+        self.min_filter = filter;
+        self.mag_filter = filter;
+        self
+    }
+
+    pub fn with_mipmaps(mut self, filter: TextureFilter) -> Self {
+        self.mipmap_filter = Some(filter);
+        self
+    }
+
+    /// Sets the wrap mode of every axis
+    pub fn with_wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap_s = wrap;
+        self.wrap_t = wrap;
+        self.wrap_r = wrap;
+        self
+    }
+
+    pub fn with_border_color(mut self, color: [f32; 4]) -> Self {
+        self.border_color = color;
+        self
+    }
+
+    pub fn with_anisotropy(mut self, anisotropy: f32) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    pub fn with_lod_bias(mut self, lod_bias: f32) -> Self {
+        self.lod_bias = lod_bias;
+        self
+    }
+
+    fn min_filter_enum(&self) -> u32 {
+        match (self.min_filter, self.mipmap_filter) {
+            (filter, None) => filter.gl_enum(),
+            (TextureFilter::Nearest, Some(TextureFilter::Nearest)) => gl::NEAREST_MIPMAP_NEAREST,
+            (TextureFilter::Linear, Some(TextureFilter::Nearest)) => gl::LINEAR_MIPMAP_NEAREST,
+            (TextureFilter::Nearest, Some(TextureFilter::Linear)) => gl::NEAREST_MIPMAP_LINEAR,
+            (TextureFilter::Linear, Some(TextureFilter::Linear)) => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    /// Feeds every parameter to the given setters, shared by textures and sampler objects
+    pub(crate) fn apply_with(
+        &self,
+        mut parameter_i: impl FnMut(u32, i32),
+        mut parameter_f: impl FnMut(u32, f32),
+        mut parameter_fv: impl FnMut(u32, &[f32; 4]),
+    ) {
+        parameter_i(gl::TEXTURE_MIN_FILTER, self.min_filter_enum() as i32);
+        parameter_i(gl::TEXTURE_MAG_FILTER, self.mag_filter.gl_enum() as i32);
+        parameter_i(gl::TEXTURE_WRAP_S, self.wrap_s.gl_enum() as i32);
+        parameter_i(gl::TEXTURE_WRAP_T, self.wrap_t.gl_enum() as i32);
+        parameter_i(gl::TEXTURE_WRAP_R, self.wrap_r.gl_enum() as i32);
+        parameter_fv(gl::TEXTURE_BORDER_COLOR, &self.border_color);
+        parameter_f(gl::TEXTURE_LOD_BIAS, self.lod_bias);
+        if let Some(max) = max_anisotropy() {
+            parameter_f(TEXTURE_MAX_ANISOTROPY_EXT, self.anisotropy.clamp(1.0, max));
+        }
+    }
+}
+
+/// The highest anisotropy the driver supports, `None` without anisotropic filtering
+pub fn max_anisotropy() -> Option<f32> {
+    thread_local! {
+        static MAX_ANISOTROPY: OnceCell<Option<f32>> = const { OnceCell::new() };
+    }
+
+    MAX_ANISOTROPY.with(|max| {
+        *max.get_or_init(|| {
+            let mut count = 0;
+            gl_call!({
+                gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+            });
+            let supported = (0..count as u32).any(|i| {
+                let name = unsafe { gl::GetStringi(gl::EXTENSIONS, i) };
+                if name.is_null() {
+                    return false;
+                }
+                let name = unsafe { CStr::from_ptr(name as *const _) };
+                matches!(
+                    name.to_bytes(),
+                    b"GL_EXT_texture_filter_anisotropic" | b"GL_ARB_texture_filter_anisotropic"
+                )
+            });
+            if !supported {
+                return None;
+            }
+
+            let mut max = 1.0;
+            gl_call!({
+                gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY_EXT, &mut max);
+            });
+            Some(max)
+        })
+    })
+}
+
+///
+/// A sampler object, overriding the sampling parameters of whatever texture is bound to
+/// the same unit
+///
+/// Samplers let one texture be sampled in different ways, e.g. a sprite sheet drawn both
+/// crisp and smoothed. Mipmap filters only work if the texture has mipmaps.
+///
+pub struct Sampler {
+    renderer_id: u32,
+    spec: TextureSpec,
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        gl_call!({
+            gl::DeleteSamplers(1, &self.renderer_id);
+        });
+    }
+}
+
+impl Sampler {
+    pub fn new(spec: TextureSpec) -> Self {
+        let mut instance = Self {
+            renderer_id: 0,
+            spec,
+        };
+        gl_call!({
+            gl::CreateSamplers(1, &mut instance.renderer_id);
+        });
+        instance.set_spec(spec);
+        instance
+    }
+
+    pub fn spec(&self) -> &TextureSpec {
+        &self.spec
+    }
+
+    pub fn set_spec(&mut self, spec: TextureSpec) {
+        let id = self.renderer_id;
+        spec.apply_with(
+            |parameter, value| {
+                gl_call!({
+                    gl::SamplerParameteri(id, parameter, value);
+                });
+            },
+            |parameter, value| {
+                gl_call!({
+                    gl::SamplerParameterf(id, parameter, value);
+                });
+            },
+            |parameter, value| {
+                gl_call!({
+                    gl::SamplerParameterfv(id, parameter, value.as_ptr());
+                });
+            },
+        );
+        self.spec = spec;
+    }
+
+    pub fn renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+
+    pub fn bind(&self, unit: u32) {
+        gl_call!({
+            gl::BindSampler(unit, self.renderer_id);
+        });
+    }
+
+    pub fn unbind(&self, unit: u32) {
+        gl_call!({
+            gl::BindSampler(unit, 0);
+        });
+    }
+}
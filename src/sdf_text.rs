@@ -0,0 +1,673 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use image::{DynamicImage, GrayImage, Luma};
+use nalgebra_glm as glm;
+use rusttype::{OutlineBuilder, Scale, ScaledGlyph};
+use serde_json::{json, Value};
+
+use crate::{
+    atlas::{AtlasError, AtlasRegion, SkylinePacker},
+    font::{FontId, FontLibrary},
+    gl_component::GLComponent,
+    shader::Shader,
+    text::{GlyphBatch, GlyphVertex},
+    texture::{Texture, TextureError, TextureObject, TextureOptions},
+    ShaderBuilder,
+};
+
+const MAX_PAGE_SIZE: usize = 4096;
+
+/// Straight segments of a glyph outline, y down relative to the glyph origin
+#[derive(Default)]
+struct Segments {
+    lines: Vec<(glm::Vec2, glm::Vec2)>,
+    start: glm::Vec2,
+    last: glm::Vec2,
+}
+
+impl Segments {
+    /// Curves are flattened into this many lines
+    const CURVE_STEPS: usize = 8;
+
+    fn push(&mut self, to: glm::Vec2) {
+        if to != self.last {
+            self.lines.push((self.last, to));
+        }
+        self.last = to;
+    }
+
+    /// Distance from `point` to the closest of `lines`, at most `max`
+    fn distance(lines: &[&(glm::Vec2, glm::Vec2)], (x, y): (f32, f32), max: f32) -> f32 {
+        let mut closest = max * max;
+        for (a, b) in lines {
+            if a.x.min(b.x) - max > x || a.x.max(b.x) + max < x {
+                continue;
+            }
+            let (edge_x, edge_y) = (b.x - a.x, b.y - a.y);
+            let t = ((x - a.x) * edge_x + (y - a.y) * edge_y) / (edge_x * edge_x + edge_y * edge_y);
+            let t = t.clamp(0.0, 1.0);
+            let (dx, dy) = (a.x + edge_x * t - x, a.y + edge_y * t - y);
+            closest = closest.min(dx * dx + dy * dy);
+        }
+        closest.sqrt()
+    }
+
+    /// Winding number of `lines` around `point`, nonzero inside
+    fn winding(lines: &[&(glm::Vec2, glm::Vec2)], (x, y): (f32, f32)) -> i32 {
+        let mut winding = 0;
+        for (a, b) in lines {
+            let side = (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x);
+            if a.y <= y && b.y > y && side > 0.0 {
+                winding += 1;
+            } else if b.y <= y && a.y > y && side < 0.0 {
+                winding -= 1;
+            }
+        }
+        winding
+    }
+}
+
+impl OutlineBuilder for Segments {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = glm::vec2(x, y);
+        self.last = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push(glm::vec2(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (p0, p1, p2) = (self.last, glm::vec2(x1, y1), glm::vec2(x, y));
+        for step in 1..=Self::CURVE_STEPS {
+            let t = step as f32 / Self::CURVE_STEPS as f32;
+            let u = 1.0 - t;
+            self.push(p0 * u * u + p1 * 2.0 * u * t + p2 * t * t);
+        }
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (p0, p1, p2, p3) = (
+            self.last,
+            glm::vec2(x1, y1),
+            glm::vec2(x2, y2),
+            glm::vec2(x, y),
+        );
+        for step in 1..=Self::CURVE_STEPS {
+            let t = step as f32 / Self::CURVE_STEPS as f32;
+            let u = 1.0 - t;
+            self.push(
+                p0 * u * u * u + p1 * 3.0 * u * u * t + p2 * 3.0 * u * t * t + p3 * t * t * t,
+            );
+        }
+    }
+
+    fn close(&mut self) {
+        self.push(self.start);
+    }
+}
+
+///
+/// Distance field of a glyph with `spread` pixels of margin, and the offset of its top left
+/// corner from the glyph origin, y down
+///
+/// 0.5 is the outline, 0 and 1 are `spread` pixels outside and inside of it.
+///
+fn distance_field(glyph: &ScaledGlyph, spread: f32) -> Option<(GrayImage, glm::Vec2)> {
+    let bounds = glyph.exact_bounding_box()?;
+    let mut segments = Segments::default();
+    if !glyph.build_outline(&mut segments) || segments.lines.is_empty() {
+        return None;
+    }
+
+    let left = (bounds.min.x - spread).floor();
+    let top = (bounds.min.y - spread).floor();
+    let width = ((bounds.max.x + spread).ceil() - left) as u32;
+    let height = ((bounds.max.y + spread).ceil() - top) as u32;
+    let mut image = GrayImage::new(width, height);
+    for y in 0..height {
+        // Only lines crossing a row affect the winding, and only those within `spread` of
+        // it the distance, the rest saturates
+        let center_y = top + y as f32 + 0.5;
+        let crossing: Vec<_> = segments
+            .lines
+            .iter()
+            .filter(|(a, b)| (a.y <= center_y) != (b.y <= center_y))
+            .collect();
+        let near: Vec<_> = segments
+            .lines
+            .iter()
+            .filter(|(a, b)| a.y.min(b.y) - spread <= center_y && a.y.max(b.y) + spread >= center_y)
+            .collect();
+
+        for x in 0..width {
+            let center = (left + x as f32 + 0.5, center_y);
+            let mut distance = Segments::distance(&near, center, spread);
+            if Segments::winding(&crossing, center) == 0 {
+                distance = -distance;
+            }
+            let value = (0.5 + distance / (2.0 * spread)).clamp(0.0, 1.0);
+            image.put_pixel(x, y, Luma([(value * 255.0).round() as u8]));
+        }
+    }
+    Some((image, glm::vec2(left, top)))
+}
+
+/// A glyph of an `SdfFont`, in pixels at the size the font was generated at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SdfGlyph {
+    /// `None` for glyphs without an outline like spaces
+    pub region: Option<AtlasRegion>,
+    /// Offset of the distance field's top left corner from the glyph origin, y down
+    pub offset: glm::Vec2,
+    pub advance: f32,
+}
+
+///
+/// Signed distance fields of a set of characters packed into one image, along with the
+/// metrics to lay them out at any size
+///
+/// Generating takes a moment, so fonts can be saved to disk and loaded instead.
+///
+#[derive(Clone)]
+pub struct SdfFont {
+    pub image: GrayImage,
+    /// Pixel size the glyphs were generated at
+    pub size: f32,
+    /// Pixels at `size` from the outline to where the distance field saturates, which is
+    /// also the widest outline, glow or shadow offset possible
+    pub spread: f32,
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+    pub glyphs: BTreeMap<char, SdfGlyph>,
+    pub kerning: HashMap<(char, char), f32>,
+}
+
+impl SdfFont {
+    pub fn glyph(&self, character: char) -> Option<&SdfGlyph> {
+        self.glyphs.get(&character)
+    }
+
+    pub fn kerning(&self, left: char, right: char) -> f32 {
+        self.kerning.get(&(left, right)).copied().unwrap_or(0.0)
+    }
+
+    pub fn line_height(&self, size: f32) -> f32 {
+        (self.ascent - self.descent + self.line_gap) * size / self.size
+    }
+
+    /// Width and height of `text` at `size`, lines break at `\n`
+    pub fn measure(&self, text: &str, size: f32) -> glm::Vec2 {
+        let mut width: f32 = 0.0;
+        let mut lines = 0;
+        for line in text.split('\n') {
+            let end = self.layout_line(line, |_, _| {});
+            width = width.max(end);
+            lines += 1;
+        }
+        glm::vec2(
+            width * size / self.size,
+            lines as f32 * self.line_height(size),
+        )
+    }
+
+    /// Calls `glyph` with each glyph of a line and its pen position at the generated size,
+    /// returns the width of the line
+    fn layout_line(&self, line: &str, mut glyph: impl FnMut(&SdfGlyph, f32)) -> f32 {
+        let mut x = 0.0;
+        let mut previous = None;
+        for character in line.chars() {
+            let Some((character, cached)) = self
+                .glyph(character)
+                .map(|cached| (character, cached))
+                .or_else(|| self.glyph('?').map(|cached| ('?', cached)))
+            else {
+                continue;
+            };
+            if let Some(previous) = previous {
+                x += self.kerning(previous, character);
+            }
+            glyph(cached, x);
+            x += cached.advance;
+            previous = Some(character);
+        }
+        x
+    }
+
+    /// Writes the image next to `path` as PNG and the metrics to `path` as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), AtlasError> {
+        let path = path.as_ref();
+        let image_path = path.with_extension("png");
+        self.image.save(&image_path)?;
+
+        let glyphs: serde_json::Map<String, Value> = self
+            .glyphs
+            .iter()
+            .map(|(character, glyph)| {
+                let mut value = json!({
+                    "offset": [glyph.offset.x, glyph.offset.y],
+                    "advance": glyph.advance,
+                });
+                if let Some(region) = &glyph.region {
+                    value["rect"] = json!([region.x, region.y, region.width, region.height]);
+                }
+                (character.to_string(), value)
+            })
+            .collect();
+        let kerning: Vec<Value> = self
+            .kerning
+            .iter()
+            .map(|(&(left, right), amount)| json!([left.to_string(), right.to_string(), amount]))
+            .collect();
+        let json = json!({
+            "image": image_path.file_name().and_then(|name| name.to_str()),
+            "size": self.size,
+            "spread": self.spread,
+            "ascent": self.ascent,
+            "descent": self.descent,
+            "line_gap": self.line_gap,
+            "glyphs": glyphs,
+            "kerning": kerning,
+        });
+        std::fs::write(path, serde_json::to_string_pretty(&json)?)?;
+        Ok(())
+    }
+
+    /// Reads a font written by `save`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AtlasError> {
+        let path = path.as_ref();
+        let invalid = |message: &str| AtlasError::InvalidMetadata(message.to_string());
+        let json: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let number = |key: &str| {
+            json.get(key)
+                .and_then(Value::as_f64)
+                .map(|value| value as f32)
+                .ok_or_else(|| invalid(&format!("missing {}", key)))
+        };
+
+        let image_name = json
+            .get("image")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid("missing image"))?;
+        let image = image::open(path.with_file_name(image_name))?.into_luma8();
+        let page_size = (image.width() as usize, image.height() as usize);
+
+        let glyphs = json
+            .get("glyphs")
+            .and_then(Value::as_object)
+            .ok_or_else(|| invalid("missing glyphs"))?
+            .iter()
+            .map(|(key, glyph)| {
+                let mut chars = key.chars();
+                let (Some(character), None) = (chars.next(), chars.next()) else {
+                    return Err(invalid(&format!("`{}` is not a single character", key)));
+                };
+                let pair = |pointer: &str| {
+                    let x = glyph.pointer(&format!("{}/0", pointer))?.as_f64()?;
+                    let y = glyph.pointer(&format!("{}/1", pointer))?.as_f64()?;
+                    Some(glm::vec2(x as f32, y as f32))
+                };
+                let region = match glyph.get("rect").and_then(Value::as_array) {
+                    Some(rect) => {
+                        let rect: Vec<usize> = rect
+                            .iter()
+                            .filter_map(|value| value.as_u64().map(|value| value as usize))
+                            .collect();
+                        let [x, y, width, height] = rect[..] else {
+                            return Err(invalid(&format!("glyph `{}` has a bad rect", key)));
+                        };
+                        if x + width > page_size.0 || y + height > page_size.1 {
+                            return Err(invalid(&format!("glyph `{}` is outside the image", key)));
+                        }
+                        Some(AtlasRegion::new(0, (x, y, width, height), page_size, true))
+                    }
+                    None => None,
+                };
+                let glyph = SdfGlyph {
+                    region,
+                    offset: pair("/offset").unwrap_or_else(glm::Vec2::zeros),
+                    advance: glyph.get("advance").and_then(Value::as_f64).unwrap_or(0.0) as f32,
+                };
+                Ok((character, glyph))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        let kerning = json
+            .get("kerning")
+            .and_then(Value::as_array)
+            .map(|pairs| {
+                pairs
+                    .iter()
+                    .filter_map(|pair| {
+                        let left = pair.get(0)?.as_str()?.chars().next()?;
+                        let right = pair.get(1)?.as_str()?.chars().next()?;
+                        Some(((left, right), pair.get(2)?.as_f64()? as f32))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            image,
+            size: number("size")?,
+            spread: number("spread")?,
+            ascent: number("ascent")?,
+            descent: number("descent")?,
+            line_gap: number("line_gap")?,
+            glyphs,
+            kerning,
+        })
+    }
+}
+
+///
+/// Generates an `SdfFont` from the outlines of a font, printable ASCII by default
+///
+/// Characters the font lacks come from the library's fallback fonts.
+///
+pub struct SdfFontBuilder<'a> {
+    fonts: &'a FontLibrary,
+    font: FontId,
+    size: f32,
+    spread: f32,
+    characters: Vec<char>,
+}
+
+impl<'a> SdfFontBuilder<'a> {
+    pub fn new(fonts: &'a FontLibrary, font: FontId) -> Self {
+        Self {
+            fonts,
+            font,
+            size: 48.0,
+            spread: 8.0,
+            characters: (' '..='~').chain(['…']).collect(),
+        }
+    }
+
+    /// Pixel size the glyphs are generated at, larger keeps sharper corners
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    /// Adds characters beyond printable ASCII
+    pub fn with_characters(mut self, characters: impl IntoIterator<Item = char>) -> Self {
+        for character in characters {
+            if !self.characters.contains(&character) {
+                self.characters.push(character);
+            }
+        }
+        self
+    }
+
+    pub fn build(self) -> Result<SdfFont, AtlasError> {
+        let scale = Scale::uniform(self.size);
+        let mut fields = Vec::new();
+        let mut glyphs = BTreeMap::new();
+        let mut resolved = Vec::with_capacity(self.characters.len());
+        for &character in &self.characters {
+            let font = self.fonts.resolve(self.font, character);
+            let glyph = self.fonts.font(font).glyph(character).scaled(scale);
+            let advance = glyph.h_metrics().advance_width;
+            let (offset, field) = match distance_field(&glyph, self.spread) {
+                Some((image, offset)) => (offset, Some(image)),
+                None => (glm::Vec2::zeros(), None),
+            };
+            glyphs.insert(
+                character,
+                SdfGlyph {
+                    region: None,
+                    offset,
+                    advance,
+                },
+            );
+            fields.extend(field.map(|image| (character, image)));
+            resolved.push((character, font, glyph.id()));
+        }
+
+        // Largest first packs tighter
+        fields.sort_by_key(|(_, image)| std::cmp::Reverse((image.height(), image.width())));
+        let mut page_size = 256;
+        let positions = loop {
+            let mut packer = SkylinePacker::new(page_size, page_size);
+            let positions: Option<Vec<_>> = fields
+                .iter()
+                .map(|(_, image)| {
+                    packer.pack(image.width() as usize + 1, image.height() as usize + 1)
+                })
+                .collect();
+            if let Some(positions) = positions {
+                break positions;
+            }
+            if page_size >= MAX_PAGE_SIZE {
+                let (character, image) = &fields[0];
+                return Err(AtlasError::DoesNotFit {
+                    name: character.to_string(),
+                    width: image.width() as usize,
+                    height: image.height() as usize,
+                });
+            }
+            page_size *= 2;
+        };
+
+        let mut image = GrayImage::new(page_size as u32, page_size as u32);
+        for ((character, field), (x, y)) in fields.iter().zip(positions) {
+            image::imageops::replace(&mut image, field, x as i64, y as i64);
+            let rect = (x, y, field.width() as usize, field.height() as usize);
+            glyphs.get_mut(character).unwrap().region =
+                Some(AtlasRegion::new(0, rect, (page_size, page_size), true));
+        }
+
+        let mut kerning = HashMap::new();
+        for &(left, left_font, left_id) in &resolved {
+            for &(right, right_font, right_id) in &resolved {
+                if left_font != right_font {
+                    continue;
+                }
+                let amount = self
+                    .fonts
+                    .font(left_font)
+                    .pair_kerning(scale, left_id, right_id);
+                if amount != 0.0 {
+                    kerning.insert((left, right), amount);
+                }
+            }
+        }
+
+        let metrics = self.fonts.font(self.font).v_metrics(scale);
+        Ok(SdfFont {
+            image,
+            size: self.size,
+            spread: self.spread,
+            ascent: metrics.ascent,
+            descent: metrics.descent,
+            line_gap: metrics.line_gap,
+            glyphs,
+            kerning,
+        })
+    }
+}
+
+///
+/// Outline, glow and drop shadow of distance field text
+///
+/// Widths and offsets are in pixels at the size the `SdfFont` was generated at, so they
+/// scale with the text. They are limited to the font's spread.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SdfTextStyle {
+    pub outline_width: f32,
+    pub outline_color: glm::Vec4,
+    pub glow_width: f32,
+    pub glow_color: glm::Vec4,
+    /// x right, y down
+    pub shadow_offset: glm::Vec2,
+    pub shadow_softness: f32,
+    pub shadow_color: glm::Vec4,
+}
+
+impl Default for SdfTextStyle {
+    fn default() -> Self {
+        Self {
+            outline_width: 0.0,
+            outline_color: glm::vec4(0.0, 0.0, 0.0, 1.0),
+            glow_width: 0.0,
+            glow_color: glm::vec4(1.0, 1.0, 1.0, 0.0),
+            shadow_offset: glm::vec2(0.0, 0.0),
+            shadow_softness: 0.0,
+            shadow_color: glm::vec4(0.0, 0.0, 0.0, 0.0),
+        }
+    }
+}
+
+impl SdfTextStyle {
+    pub fn with_outline(mut self, width: f32, color: glm::Vec4) -> Self {
+        self.outline_width = width;
+        self.outline_color = color;
+        self
+    }
+
+    pub fn with_glow(mut self, width: f32, color: glm::Vec4) -> Self {
+        self.glow_width = width;
+        self.glow_color = color;
+        self
+    }
+
+    pub fn with_shadow(mut self, offset: glm::Vec2, softness: f32, color: glm::Vec4) -> Self {
+        self.shadow_offset = offset;
+        self.shadow_softness = softness;
+        self.shadow_color = color;
+        self
+    }
+}
+
+///
+/// Draws text from an `SdfFont`, staying sharp at any size or zoom
+///
+/// Unlike `TextRenderer` every size is drawn from the same atlas. Positions are in world
+/// units with y up and `size` is the height of an em in world units, so text can be placed
+/// in the world as well as on screen.
+///
+pub struct SdfTextRenderer {
+    font: SdfFont,
+    texture: Texture,
+    vertices: Vec<GlyphVertex>,
+    batch: GlyphBatch,
+    shader: Shader,
+}
+
+impl SdfTextRenderer {
+    pub fn new(font: SdfFont) -> Result<Self, TextureError> {
+        let texture = Texture::from_image(
+            DynamicImage::ImageLuma8(font.image.clone()),
+            &TextureOptions::default(),
+        )?;
+
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("res/shaders/SdfText.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        shader.bind();
+        shader.uniform_1i("u_DistanceField", 0);
+        shader.unbind();
+
+        Ok(Self {
+            font,
+            texture,
+            vertices: Vec::new(),
+            batch: GlyphBatch::new(),
+            shader,
+        })
+    }
+
+    pub fn font(&self) -> &SdfFont {
+        &self.font
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Adds `text` to the next `prepare`, `position` being the top left corner of the first line
+    pub fn queue(&mut self, text: &str, position: glm::Vec2, size: f32, color: glm::Vec4) {
+        let scale = size / self.font.size;
+        let line_height = self.font.line_height(size);
+        for (index, line) in text.split('\n').enumerate() {
+            let baseline = position.y - self.font.ascent * scale - index as f32 * line_height;
+            let vertices = &mut self.vertices;
+            self.font.layout_line(line, |glyph, x| {
+                let Some(region) = &glyph.region else {
+                    return;
+                };
+                let left = position.x + (x + glyph.offset.x) * scale;
+                let top = baseline - glyph.offset.y * scale;
+                let right = left + region.width as f32 * scale;
+                let bottom = top - region.height as f32 * scale;
+                vertices.extend(GlyphVertex::quad(
+                    (left, bottom),
+                    (right, top),
+                    region,
+                    color,
+                ));
+            });
+        }
+    }
+
+    /// Uploads the queued glyphs, replacing what was prepared before
+    pub fn prepare(&mut self) {
+        self.batch.upload(&self.vertices);
+        self.vertices.clear();
+    }
+
+    /// Draws the glyphs of the last `prepare`
+    pub fn draw(&self, view_projection: &glm::Mat4, style: &SdfTextStyle) {
+        let rgba = |color: &glm::Vec4| (color.x, color.y, color.z, color.w);
+        if self.batch.quads() == 0 {
+            return;
+        }
+
+        let spread = self.font.spread;
+        // Shadows sample the field shifted by the offset, in texture coordinates where v is up
+        let shadow_offset = glm::vec2(
+            style.shadow_offset.x / self.texture.width() as f32,
+            -style.shadow_offset.y / self.texture.height() as f32,
+        );
+
+        self.shader.bind();
+        self.shader
+            .uniform_mat4("u_ViewProjection", view_projection);
+        self.shader.uniform_1f("u_Spread", spread);
+        self.shader
+            .uniform_1f("u_OutlineWidth", style.outline_width.clamp(0.0, spread));
+        self.shader
+            .uniform_4f("u_OutlineColor", rgba(&style.outline_color));
+        self.shader
+            .uniform_1f("u_GlowWidth", style.glow_width.clamp(0.0, spread));
+        self.shader
+            .uniform_4f("u_GlowColor", rgba(&style.glow_color));
+        self.shader
+            .uniform_2f("u_ShadowOffset", (shadow_offset.x, shadow_offset.y));
+        self.shader
+            .uniform_1f("u_ShadowSoftness", style.shadow_softness.clamp(0.0, spread));
+        self.shader
+            .uniform_4f("u_ShadowColor", rgba(&style.shadow_color));
+        self.texture.bind_unit(0);
+        self.batch.draw();
+        self.shader.unbind();
+    }
+
+    /// Prepares and draws everything queued since the last flush
+    pub fn flush(&mut self, view_projection: &glm::Mat4, style: &SdfTextStyle) {
+        self.prepare();
+        self.draw(view_projection, style);
+    }
+}
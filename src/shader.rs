@@ -3,7 +3,7 @@
 use gl::types::{GLchar, GLint};
 use glcall_macro::gl_call;
 use glm::Matrix4;
-use nalgebra_glm::TMat4;
+use nalgebra_glm::{TMat3, TMat4};
 use std::{ffi::CString, ptr};
 
 use crate::gl_component::GLComponent;
@@ -150,6 +150,13 @@ impl Shader {
         });
     }
 
+    pub fn uniform_mat3(&self, location: impl Into<String>, val: &TMat3<f32>) {
+        let location = self.uniform_location(location.into());
+        gl_call!({
+            gl::UniformMatrix3fv(location, 1, gl::FALSE, val.as_ptr());
+        });
+    }
+
     pub fn uniform_1iv(&self, location: impl Into<String>, val: Vec<GLint>) {
         let location = self.uniform_location(location.into());
         gl_call!({
@@ -157,6 +164,21 @@ impl Shader {
         });
     }
 
+    /// Connects the uniform block `block` to a `UniformBuffer` binding point
+    pub fn bind_uniform_block(&self, block: impl Into<String>, binding: u32) {
+        let cname =
+            CString::new(block.into()).expect("Failed to convert uniform block name to CString");
+        let mut index = gl::INVALID_INDEX;
+        gl_call!({
+            index = gl::GetUniformBlockIndex(self.renderer_id, cname.as_ptr());
+        });
+        if index != gl::INVALID_INDEX {
+            gl_call!({
+                gl::UniformBlockBinding(self.renderer_id, index, binding);
+            });
+        }
+    }
+
     pub fn from_sources(fragment_src: impl Into<String>, vertex_src: impl Into<String>) -> Self {
         let fragment_id = {
             let shader_id = unsafe { gl::CreateShader(gl::FRAGMENT_SHADER) };
@@ -0,0 +1,293 @@
+use std::rc::Rc;
+
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::{Camera, PerspectiveCamera},
+    framebuffer::{Framebuffer, FramebufferIncompleteError},
+    gl_component::GLComponent,
+    light::SpotLight,
+    material::Material,
+    renderer::Renderer,
+    texture::Texture,
+};
+
+/// Most cascades `Lit.glsl` can sample
+pub const MAX_CASCADES: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowSettings {
+    /// Width and height of every shadow map, in texels
+    pub resolution: usize,
+    /// Depth bias, scaled up on surfaces facing away from the light
+    pub bias: f32,
+    /// World space offset along the surface normal before the shadow map lookup
+    pub normal_bias: f32,
+    /// PCF kernel radius, `0` for hard shadows and `n` for a `(2n + 1)^2` kernel
+    pub pcf_radius: i32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 2048,
+            bias: 0.002,
+            normal_bias: 0.02,
+            pcf_radius: 1,
+        }
+    }
+}
+
+impl ShadowSettings {
+    pub fn apply(&self, material: Material) -> Material {
+        material
+            .with_uniform("u_ShadowBias", self.bias)
+            .with_uniform("u_ShadowNormalBias", self.normal_bias)
+            .with_uniform("u_PcfRadius", self.pcf_radius)
+    }
+}
+
+///
+/// A single depth map rendered from a light's point of view
+///
+pub struct ShadowMap {
+    framebuffer: Framebuffer,
+    light_space: glm::Mat4,
+}
+
+impl ShadowMap {
+    pub fn new(resolution: usize) -> Result<Self, FramebufferIncompleteError> {
+        Ok(Self {
+            framebuffer: Framebuffer::depth_only(resolution, resolution)?,
+            light_space: glm::Mat4::identity(),
+        })
+    }
+
+    pub fn resolution(&self) -> usize {
+        self.framebuffer.width()
+    }
+
+    pub fn texture(&self) -> &Rc<Texture> {
+        self.framebuffer
+            .depth_attachment()
+            .expect("Shadow map framebuffer has no depth attachment")
+    }
+
+    /// Projection * view matrix of the light the map is rendered from
+    pub fn light_space(&self) -> &glm::Mat4 {
+        &self.light_space
+    }
+
+    pub fn set_light_space(&mut self, light_space: glm::Mat4) {
+        self.light_space = light_space;
+    }
+
+    /// Binds the map as the render target and clears it
+    pub fn begin(&self, renderer: &Renderer) {
+        self.framebuffer.bind();
+        renderer.clear_depth();
+    }
+
+    pub fn end(&self) {
+        self.framebuffer.unbind();
+    }
+
+    /// Binds the map as the shadow map of the first spot light in `Lit.glsl`
+    pub fn apply_spot(&self, material: Material) -> Material {
+        material
+            .with_texture("u_SpotShadowMap", self.texture().clone())
+            .with_uniform("u_SpotLightSpace", self.light_space)
+            .with_uniform("u_SpotShadowEnabled", 1)
+    }
+}
+
+///
+/// Directional light shadows split into cascades along the camera's view distance, each
+/// cascade covering a slice of the view frustum with its own shadow map
+///
+/// Split distances blend between a uniform and a logarithmic distribution with
+/// `split_lambda`, `0.0` being fully uniform.
+///
+pub struct CascadedShadowMap {
+    cascades: Vec<ShadowMap>,
+    splits: Vec<f32>,
+
+    pub split_lambda: f32,
+    /// Shadows are only rendered up to this distance from the camera
+    pub max_distance: f32,
+    /// How far behind a cascade casters are still included
+    pub caster_margin: f32,
+}
+
+impl CascadedShadowMap {
+    pub fn new(
+        cascade_count: usize,
+        resolution: usize,
+    ) -> Result<Self, FramebufferIncompleteError> {
+        let cascade_count = cascade_count.clamp(1, MAX_CASCADES);
+        let cascades = (0..cascade_count)
+            .map(|_| ShadowMap::new(resolution))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            cascades,
+            splits: vec![0.0; cascade_count],
+            split_lambda: 0.75,
+            max_distance: 50.0,
+            caster_margin: 20.0,
+        })
+    }
+
+    pub fn cascades(&self) -> &[ShadowMap] {
+        &self.cascades
+    }
+
+    pub fn cascade_count(&self) -> usize {
+        self.cascades.len()
+    }
+
+    pub fn resolution(&self) -> usize {
+        self.cascades[0].resolution()
+    }
+
+    /// View space distance where each cascade ends
+    pub fn splits(&self) -> &[f32] {
+        &self.splits
+    }
+
+    /// Fits every cascade around its slice of `camera`'s frustum
+    pub fn update(&mut self, camera: &PerspectiveCamera, direction: &glm::Vec3) {
+        let (near, far) = camera.clip_planes();
+        let far = far.min(self.max_distance).max(near);
+        self.splits = cascade_splits(near, far, self.cascades.len(), self.split_lambda);
+
+        let mut slice_near = near;
+        for (cascade, &slice_far) in self.cascades.iter_mut().zip(self.splits.iter()) {
+            let projection = glm::perspective(
+                camera.aspect(),
+                camera.fov().to_radians(),
+                slice_near,
+                slice_far,
+            );
+            let corners = frustum_corners(&glm::inverse(&(projection * camera.view())));
+            cascade.set_light_space(directional_light_space(
+                &corners,
+                direction,
+                cascade.resolution(),
+                self.caster_margin,
+            ));
+            slice_near = slice_far;
+        }
+    }
+
+    /// Binds every cascade for the directional light in `Lit.glsl`
+    pub fn apply(&self, material: Material) -> Material {
+        let mut splits = [0.0; MAX_CASCADES];
+        splits[..self.splits.len()].copy_from_slice(&self.splits);
+
+        let mut material = material
+            .with_uniform("u_ShadowsEnabled", 1)
+            .with_uniform("u_CascadeCount", self.cascades.len() as i32)
+            .with_uniform("u_CascadeSplits", glm::Vec4::from(splits));
+
+        for (i, cascade) in self.cascades.iter().enumerate() {
+            material = material
+                .with_texture(format!("u_CascadeMap{}", i), cascade.texture().clone())
+                .with_uniform(
+                    format!("u_CascadeLightSpace[{}]", i),
+                    *cascade.light_space(),
+                );
+        }
+        material
+    }
+}
+
+/// Far distance of each of `count` slices between `near` and `far`
+pub fn cascade_splits(near: f32, far: f32, count: usize, lambda: f32) -> Vec<f32> {
+    (1..=count)
+        .map(|i| {
+            let p = i as f32 / count as f32;
+            let logarithmic = near * (far / near).powf(p);
+            let uniform = near + (far - near) * p;
+            lambda * logarithmic + (1.0 - lambda) * uniform
+        })
+        .collect()
+}
+
+/// World space corners of the frustum described by an inverse view projection matrix
+pub fn frustum_corners(inverse_view_projection: &glm::Mat4) -> [glm::Vec3; 8] {
+    let mut corners = [glm::Vec3::zeros(); 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+        let ndc = glm::vec4(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { -1.0 } else { 1.0 },
+            1.0,
+        );
+        let world = inverse_view_projection * ndc;
+        *corner = world.xyz() / world.w;
+    }
+    corners
+}
+
+///
+/// Orthographic light space that encloses `corners`, looking along `direction`
+///
+/// The projection is fitted around a bounding sphere and snapped to whole texels so the
+/// shadow edges don't shimmer while the camera moves or turns.
+///
+pub fn directional_light_space(
+    corners: &[glm::Vec3; 8],
+    direction: &glm::Vec3,
+    resolution: usize,
+    caster_margin: f32,
+) -> glm::Mat4 {
+    let center = corners.iter().sum::<glm::Vec3>() / corners.len() as f32;
+    let radius = corners
+        .iter()
+        .map(|corner| glm::distance(corner, &center))
+        .fold(0.0f32, f32::max);
+    let radius = (radius * 16.0).ceil() / 16.0;
+
+    let direction = glm::normalize(direction);
+    let eye = center - direction * (radius + caster_margin);
+    let view = glm::look_at(&eye, &center, &stable_up(&direction));
+    let mut projection = glm::ortho(
+        -radius,
+        radius,
+        -radius,
+        radius,
+        0.0,
+        2.0 * radius + caster_margin,
+    );
+
+    let half_resolution = resolution as f32 / 2.0;
+    let origin = (projection * view) * glm::vec4(0.0, 0.0, 0.0, 1.0);
+    let origin = glm::vec2(origin.x, origin.y) * half_resolution;
+    let offset = (glm::round(&origin) - origin) / half_resolution;
+    projection[(0, 3)] += offset.x;
+    projection[(1, 3)] += offset.y;
+
+    projection * view
+}
+
+/// Perspective light space covering the cone of `light`
+pub fn spot_light_space(light: &SpotLight, near: f32, far: f32) -> glm::Mat4 {
+    let direction = glm::normalize(&light.direction);
+    let fov = (light.outer_angle * 2.0).clamp(1.0, 170.0).to_radians();
+    let projection = glm::perspective(1.0, fov, near, far);
+    let view = glm::look_at(
+        &light.position,
+        &(light.position + direction),
+        &stable_up(&direction),
+    );
+    projection * view
+}
+
+fn stable_up(direction: &glm::Vec3) -> glm::Vec3 {
+    if direction.y.abs() > 0.99 {
+        glm::vec3(0.0, 0.0, 1.0)
+    } else {
+        glm::vec3(0.0, 1.0, 0.0)
+    }
+}
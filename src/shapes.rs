@@ -0,0 +1,328 @@
+use std::{f32::consts::TAU, ffi::c_void};
+
+use glcall_macro::gl_call;
+use memoffset::offset_of;
+use nalgebra_glm as glm;
+
+use crate::{
+    gl_component::GLComponent,
+    shader::Shader,
+    tessellation::{arc_segments, ellipse_points, rounded_rect_points, StrokeStyle, Tessellation},
+    ShaderBuilder,
+};
+
+/// Whether a shape is filled or only its outline is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ShapeStyle {
+    #[default]
+    Fill,
+    Stroke(StrokeStyle),
+}
+
+impl From<StrokeStyle> for ShapeStyle {
+    fn from(value: StrokeStyle) -> Self {
+        Self::Stroke(value)
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct ShapeVertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+///
+/// Immediate mode 2D shapes, tessellated on the CPU and batched into a single draw call
+///
+/// Shapes are queued every frame, e.g. in `update`, then uploaded by `prepare` and drawn by
+/// `draw`. Positions are in world units, curves are subdivided so they stay within
+/// `tolerance` world units of the true curve.
+///
+pub struct ShapeRenderer {
+    tessellation: Tessellation,
+    vertices: Vec<ShapeVertex>,
+    indices: Vec<u32>,
+
+    vao: u32,
+    vbo: u32,
+    ibo: u32,
+    index_count: usize,
+    shader: Shader,
+}
+
+impl Default for ShapeRenderer {
+    fn default() -> Self {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("res/shaders/Shape.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+
+        let mut vao = 0;
+        let mut vbo = 0;
+        let mut ibo = 0;
+        let stride = std::mem::size_of::<ShapeVertex>() as i32;
+        gl_call!({
+            gl::GenVertexArrays(1, &mut vao);
+            gl::BindVertexArray(vao);
+            gl::CreateBuffers(1, &mut vbo);
+            gl::CreateBuffers(1, &mut ibo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ibo);
+
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(
+                0,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset_of!(ShapeVertex, position) as *const c_void,
+            );
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(
+                1,
+                4,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset_of!(ShapeVertex, color) as *const c_void,
+            );
+            gl::BindVertexArray(0);
+        });
+
+        Self {
+            tessellation: Tessellation::default(),
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            vao,
+            vbo,
+            ibo,
+            index_count: 0,
+            shader,
+        }
+    }
+}
+
+impl Drop for ShapeRenderer {
+    fn drop(&mut self) {
+        gl_call!({
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteBuffers(1, &self.ibo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        });
+    }
+}
+
+impl ShapeRenderer {
+    pub fn tolerance(&self) -> f32 {
+        self.tessellation.tolerance
+    }
+
+    /// Smaller values give smoother curves, e.g. when zoomed in
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tessellation.tolerance = tolerance;
+    }
+
+    /// Number of triangles queued since the last `prepare`
+    pub fn triangles(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Moves what was tessellated into the batch with `color`
+    fn commit(&mut self, color: glm::Vec4) {
+        let first = self.vertices.len() as u32;
+        let color = [color.x, color.y, color.z, color.w];
+        self.vertices.extend(
+            self.tessellation
+                .positions
+                .iter()
+                .map(|position| ShapeVertex {
+                    position: [position.x, position.y],
+                    color,
+                }),
+        );
+        self.indices
+            .extend(self.tessellation.indices.iter().map(|index| first + index));
+        self.tessellation.clear();
+    }
+
+    /// Fills or strokes a closed outline, `convex` skips ear clipping
+    fn outline(&mut self, points: &[glm::Vec2], convex: bool, style: ShapeStyle, color: glm::Vec4) {
+        match style {
+            ShapeStyle::Fill if convex => self.tessellation.fill_convex(points),
+            ShapeStyle::Fill => self.tessellation.fill_polygon(points),
+            ShapeStyle::Stroke(stroke) => self.tessellation.stroke(points, true, &stroke),
+        }
+        self.commit(color);
+    }
+
+    pub fn line(
+        &mut self,
+        start: glm::Vec2,
+        end: glm::Vec2,
+        style: &StrokeStyle,
+        color: glm::Vec4,
+    ) {
+        self.polyline(&[start, end], false, style, color);
+    }
+
+    /// Connected line segments, `closed` also connects the last point to the first
+    pub fn polyline(
+        &mut self,
+        points: &[glm::Vec2],
+        closed: bool,
+        style: &StrokeStyle,
+        color: glm::Vec4,
+    ) {
+        self.tessellation.stroke(points, closed, style);
+        self.commit(color);
+    }
+
+    pub fn triangle(
+        &mut self,
+        a: glm::Vec2,
+        b: glm::Vec2,
+        c: glm::Vec2,
+        style: impl Into<ShapeStyle>,
+        color: glm::Vec4,
+    ) {
+        self.outline(&[a, b, c], true, style.into(), color);
+    }
+
+    pub fn rect(
+        &mut self,
+        min: glm::Vec2,
+        max: glm::Vec2,
+        style: impl Into<ShapeStyle>,
+        color: glm::Vec4,
+    ) {
+        self.rounded_rect(min, max, 0.0, style, color);
+    }
+
+    pub fn rounded_rect(
+        &mut self,
+        min: glm::Vec2,
+        max: glm::Vec2,
+        radius: f32,
+        style: impl Into<ShapeStyle>,
+        color: glm::Vec4,
+    ) {
+        let segments = arc_segments(radius, TAU / 4.0, self.tolerance());
+        let points = rounded_rect_points(min, max, radius, segments);
+        self.outline(&points, true, style.into(), color);
+    }
+
+    pub fn circle(
+        &mut self,
+        center: glm::Vec2,
+        radius: f32,
+        style: impl Into<ShapeStyle>,
+        color: glm::Vec4,
+    ) {
+        self.ellipse(center, glm::vec2(radius, radius), style, color);
+    }
+
+    pub fn ellipse(
+        &mut self,
+        center: glm::Vec2,
+        radii: glm::Vec2,
+        style: impl Into<ShapeStyle>,
+        color: glm::Vec4,
+    ) {
+        let segments = arc_segments(radii.x.max(radii.y), TAU, self.tolerance());
+        let points = ellipse_points(center, radii, 0.0, TAU, segments);
+        self.outline(&points, true, style.into(), color);
+    }
+
+    ///
+    /// An arc from `start` to `end` radians, counter clockwise for `end > start`. Filled arcs
+    /// are pie slices, stroked ones only the curve
+    ///
+    pub fn arc(
+        &mut self,
+        center: glm::Vec2,
+        radius: f32,
+        start: f32,
+        end: f32,
+        style: impl Into<ShapeStyle>,
+        color: glm::Vec4,
+    ) {
+        let segments = arc_segments(radius, end - start, self.tolerance());
+        let radii = glm::vec2(radius, radius);
+        let points = ellipse_points(center, radii, start, end, segments);
+        match style.into() {
+            ShapeStyle::Fill => {
+                let mut slice = vec![center];
+                slice.extend(points);
+                self.tessellation.fill_convex(&slice);
+            }
+            ShapeStyle::Stroke(stroke) => {
+                let full = (end - start).abs() >= TAU - 1e-4;
+                self.tessellation.stroke(&points, full, &stroke);
+            }
+        }
+        self.commit(color);
+    }
+
+    /// Any simple polygon, convex or concave
+    pub fn polygon(
+        &mut self,
+        points: &[glm::Vec2],
+        style: impl Into<ShapeStyle>,
+        color: glm::Vec4,
+    ) {
+        self.outline(points, false, style.into(), color);
+    }
+
+    /// Uploads the queued shapes, replacing what was prepared before
+    pub fn prepare(&mut self) {
+        self.index_count = self.indices.len();
+        if self.index_count > 0 {
+            gl_call!({
+                gl::NamedBufferData(
+                    self.vbo,
+                    std::mem::size_of_val(self.vertices.as_slice()) as isize,
+                    self.vertices.as_ptr() as *const c_void,
+                    gl::STREAM_DRAW,
+                );
+                gl::NamedBufferData(
+                    self.ibo,
+                    std::mem::size_of_val(self.indices.as_slice()) as isize,
+                    self.indices.as_ptr() as *const c_void,
+                    gl::STREAM_DRAW,
+                );
+            });
+        }
+        self.vertices.clear();
+        self.indices.clear();
+    }
+
+    /// Draws the shapes of the last `prepare`
+    pub fn draw(&self, view_projection: &glm::Mat4) {
+        if self.index_count == 0 {
+            return;
+        }
+
+        self.shader.bind();
+        self.shader
+            .uniform_mat4("u_ViewProjection", view_projection);
+        gl_call!({
+            gl::BindVertexArray(self.vao);
+            gl::DrawElements(
+                gl::TRIANGLES,
+                self.index_count as i32,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+            );
+            gl::BindVertexArray(0);
+        });
+        self.shader.unbind();
+    }
+
+    /// Prepares and draws everything queued since the last flush
+    pub fn flush(&mut self, view_projection: &glm::Mat4) {
+        self.prepare();
+        self.draw(view_projection);
+    }
+}
@@ -0,0 +1,463 @@
+use std::{collections::BTreeMap, path::Path, rc::Rc};
+
+use serde_json::Value;
+
+use crate::{
+    atlas::{AtlasError, AtlasRegion},
+    texture::{Texture, TextureOptions},
+};
+
+/// Frame indices in playback order, by animation name
+type Animations = BTreeMap<String, Vec<usize>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteFrame {
+    pub name: String,
+    pub region: AtlasRegion,
+    /// Where the trimmed region sits inside the original image, from its top left
+    pub offset: (usize, usize),
+    /// Size of the original image before transparent borders were trimmed
+    pub source_size: (usize, usize),
+    /// Seconds the frame is shown for when animated
+    pub duration: f32,
+}
+
+///
+/// Frames cut out of a single texture, either on a regular grid or described by
+/// TexturePacker or Aseprite JSON
+///
+/// Animations are sequences of frame indices. Aseprite frame tags become animations,
+/// with reverse and ping-pong directions already expanded.
+///
+pub struct SpriteSheet {
+    texture: Rc<Texture>,
+    frames: Vec<SpriteFrame>,
+    animations: Animations,
+}
+
+impl SpriteSheet {
+    ///
+    /// Cuts the texture into frames of `frame_width` x `frame_height` pixels, left to right
+    /// and top to bottom, with `spacing` pixels between them
+    ///
+    /// Frames are named by their index and the texture is expected to have been uploaded
+    /// with the default vertical flip. The whole sheet is a single "default" animation
+    /// running at 10 frames per second.
+    ///
+    pub fn from_grid(
+        texture: Rc<Texture>,
+        frame_width: usize,
+        frame_height: usize,
+        spacing: usize,
+    ) -> Self {
+        let page_size = (texture.width(), texture.height());
+        let frames = Self::grid_frames(page_size, frame_width, frame_height, spacing);
+        let animations = BTreeMap::from([("default".to_string(), (0..frames.len()).collect())]);
+        Self {
+            texture,
+            frames,
+            animations,
+        }
+    }
+
+    fn grid_frames(
+        page_size: (usize, usize),
+        frame_width: usize,
+        frame_height: usize,
+        spacing: usize,
+    ) -> Vec<SpriteFrame> {
+        let columns = (page_size.0 + spacing) / (frame_width + spacing).max(1);
+        let rows = (page_size.1 + spacing) / (frame_height + spacing).max(1);
+
+        (0..rows * columns)
+            .map(|index| {
+                let (column, row) = (index % columns, index / columns);
+                let rect = (
+                    column * (frame_width + spacing),
+                    row * (frame_height + spacing),
+                    frame_width,
+                    frame_height,
+                );
+                SpriteFrame {
+                    name: index.to_string(),
+                    region: AtlasRegion::new(0, rect, page_size, true),
+                    offset: (0, 0),
+                    source_size: (frame_width, frame_height),
+                    duration: 0.1,
+                }
+            })
+            .collect()
+    }
+
+    /// Loads JSON metadata and the image it names in `meta.image`, relative to the JSON file
+    pub fn load(path: impl AsRef<Path>, options: &TextureOptions) -> Result<Self, AtlasError> {
+        let path = path.as_ref();
+        let json: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let image = json
+            .pointer("/meta/image")
+            .and_then(Value::as_str)
+            .ok_or_else(|| AtlasError::InvalidMetadata("missing meta.image".into()))?;
+        let image = path.parent().unwrap_or(Path::new("")).join(image);
+
+        let texture = Texture::load(image.to_string_lossy(), options)?;
+        Self::from_value(Rc::new(texture), &json, options.flip_vertically)
+    }
+
+    ///
+    /// Reads frames from TexturePacker or Aseprite JSON, in either the hash (frames keyed by
+    /// name) or array (frames with a `filename`) layout
+    ///
+    /// The texture is expected to have been uploaded with the default vertical flip.
+    ///
+    pub fn from_json(texture: Rc<Texture>, json: &str) -> Result<Self, AtlasError> {
+        Self::from_value(texture, &serde_json::from_str(json)?, true)
+    }
+
+    fn from_value(texture: Rc<Texture>, json: &Value, flipped: bool) -> Result<Self, AtlasError> {
+        let page_size = (texture.width(), texture.height());
+        let (frames, animations) = Self::parse(json, page_size, flipped)?;
+        Ok(Self {
+            texture,
+            frames,
+            animations,
+        })
+    }
+
+    /// The frames and animations of JSON metadata for a texture of `page_size` pixels
+    fn parse(
+        json: &Value,
+        page_size: (usize, usize),
+        flipped: bool,
+    ) -> Result<(Vec<SpriteFrame>, Animations), AtlasError> {
+        let invalid = |message: String| AtlasError::InvalidMetadata(message);
+        // Frame tags index frames in file order, which `preserve_order` keeps for hashes too
+        let entries: Vec<(String, &Value)> = match json.get("frames") {
+            Some(Value::Object(frames)) => frames
+                .iter()
+                .map(|(name, frame)| (name.clone(), frame))
+                .collect(),
+            Some(Value::Array(frames)) => frames
+                .iter()
+                .map(|frame| {
+                    let name = frame.get("filename").and_then(Value::as_str);
+                    name.map(|name| (name.to_string(), frame))
+                        .ok_or_else(|| invalid("frame without a filename".into()))
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err(invalid("missing frames".into())),
+        };
+
+        let frames = entries
+            .into_iter()
+            .map(|(name, frame)| {
+                let field = |pointer: &str| {
+                    frame
+                        .pointer(pointer)
+                        .and_then(Value::as_u64)
+                        .map(|value| value as usize)
+                };
+                let required = |pointer: &str| {
+                    field(pointer)
+                        .ok_or_else(|| invalid(format!("frame `{}` lacks {}", name, pointer)))
+                };
+
+                let (x, y) = (required("/frame/x")?, required("/frame/y")?);
+                let (width, height) = (required("/frame/w")?, required("/frame/h")?);
+                let rotated = frame.get("rotated").and_then(Value::as_bool) == Some(true);
+
+                // Rotated frames list the upright size, the stored rectangle is turned sideways
+                let rect = if rotated {
+                    (x, y, height, width)
+                } else {
+                    (x, y, width, height)
+                };
+                if rect.0 + rect.2 > page_size.0 || rect.1 + rect.3 > page_size.1 {
+                    return Err(invalid(format!("frame `{}` is outside the texture", name)));
+                }
+
+                let mut region = AtlasRegion::new(0, rect, page_size, flipped);
+                region.rotated = rotated;
+                Ok(SpriteFrame {
+                    offset: (
+                        field("/spriteSourceSize/x").unwrap_or(0),
+                        field("/spriteSourceSize/y").unwrap_or(0),
+                    ),
+                    source_size: (
+                        field("/sourceSize/w").unwrap_or(width),
+                        field("/sourceSize/h").unwrap_or(height),
+                    ),
+                    duration: field("/duration").unwrap_or(100) as f32 / 1000.0,
+                    name,
+                    region,
+                })
+            })
+            .collect::<Result<Vec<_>, AtlasError>>()?;
+
+        let mut animations = BTreeMap::new();
+        let tags = json.pointer("/meta/frameTags").and_then(Value::as_array);
+        for tag in tags.into_iter().flatten() {
+            let name = tag.get("name").and_then(Value::as_str);
+            let from = tag.get("from").and_then(Value::as_u64);
+            let to = tag.get("to").and_then(Value::as_u64);
+            let (Some(name), Some(from), Some(to)) = (name, from, to) else {
+                return Err(invalid("frame tag without name, from or to".into()));
+            };
+            let (from, to) = (from as usize, to as usize);
+            if from > to || to >= frames.len() {
+                return Err(invalid(format!("frame tag `{}` is out of range", name)));
+            }
+
+            let forward: Vec<usize> = (from..=to).collect();
+            let sequence = match tag.get("direction").and_then(Value::as_str) {
+                Some("reverse") => forward.into_iter().rev().collect(),
+                // Plays back without repeating the first and last frame
+                Some("pingpong") => {
+                    let mut sequence = forward;
+                    sequence.extend((from + 1..to).rev());
+                    sequence
+                }
+                _ => forward,
+            };
+            animations.insert(name.to_string(), sequence);
+        }
+        if animations.is_empty() {
+            animations.insert("default".to_string(), (0..frames.len()).collect());
+        }
+
+        Ok((frames, animations))
+    }
+
+    pub fn texture(&self) -> &Rc<Texture> {
+        &self.texture
+    }
+
+    pub fn frames(&self) -> &[SpriteFrame] {
+        &self.frames
+    }
+
+    pub fn frame(&self, index: usize) -> Option<&SpriteFrame> {
+        self.frames.get(index)
+    }
+
+    pub fn frame_by_name(&self, name: &str) -> Option<&SpriteFrame> {
+        self.frames.iter().find(|frame| frame.name == name)
+    }
+
+    /// Frame indices of an animation, in playback order
+    pub fn animation(&self, name: &str) -> Option<&[usize]> {
+        self.animations.get(name).map(Vec::as_slice)
+    }
+
+    pub fn animation_names(&self) -> impl Iterator<Item = &String> {
+        self.animations.keys()
+    }
+
+    /// The frame of a looping animation shown `time` seconds after it started
+    pub fn frame_at(&self, animation: &str, time: f32) -> Option<&SpriteFrame> {
+        let sequence = self.animations.get(animation)?;
+        let length: f32 = sequence.iter().map(|&i| self.frames[i].duration).sum();
+        if length <= 0.0 {
+            return sequence.first().map(|&i| &self.frames[i]);
+        }
+
+        let mut time = time.rem_euclid(length);
+        for &index in sequence {
+            let frame = &self.frames[index];
+            if time < frame.duration {
+                return Some(frame);
+            }
+            time -= frame.duration;
+        }
+        sequence.last().map(|&i| &self.frames[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXTURE_PACKER: &str = r#"{
+        "frames": {
+            "idle.png": {
+                "frame": { "x": 0, "y": 0, "w": 32, "h": 16 },
+                "rotated": false,
+                "trimmed": true,
+                "spriteSourceSize": { "x": 4, "y": 8, "w": 32, "h": 16 },
+                "sourceSize": { "w": 40, "h": 32 }
+            },
+            "jump.png": {
+                "frame": { "x": 32, "y": 0, "w": 16, "h": 32 },
+                "rotated": true
+            }
+        },
+        "meta": { "image": "sheet.png", "size": { "w": 64, "h": 64 } }
+    }"#;
+
+    const ASEPRITE: &str = r#"{
+        "frames": [
+            { "filename": "run 0", "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+            { "filename": "run 1", "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "duration": 50 },
+            { "filename": "run 2", "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+            { "filename": "run 3", "frame": { "x": 48, "y": 0, "w": 16, "h": 16 }, "duration": 200 }
+        ],
+        "meta": {
+            "image": "sheet.png",
+            "frameTags": [
+                { "name": "forward", "from": 0, "to": 3, "direction": "forward" },
+                { "name": "reverse", "from": 1, "to": 3, "direction": "reverse" },
+                { "name": "pingpong", "from": 0, "to": 3, "direction": "pingpong" }
+            ]
+        }
+    }"#;
+
+    fn parse(json: &str) -> Result<(Vec<SpriteFrame>, Animations), AtlasError> {
+        SpriteSheet::parse(&serde_json::from_str(json)?, (64, 64), true)
+    }
+
+    fn invalid(json: &str) -> String {
+        match parse(json) {
+            Err(AtlasError::InvalidMetadata(message)) => message,
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("{} parsed", json),
+        }
+    }
+
+    #[test]
+    fn grid() {
+        let frames = SpriteSheet::grid_frames((64, 64), 16, 16, 4);
+        // Three columns and rows fit with the spacing, a fourth would end past the edge
+        assert_eq!(frames.len(), 3 * 3);
+
+        let frame = &frames[4];
+        assert_eq!(frame.name, "4");
+        let region = frame.region;
+        assert_eq!(
+            (region.x, region.y, region.width, region.height),
+            (20, 20, 16, 16)
+        );
+        assert_eq!(region.uv_top_left, [0.3125, 0.6875]);
+        assert_eq!(region.uv_bottom_right, [0.5625, 0.4375]);
+        assert_eq!(frame.source_size, (16, 16));
+    }
+
+    #[test]
+    fn grid_without_spacing() {
+        let frames = SpriteSheet::grid_frames((64, 64), 16, 32, 0);
+        assert_eq!(frames.len(), 8);
+        assert_eq!((frames[7].region.x, frames[7].region.y), (48, 32));
+    }
+
+    #[test]
+    fn texture_packer_hash() {
+        let (frames, animations) = parse(TEXTURE_PACKER).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(animations["default"], [0, 1]);
+
+        let idle = frames
+            .iter()
+            .find(|frame| frame.name == "idle.png")
+            .unwrap();
+        assert_eq!(idle.offset, (4, 8));
+        assert_eq!(idle.source_size, (40, 32));
+        assert_eq!(idle.region.uv_top_left, [0.0, 1.0]);
+        assert_eq!(idle.region.uv_bottom_right, [0.5, 0.75]);
+        assert_eq!(idle.duration, 0.1);
+
+        // Stored sideways, the listed size is the upright one
+        let jump = frames
+            .iter()
+            .find(|frame| frame.name == "jump.png")
+            .unwrap();
+        assert!(jump.region.rotated);
+        assert_eq!((jump.region.width, jump.region.height), (32, 16));
+        assert_eq!(jump.region.size(), (16, 32));
+        assert_eq!(jump.source_size, (16, 32));
+    }
+
+    #[test]
+    fn aseprite_array_and_tags() {
+        let (frames, animations) = parse(ASEPRITE).unwrap();
+        let names: Vec<_> = frames.iter().map(|frame| frame.name.as_str()).collect();
+        assert_eq!(names, ["run 0", "run 1", "run 2", "run 3"]);
+        assert_eq!(frames[1].duration, 0.05);
+        assert_eq!(frames[3].region.x, 48);
+
+        assert_eq!(animations.len(), 3);
+        assert_eq!(animations["forward"], [0, 1, 2, 3]);
+        assert_eq!(animations["reverse"], [3, 2, 1]);
+        assert_eq!(animations["pingpong"], [0, 1, 2, 3, 2, 1]);
+    }
+
+    #[test]
+    fn hash_keeps_file_order() {
+        // Sorted by name "run 10" and "run 11" would come before "run 2"
+        let frames: Vec<String> = (0..12)
+            .map(|i| {
+                format!(
+                    r#""run {}": {{ "frame": {{ "x": {}, "y": {}, "w": 16, "h": 16 }} }}"#,
+                    i,
+                    i % 4 * 16,
+                    i / 4 * 16
+                )
+            })
+            .collect();
+        let json = format!(
+            r#"{{
+                "frames": {{ {} }},
+                "meta": {{ "frameTags": [{{ "name": "run", "from": 2, "to": 10 }}] }}
+            }}"#,
+            frames.join(", ")
+        );
+
+        let (frames, animations) = parse(&json).unwrap();
+        let names: Vec<_> = frames.iter().map(|frame| frame.name.as_str()).collect();
+        let expected: Vec<_> = (0..12).map(|i| format!("run {}", i)).collect();
+        assert_eq!(names, expected);
+        assert_eq!(animations["run"], (2..=10).collect::<Vec<_>>());
+        assert_eq!((frames[10].region.x, frames[10].region.y), (32, 32));
+    }
+
+    #[test]
+    fn malformed_json() {
+        assert!(matches!(parse("{ \"frames\": "), Err(AtlasError::Json(_))));
+        assert!(invalid("{}").contains("missing frames"));
+        assert!(invalid(r#"{ "frames": 3 }"#).contains("missing frames"));
+    }
+
+    #[test]
+    fn malformed_frames() {
+        let message = invalid(r#"{ "frames": { "a": { "frame": { "x": 0, "y": 0, "w": 8 } } } }"#);
+        assert!(message.contains("`a`") && message.contains("/frame/h"));
+
+        let message =
+            invalid(r#"{ "frames": { "a": { "frame": { "x": 0, "y": "0", "w": 8, "h": 8 } } } }"#);
+        assert!(message.contains("/frame/y"));
+
+        let message = invalid(r#"{ "frames": [{ "frame": { "x": 0, "y": 0, "w": 8, "h": 8 } }] }"#);
+        assert!(message.contains("filename"));
+
+        let message =
+            invalid(r#"{ "frames": { "a": { "frame": { "x": 60, "y": 0, "w": 8, "h": 8 } } } }"#);
+        assert!(message.contains("outside"));
+
+        // Fits upright but not turned sideways
+        let message = invalid(
+            r#"{ "frames": { "a": { "frame": { "x": 0, "y": 48, "w": 32, "h": 8 }, "rotated": true } } }"#,
+        );
+        assert!(message.contains("outside"));
+    }
+
+    #[test]
+    fn malformed_tags() {
+        let with_tags = |tags: &str| {
+            ASEPRITE.replace(r#""frameTags": ["#, &format!(r#""frameTags": [{}, "#, tags))
+        };
+
+        let message = invalid(&with_tags(r#"{ "name": "broken", "from": 2, "to": 4 }"#));
+        assert!(message.contains("`broken`"));
+        let message = invalid(&with_tags(r#"{ "name": "backwards", "from": 2, "to": 1 }"#));
+        assert!(message.contains("out of range"));
+        let message = invalid(&with_tags(r#"{ "name": "open", "from": 0 }"#));
+        assert!(message.contains("without"));
+    }
+}
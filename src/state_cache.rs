@@ -0,0 +1,174 @@
+use std::cell::{Cell, RefCell};
+
+use glcall_macro::gl_call;
+
+use crate::render_state::{BlendState, DepthState, RasterizerState, StencilState};
+
+/// How many binds and state changes a `GlStateCache` sent to the driver and how many it skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StateCacheStats {
+    pub issued: usize,
+    pub skipped: usize,
+}
+
+///
+/// Remembers the bound program, vertex array, index buffer, textures and samplers, and the
+/// render states, so binding the same object or setting the same state twice in a row does
+/// not reach the driver
+///
+/// The cache only knows about changes made through it. Code binding objects directly, e.g.
+/// `Shader::bind`, leaves it stale, so its bindings are invalidated before every batch of
+/// cached draws. States are only changed through the renderer and invalidated once a frame.
+///
+#[derive(Debug, Default)]
+pub struct GlStateCache {
+    program: Cell<Option<u32>>,
+    vertex_array: Cell<Option<u32>>,
+    index_buffer: Cell<Option<u32>>,
+    textures: RefCell<Vec<Option<u32>>>,
+    samplers: RefCell<Vec<Option<u32>>>,
+    blend: Cell<Option<BlendState>>,
+    depth: Cell<Option<DepthState>>,
+    stencil: Cell<Option<StencilState>>,
+    rasterizer: Cell<Option<RasterizerState>>,
+    stats: Cell<StateCacheStats>,
+}
+
+impl GlStateCache {
+    /// Forgets every binding and state, the next change of each kind always reaches the driver
+    pub fn invalidate(&self) {
+        self.invalidate_bindings();
+        self.blend.set(None);
+        self.depth.set(None);
+        self.stencil.set(None);
+        self.rasterizer.set(None);
+    }
+
+    /// Forgets the bound objects but keeps the render states
+    pub fn invalidate_bindings(&self) {
+        self.program.set(None);
+        self.vertex_array.set(None);
+        self.index_buffer.set(None);
+        self.textures.borrow_mut().clear();
+        self.samplers.borrow_mut().clear();
+    }
+
+    pub fn stats(&self) -> StateCacheStats {
+        self.stats.get()
+    }
+
+    pub fn reset_stats(&self) {
+        self.stats.set(StateCacheStats::default());
+    }
+
+    /// Counts the change and tells whether `value` differs from what is cached
+    fn changed<T: Copy + PartialEq>(&self, cached: &Cell<Option<T>>, value: T) -> bool {
+        let mut stats = self.stats.get();
+        let changed = cached.get() != Some(value);
+        if changed {
+            cached.set(Some(value));
+            stats.issued += 1;
+        } else {
+            stats.skipped += 1;
+        }
+        self.stats.set(stats);
+        changed
+    }
+
+    fn unit_changed(&self, units: &RefCell<Vec<Option<u32>>>, unit: u32, value: u32) -> bool {
+        let mut units = units.borrow_mut();
+        let unit = unit as usize;
+        if units.len() <= unit {
+            units.resize(unit + 1, None);
+        }
+        let cached = Cell::new(units[unit]);
+        let changed = self.changed(&cached, value);
+        units[unit] = cached.get();
+        changed
+    }
+
+    pub fn use_program(&self, program: u32) {
+        if self.changed(&self.program, program) {
+            gl_call!({
+                gl::UseProgram(program);
+            });
+        }
+    }
+
+    /// Binding a vertex array also binds the index buffer it recorded, so that is forgotten
+    pub fn bind_vertex_array(&self, vertex_array: u32) {
+        if self.changed(&self.vertex_array, vertex_array) {
+            self.index_buffer.set(None);
+            gl_call!({
+                gl::BindVertexArray(vertex_array);
+            });
+        }
+    }
+
+    pub fn bind_index_buffer(&self, index_buffer: u32) {
+        if self.changed(&self.index_buffer, index_buffer) {
+            gl_call!({
+                gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, index_buffer);
+            });
+        }
+    }
+
+    /// Binds `texture` to `unit` whatever its target is, without changing the active unit
+    pub fn bind_texture(&self, unit: u32, texture: u32) {
+        if self.unit_changed(&self.textures, unit, texture) {
+            gl_call!({
+                gl::BindTextureUnit(unit, texture);
+            });
+        }
+    }
+
+    /// Zero falls back to the parameters of the texture bound to `unit`
+    pub fn bind_sampler(&self, unit: u32, sampler: u32) {
+        if self.unit_changed(&self.samplers, unit, sampler) {
+            gl_call!({
+                gl::BindSampler(unit, sampler);
+            });
+        }
+    }
+
+    /// The last blend state set, `None` after invalidating
+    pub fn blend(&self) -> Option<BlendState> {
+        self.blend.get()
+    }
+
+    pub fn depth(&self) -> Option<DepthState> {
+        self.depth.get()
+    }
+
+    pub fn stencil(&self) -> Option<StencilState> {
+        self.stencil.get()
+    }
+
+    pub fn rasterizer(&self) -> Option<RasterizerState> {
+        self.rasterizer.get()
+    }
+
+    pub fn set_blend(&self, blend: &BlendState) {
+        if self.changed(&self.blend, *blend) {
+            blend.apply();
+        }
+    }
+
+    pub fn set_depth(&self, depth: &DepthState) {
+        if self.changed(&self.depth, *depth) {
+            depth.apply();
+        }
+    }
+
+    pub fn set_stencil(&self, stencil: &StencilState) {
+        if self.changed(&self.stencil, *stencil) {
+            stencil.apply();
+        }
+    }
+
+    pub fn set_rasterizer(&self, rasterizer: &RasterizerState) {
+        if self.changed(&self.rasterizer, *rasterizer) {
+            rasterizer.apply();
+        }
+    }
+}
@@ -0,0 +1,723 @@
+use std::f32::consts::{PI, TAU};
+
+use nalgebra_glm as glm;
+
+/// How the open ends of a stroke are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// Ends exactly at the end points
+    #[default]
+    Butt,
+    /// Extends half the width past the end points
+    Square,
+    Round,
+}
+
+/// How the corners between segments of a stroke are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Sharp corners, beveled once longer than the miter limit
+    #[default]
+    Miter,
+    Bevel,
+    Round,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    /// Longest miter as a multiple of half the width
+    pub miter_limit: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+        }
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+}
+
+fn cross(a: glm::Vec2, b: glm::Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Left hand normal of a direction
+fn normal(direction: glm::Vec2) -> glm::Vec2 {
+    glm::vec2(-direction.y, direction.x)
+}
+
+/// Twice the signed area of a polygon, positive if counter clockwise
+fn signed_area(points: &[glm::Vec2]) -> f32 {
+    (0..points.len())
+        .map(|i| cross(points[i], points[(i + 1) % points.len()]))
+        .sum()
+}
+
+/// Whether `point` is inside or on the edges of the counter clockwise triangle `a`, `b`, `c`
+fn in_triangle(point: glm::Vec2, a: glm::Vec2, b: glm::Vec2, c: glm::Vec2) -> bool {
+    cross(b - a, point - a) >= 0.0
+        && cross(c - b, point - b) >= 0.0
+        && cross(a - c, point - c) >= 0.0
+}
+
+///
+/// Splits a simple polygon, convex or concave and in either winding, into triangles by ear
+/// clipping. Returns indices into `points`, counter clockwise
+///
+/// Self intersecting polygons still produce triangles but may be covered incorrectly.
+///
+pub fn triangulate(points: &[glm::Vec2]) -> Vec<[u32; 3]> {
+    if points.len() < 3 {
+        return Vec::new();
+    }
+
+    let mut remaining: Vec<u32> = (0..points.len() as u32).collect();
+    if signed_area(points) < 0.0 {
+        remaining.reverse();
+    }
+    let point = |index: u32| points[index as usize];
+
+    let mut triangles = Vec::with_capacity(points.len() - 2);
+    let mut i = 0;
+    let mut attempts = 0;
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        i %= count;
+        let (previous, current, next) = (
+            remaining[(i + count - 1) % count],
+            remaining[i],
+            remaining[(i + 1) % count],
+        );
+        let (a, b, c) = (point(previous), point(current), point(next));
+
+        let convex = cross(b - a, c - b) > 0.0;
+        let is_ear = convex
+            && !remaining.iter().any(|&other| {
+                let p = point(other);
+                ![previous, current, next].contains(&other)
+                    && p != a
+                    && p != b
+                    && p != c
+                    && in_triangle(p, a, b, c)
+            });
+
+        // Without any ear left the polygon intersects itself, clip anyway to finish
+        if is_ear || attempts > count {
+            triangles.push([previous, current, next]);
+            remaining.remove(i);
+            attempts = 0;
+        } else {
+            i += 1;
+            attempts += 1;
+        }
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+/// Segments needed for an arc of `radius` and `sweep` radians to stay within `tolerance`
+/// of the true curve
+pub fn arc_segments(radius: f32, sweep: f32, tolerance: f32) -> usize {
+    if radius <= tolerance {
+        return 4;
+    }
+    let step = 2.0 * (1.0 - tolerance / radius).acos();
+    ((sweep.abs() / step).ceil() as usize).clamp(4, 256)
+}
+
+///
+/// Points along an elliptic arc from `start` to `end` radians, counter clockwise for
+/// `end > start`. Both ends are included unless the arc is a full turn
+///
+pub fn ellipse_points(
+    center: glm::Vec2,
+    radii: glm::Vec2,
+    start: f32,
+    end: f32,
+    segments: usize,
+) -> Vec<glm::Vec2> {
+    let segments = segments.max(1);
+    let full = (end - start).abs() >= TAU - 1e-4;
+    let count = if full { segments } else { segments + 1 };
+    (0..count)
+        .map(|i| {
+            let angle = start + (end - start) * i as f32 / segments as f32;
+            center + glm::vec2(angle.cos() * radii.x, angle.sin() * radii.y)
+        })
+        .collect()
+}
+
+/// Counter clockwise outline of a rectangle with corners rounded by `radius`
+pub fn rounded_rect_points(
+    min: glm::Vec2,
+    max: glm::Vec2,
+    radius: f32,
+    segments_per_corner: usize,
+) -> Vec<glm::Vec2> {
+    let radius = radius.min((max.x - min.x) / 2.0).min((max.y - min.y) / 2.0);
+    if radius <= 0.0 {
+        return vec![min, glm::vec2(max.x, min.y), max, glm::vec2(min.x, max.y)];
+    }
+
+    let corners = [
+        (glm::vec2(max.x - radius, min.y + radius), -PI / 2.0),
+        (glm::vec2(max.x - radius, max.y - radius), 0.0),
+        (glm::vec2(min.x + radius, max.y - radius), PI / 2.0),
+        (glm::vec2(min.x + radius, min.y + radius), PI),
+    ];
+    corners
+        .into_iter()
+        .flat_map(|(center, start)| {
+            ellipse_points(
+                center,
+                glm::vec2(radius, radius),
+                start,
+                start + PI / 2.0,
+                segments_per_corner,
+            )
+        })
+        .collect()
+}
+
+///
+/// Triangles of filled and stroked shapes, to be drawn with `GL_TRIANGLES`
+///
+/// Tessellation is pure CPU work, `ShapeRenderer` batches the results.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tessellation {
+    pub positions: Vec<glm::Vec2>,
+    pub indices: Vec<u32>,
+    /// Furthest round caps and joins may deviate from a true circle
+    pub tolerance: f32,
+}
+
+impl Default for Tessellation {
+    fn default() -> Self {
+        Self {
+            positions: Vec::new(),
+            indices: Vec::new(),
+            tolerance: 0.25,
+        }
+    }
+}
+
+impl Tessellation {
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.indices.clear();
+    }
+
+    fn push(&mut self, position: glm::Vec2) -> u32 {
+        self.positions.push(position);
+        self.positions.len() as u32 - 1
+    }
+
+    pub fn triangle(&mut self, a: glm::Vec2, b: glm::Vec2, c: glm::Vec2) {
+        let first = self.push(a);
+        self.push(b);
+        self.push(c);
+        self.indices.extend([first, first + 1, first + 2]);
+    }
+
+    fn quad(&mut self, a: glm::Vec2, b: glm::Vec2, c: glm::Vec2, d: glm::Vec2) {
+        let first = self.push(a);
+        self.push(b);
+        self.push(c);
+        self.push(d);
+        self.indices
+            .extend([0, 1, 2, 2, 3, 0].map(|index| first + index));
+    }
+
+    ///
+    /// Fills a fan around the first point, which covers convex polygons as well as shapes
+    /// whose outline is fully visible from the first point, like pie slices
+    ///
+    pub fn fill_convex(&mut self, points: &[glm::Vec2]) {
+        if points.len() < 3 {
+            return;
+        }
+        let first = self.positions.len() as u32;
+        self.positions.extend_from_slice(points);
+        for i in 1..points.len() as u32 - 1 {
+            self.indices.extend([first, first + i, first + i + 1]);
+        }
+    }
+
+    /// Fills any simple polygon, see `triangulate`
+    pub fn fill_polygon(&mut self, points: &[glm::Vec2]) {
+        let first = self.positions.len() as u32;
+        self.positions.extend_from_slice(points);
+        for triangle in triangulate(points) {
+            self.indices.extend(triangle.map(|index| first + index));
+        }
+    }
+
+    /// A fan of triangles around `center` from `start` over `sweep` radians
+    fn fan(&mut self, center: glm::Vec2, radius: f32, start: f32, sweep: f32) {
+        let segments = arc_segments(radius, sweep, self.tolerance);
+        let radii = glm::vec2(radius, radius);
+        let mut points = vec![center];
+        points.extend(ellipse_points(
+            center,
+            radii,
+            start,
+            start + sweep,
+            segments,
+        ));
+        self.fill_convex(&points);
+    }
+
+    ///
+    /// Strokes a polyline, or a polygon if `closed`
+    ///
+    /// Each segment is a quad and joins fill the gaps on the outer side of corners, so
+    /// translucent strokes are slightly darker where segments overlap inside corners.
+    ///
+    pub fn stroke(&mut self, points: &[glm::Vec2], closed: bool, style: &StrokeStyle) {
+        let mut points = points.to_vec();
+        points.dedup();
+        if closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        let half = style.width / 2.0;
+        if points.len() < 2 || half <= 0.0 {
+            return;
+        }
+
+        let count = points.len();
+        let segments = if closed && count > 2 {
+            count
+        } else {
+            count - 1
+        };
+        let closed = segments == count;
+        let direction = |i: usize| glm::normalize(&(points[(i + 1) % count] - points[i]));
+
+        for i in 0..segments {
+            let (mut start, mut end) = (points[i], points[(i + 1) % count]);
+            let direction = direction(i);
+            if !closed && style.cap == LineCap::Square {
+                if i == 0 {
+                    start -= direction * half;
+                }
+                if i == segments - 1 {
+                    end += direction * half;
+                }
+            }
+            let offset = normal(direction) * half;
+            self.quad(start - offset, end - offset, end + offset, start + offset);
+        }
+
+        let joints = if closed { 0..count } else { 1..count - 1 };
+        for joint in joints {
+            let incoming = direction((joint + count - 1) % count);
+            let outgoing = direction(joint);
+            self.join(points[joint], incoming, outgoing, half, style);
+        }
+
+        if !closed && style.cap == LineCap::Round {
+            let first = direction(0);
+            let last = direction(segments - 1);
+            let angle = |direction: glm::Vec2| direction.y.atan2(direction.x);
+            self.fan(points[0], half, angle(first) + PI / 2.0, PI);
+            self.fan(points[count - 1], half, angle(last) - PI / 2.0, PI);
+        }
+    }
+
+    /// Fills the gap on the outer side of a corner
+    fn join(
+        &mut self,
+        point: glm::Vec2,
+        incoming: glm::Vec2,
+        outgoing: glm::Vec2,
+        half: f32,
+        style: &StrokeStyle,
+    ) {
+        let turn = cross(incoming, outgoing);
+        if turn.abs() < 1e-6 && glm::dot(&incoming, &outgoing) > 0.0 {
+            return;
+        }
+        // Turning left opens a gap on the right
+        let side = if turn > 0.0 { -1.0 } else { 1.0 };
+        let (before, after) = (normal(incoming) * side, normal(outgoing) * side);
+        let (outer_in, outer_out) = (point + before * half, point + after * half);
+
+        match style.join {
+            LineJoin::Round => {
+                // The outer side is always the shorter way around
+                let start = before.y.atan2(before.x);
+                let sweep = cross(before, after).atan2(glm::dot(&before, &after));
+                self.fan(point, half, start, sweep);
+            }
+            LineJoin::Miter => {
+                let bisector = before + after;
+                let cos = glm::length(&bisector) / 2.0;
+                if cos > 1e-6 && 1.0 / cos <= style.miter_limit {
+                    let tip = point + glm::normalize(&bisector) * (half / cos);
+                    self.quad(point, outer_in, tip, outer_out);
+                } else {
+                    self.triangle(point, outer_in, outer_out);
+                }
+            }
+            LineJoin::Bevel => self.triangle(point, outer_in, outer_out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coordinates: &[(f32, f32)]) -> Vec<glm::Vec2> {
+        coordinates.iter().map(|&(x, y)| glm::vec2(x, y)).collect()
+    }
+
+    /// Twice the signed area of each triangle
+    fn triangle_areas(points: &[glm::Vec2], triangles: &[[u32; 3]]) -> Vec<f32> {
+        triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|i| points[i as usize]);
+                cross(b - a, c - a)
+            })
+            .collect()
+    }
+
+    /// Covers the polygon once with counter clockwise triangles, whatever the input winding
+    fn assert_triangulated(points: &[glm::Vec2]) {
+        let triangles = triangulate(points);
+        assert_eq!(triangles.len(), points.len() - 2);
+
+        let areas = triangle_areas(points, &triangles);
+        for (triangle, area) in triangles.iter().zip(&areas) {
+            assert!(*area >= -1e-4, "{:?} is clockwise", triangle);
+        }
+        let total: f32 = areas.iter().sum();
+        assert!(
+            (total - signed_area(points).abs()).abs() < 1e-3,
+            "{} != {}",
+            total,
+            signed_area(points)
+        );
+    }
+
+    fn assert_both_windings(mut points: Vec<glm::Vec2>) {
+        assert_triangulated(&points);
+        points.reverse();
+        assert_triangulated(&points);
+    }
+
+    /// Area covered by a tessellation, counting overlaps twice
+    fn area(tessellation: &Tessellation) -> f32 {
+        tessellation
+            .indices
+            .chunks_exact(3)
+            .map(|t| {
+                let [a, b, c] = [t[0], t[1], t[2]].map(|i| tessellation.positions[i as usize]);
+                cross(b - a, c - a).abs() / 2.0
+            })
+            .sum()
+    }
+
+    fn bounds(tessellation: &Tessellation) -> (glm::Vec2, glm::Vec2) {
+        tessellation.positions.iter().fold(
+            (glm::vec2(f32::MAX, f32::MAX), glm::vec2(f32::MIN, f32::MIN)),
+            |(min, max), p| (glm::min2(&min, p), glm::max2(&max, p)),
+        )
+    }
+
+    fn assert_close(a: f32, b: f32, epsilon: f32) {
+        assert!((a - b).abs() < epsilon, "{} != {}", a, b);
+    }
+
+    fn stroke(line: &[(f32, f32)], closed: bool, style: &StrokeStyle) -> Tessellation {
+        let mut tessellation = Tessellation::default().with_tolerance(0.01);
+        tessellation.stroke(&points(line), closed, style);
+        tessellation
+    }
+
+    /// Area of the stroke added on top of the segment quads of an open polyline
+    fn extra_area(points: &[(f32, f32)], style: &StrokeStyle) -> f32 {
+        let segments: f32 = points
+            .windows(2)
+            .map(|w| glm::distance(&glm::vec2(w[0].0, w[0].1), &glm::vec2(w[1].0, w[1].1)))
+            .sum();
+        area(&stroke(points, false, style)) - segments * style.width
+    }
+
+    #[test]
+    fn signed_area_follows_winding() {
+        let mut square = points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+        assert_eq!(signed_area(&square), 8.0);
+        square.reverse();
+        assert_eq!(signed_area(&square), -8.0);
+    }
+
+    #[test]
+    fn convex() {
+        assert_both_windings(points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]));
+        assert_both_windings(ellipse_points(
+            glm::vec2(1.0, 1.0),
+            glm::vec2(3.0, 2.0),
+            0.0,
+            TAU,
+            24,
+        ));
+    }
+
+    #[test]
+    fn concave() {
+        // L shape
+        assert_both_windings(points(&[
+            (0.0, 0.0),
+            (3.0, 0.0),
+            (3.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 3.0),
+            (0.0, 3.0),
+        ]));
+        // Arrow head with a reflex vertex
+        assert_both_windings(points(&[(0.0, 0.0), (2.0, 0.0), (1.0, 0.5), (1.0, 2.0)]));
+
+        let star: Vec<_> = (0..10)
+            .map(|i| {
+                let angle = i as f32 * TAU / 10.0;
+                let radius = if i % 2 == 0 { 2.0 } else { 0.8 };
+                glm::vec2(angle.cos(), angle.sin()) * radius
+            })
+            .collect();
+        assert_both_windings(star);
+
+        // Comb, reflex vertices whose ears contain other vertices
+        assert_both_windings(points(&[
+            (0.0, 0.0),
+            (5.0, 0.0),
+            (5.0, 3.0),
+            (4.0, 3.0),
+            (4.0, 1.0),
+            (3.0, 1.0),
+            (3.0, 3.0),
+            (2.0, 3.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 3.0),
+            (0.0, 3.0),
+        ]));
+    }
+
+    #[test]
+    fn collinear_points() {
+        assert_both_windings(points(&[
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (2.0, 2.0),
+            (1.0, 2.0),
+            (0.0, 2.0),
+        ]));
+    }
+
+    #[test]
+    fn duplicate_points() {
+        assert_both_windings(points(&[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 2.0),
+            (0.0, 2.0),
+            (0.0, 2.0),
+        ]));
+    }
+
+    #[test]
+    fn degenerate_polygons() {
+        assert!(triangulate(&points(&[(0.0, 0.0), (1.0, 0.0)])).is_empty());
+        assert_eq!(
+            triangulate(&points(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)])).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn fill_polygon_offsets_indices() {
+        let mut tessellation = Tessellation::default();
+        tessellation.triangle(
+            glm::vec2(0.0, 0.0),
+            glm::vec2(1.0, 0.0),
+            glm::vec2(0.0, 1.0),
+        );
+        tessellation.fill_polygon(&points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]));
+        assert_eq!(tessellation.positions.len(), 7);
+        assert!(tessellation.indices[3..].iter().all(|&i| i >= 3));
+        assert_close(area(&tessellation), 4.5, 1e-5);
+    }
+
+    #[test]
+    fn rounded_rect_clamps_radius() {
+        let (min, max) = (glm::vec2(0.0, 0.0), glm::vec2(10.0, 4.0));
+        let outline = rounded_rect_points(min, max, 5.0, 8);
+
+        assert_eq!(outline.len(), 4 * 9);
+        assert!(signed_area(&outline) > 0.0);
+        let (low, high) = outline.iter().fold((max, min), |(low, high), p| {
+            (glm::min2(&low, p), glm::max2(&high, p))
+        });
+        assert_close(low.x, 0.0, 1e-5);
+        assert_close(low.y, 0.0, 1e-5);
+        assert_close(high.x, 10.0, 1e-5);
+        assert_close(high.y, 4.0, 1e-5);
+
+        // Clamped to half the height, the short sides are half circles
+        let expected = 10.0 * 4.0 - (4.0 - PI) * 2.0 * 2.0;
+        assert_close(signed_area(&outline) / 2.0, expected, 0.1);
+    }
+
+    #[test]
+    fn rounded_rect_without_radius() {
+        let outline = rounded_rect_points(glm::vec2(0.0, 0.0), glm::vec2(2.0, 1.0), 0.0, 8);
+        assert_eq!(
+            outline,
+            points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (0.0, 1.0)])
+        );
+    }
+
+    #[test]
+    fn butt_cap() {
+        let tessellation = stroke(&[(0.0, 0.0), (10.0, 0.0)], false, &StrokeStyle::new(2.0));
+        assert_eq!(tessellation.indices.len(), 6);
+        assert_eq!(
+            bounds(&tessellation),
+            (glm::vec2(0.0, -1.0), glm::vec2(10.0, 1.0))
+        );
+        assert_close(area(&tessellation), 20.0, 1e-4);
+    }
+
+    #[test]
+    fn square_cap() {
+        let style = StrokeStyle::new(2.0).with_cap(LineCap::Square);
+        let tessellation = stroke(&[(0.0, 0.0), (10.0, 0.0)], false, &style);
+        assert_eq!(
+            bounds(&tessellation),
+            (glm::vec2(-1.0, -1.0), glm::vec2(11.0, 1.0))
+        );
+        assert_close(area(&tessellation), 24.0, 1e-4);
+    }
+
+    #[test]
+    fn round_cap() {
+        let style = StrokeStyle::new(2.0).with_cap(LineCap::Round);
+        let tessellation = stroke(&[(0.0, 0.0), (10.0, 0.0)], false, &style);
+        let (min, max) = bounds(&tessellation);
+        assert_close(min.x, -1.0, 1e-4);
+        assert_close(max.x, 11.0, 1e-4);
+        // Two half circles, a little smaller as polygons
+        let caps = area(&tessellation) - 20.0;
+        assert!(caps < PI && caps > PI * 0.98, "{}", caps);
+    }
+
+    #[test]
+    fn miter_join() {
+        let corner = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)];
+        let style = StrokeStyle::new(2.0);
+        let tessellation = stroke(&corner, false, &style);
+
+        // The corner is filled up to the tip
+        let (min, max) = bounds(&tessellation);
+        assert_close(min.y, -1.0, 1e-4);
+        assert_close(max.x, 11.0, 1e-4);
+        assert_eq!(tessellation.indices.len(), 6 * 3);
+        assert_close(extra_area(&corner, &style), 1.0, 1e-4);
+    }
+
+    #[test]
+    fn bevel_join() {
+        let corner = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)];
+        let style = StrokeStyle::new(2.0).with_join(LineJoin::Bevel);
+        assert_close(extra_area(&corner, &style), 0.5, 1e-4);
+    }
+
+    #[test]
+    fn round_join() {
+        let corner = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)];
+        let style = StrokeStyle::new(2.0).with_join(LineJoin::Round);
+        let join = extra_area(&corner, &style);
+        assert!(join < PI / 4.0 && join > PI / 4.0 * 0.98, "{}", join);
+
+        // Turning the other way fills the other side
+        let mirrored = [(0.0, 0.0), (10.0, 0.0), (10.0, -10.0)];
+        assert_close(extra_area(&mirrored, &style), join, 1e-4);
+    }
+
+    #[test]
+    fn miter_limit_falls_back_to_bevel() {
+        // A hairpin, its miter would be far longer than the limit
+        let hairpin = [(0.0, 0.0), (10.0, 0.0), (0.0, 1.0)];
+        let tessellation = stroke(&hairpin, false, &StrokeStyle::new(2.0));
+        assert_eq!(tessellation.indices.len(), 6 * 2 + 3);
+
+        let style = StrokeStyle::new(2.0).with_miter_limit(100.0);
+        let tessellation = stroke(&hairpin, false, &style);
+        assert_eq!(tessellation.indices.len(), 6 * 3);
+    }
+
+    #[test]
+    fn straight_and_closed_strokes() {
+        // Collinear points need no join
+        let tessellation = stroke(
+            &[(0.0, 0.0), (5.0, 0.0), (10.0, 0.0)],
+            false,
+            &StrokeStyle::new(2.0),
+        );
+        assert_eq!(tessellation.indices.len(), 6 * 2);
+
+        // A closed square joins every corner, a repeated first point is ignored
+        let square = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0), (0.0, 0.0)];
+        let tessellation = stroke(&square, true, &StrokeStyle::new(2.0));
+        assert_eq!(tessellation.indices.len(), 6 * 4 + 6 * 4);
+        assert_eq!(
+            bounds(&tessellation),
+            (glm::vec2(-1.0, -1.0), glm::vec2(5.0, 5.0))
+        );
+    }
+}
@@ -1,10 +1,14 @@
 use enum_iterator::all;
-use imgui_glfw_rs::imgui::Ui;
+use imgui_glfw_rs::imgui::{self, Ui};
 
-use crate::{renderer::Renderer, str_to_imstr};
+use crate::{application::Layer, input::Input, renderer::Renderer, str_to_imstr};
 
 use super::{
-    test_batch_rendering::TestBatchRendering, test_clear_color::TestClearColor,
+    test_3d::Test3D, test_batch_rendering::TestBatchRendering, test_clear_color::TestClearColor,
+    test_debug_draw::TestDebugDraw, test_lighting::TestLighting, test_pbr::TestPbr,
+    test_render_queue::TestRenderQueue, test_render_states::TestRenderStates,
+    test_sampling::TestSampling, test_sdf_text::TestSdfText, test_shadows::TestShadows,
+    test_shapes::TestShapes, test_sprite_atlas::TestSpriteAtlas,
     test_text_rendering::TestTextRendering, test_texture::TestTexture, TestType, TestTypeInternal,
     Testable,
 };
@@ -12,11 +16,16 @@ use super::{
 #[derive(Default)]
 pub struct TestMenu {
     active_test: Option<TestTypeInternal>,
+    screen_size: Option<(f32, f32)>,
 }
 
 impl TestMenu {
-    pub fn render(&self, screen_size: (f32, f32), renderer: &Renderer) {
-        if let Some(active_test) = &self.active_test {
+    pub fn render(&mut self, screen_size: (f32, f32), renderer: &Renderer) {
+        if let Some(active_test) = &mut self.active_test {
+            if self.screen_size != Some(screen_size) {
+                active_test.resize(screen_size);
+                self.screen_size = Some(screen_size);
+            }
             active_test.render(screen_size, renderer);
         }
     }
@@ -37,9 +46,9 @@ impl TestMenu {
         }
     }
 
-    pub fn update(&mut self, delta_time: f32) {
+    pub fn update(&mut self, delta_time: f32, input: &Input) {
         if let Some(active_test) = &mut self.active_test {
-            active_test.update(delta_time);
+            active_test.update(delta_time, input);
         }
     }
 
@@ -51,6 +60,7 @@ impl TestMenu {
     }
 
     pub fn use_test(&mut self, test: TestType) {
+        self.screen_size = None;
         self.active_test = Some(match test {
             TestType::ClearColor => TestTypeInternal::ClearColor(TestClearColor::default()),
             TestType::Texture => TestTypeInternal::Texture(TestTexture::default()),
@@ -60,6 +70,39 @@ impl TestMenu {
             TestType::TextRendering => {
                 TestTypeInternal::TextRendering(TestTextRendering::default())
             }
+            TestType::Scene3D => TestTypeInternal::Scene3D(Test3D::default()),
+            TestType::Lighting => TestTypeInternal::Lighting(TestLighting::default()),
+            TestType::Shadows => TestTypeInternal::Shadows(TestShadows::default()),
+            TestType::Pbr => TestTypeInternal::Pbr(TestPbr::default()),
+            TestType::Sampling => TestTypeInternal::Sampling(TestSampling::default()),
+            TestType::SpriteAtlas => TestTypeInternal::SpriteAtlas(TestSpriteAtlas::default()),
+            TestType::SdfText => TestTypeInternal::SdfText(TestSdfText::default()),
+            TestType::Shapes => TestTypeInternal::Shapes(TestShapes::default()),
+            TestType::DebugDraw => TestTypeInternal::DebugDraw(TestDebugDraw::default()),
+            TestType::RenderQueue => TestTypeInternal::RenderQueue(TestRenderQueue::default()),
+            TestType::RenderStates => TestTypeInternal::RenderStates(TestRenderStates::default()),
         });
     }
 }
+
+impl Layer for TestMenu {
+    fn name(&self) -> String {
+        "Test Menu".into()
+    }
+
+    fn on_update(&mut self, delta_time: f32, input: &Input) {
+        self.update(delta_time, input);
+    }
+
+    fn on_render(&mut self, screen_size: (f32, f32), renderer: &Renderer) {
+        self.render(screen_size, renderer);
+    }
+
+    fn on_imgui(&mut self, screen_size: (f32, f32), ui: &Ui) {
+        ui.window(&str_to_imstr(self.imgui_title()))
+            .size([500.0, 100.0], imgui::Condition::FirstUseEver)
+            .build(|| {
+                self.imgui_render(screen_size, ui);
+            });
+    }
+}
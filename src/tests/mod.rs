@@ -1,17 +1,22 @@
-use enum_iterator::Sequence;
+use enum_iterator::{all, Sequence};
 use imgui_glfw_rs::imgui::Ui;
 
-use crate::renderer::Renderer;
+use crate::{input::Input, renderer::Renderer};
 
 use self::{
-    test_batch_rendering::TestBatchRendering, test_clear_color::TestClearColor,
+    test_3d::Test3D, test_batch_rendering::TestBatchRendering, test_clear_color::TestClearColor,
+    test_debug_draw::TestDebugDraw, test_lighting::TestLighting, test_pbr::TestPbr,
+    test_render_queue::TestRenderQueue, test_render_states::TestRenderStates,
+    test_sampling::TestSampling, test_sdf_text::TestSdfText, test_shadows::TestShadows,
+    test_shapes::TestShapes, test_sprite_atlas::TestSpriteAtlas,
     test_text_rendering::TestTextRendering, test_texture::TestTexture,
 };
 
 pub trait Testable: TestableID {
     fn render(&self, screen_size: (f32, f32), renderer: &Renderer);
     fn imgui_render(&mut self, screen_size: (f32, f32), ui: &Ui);
-    fn update(&mut self, delta_time: f32);
+    fn update(&mut self, delta_time: f32, input: &Input);
+    fn resize(&mut self, _screen_size: (f32, f32)) {}
 }
 
 pub trait TestableID {
@@ -29,6 +34,17 @@ pub enum TestType {
     Texture,
     BatchRendering,
     TextRendering,
+    Scene3D,
+    Lighting,
+    Shadows,
+    Pbr,
+    Sampling,
+    SpriteAtlas,
+    SdfText,
+    Shapes,
+    DebugDraw,
+    RenderQueue,
+    RenderStates,
 }
 
 impl From<TestType> for String {
@@ -38,15 +54,63 @@ impl From<TestType> for String {
             TestType::Texture => TestTexture::test_name(),
             TestType::BatchRendering => TestBatchRendering::test_name(),
             TestType::TextRendering => TestTextRendering::test_name(),
+            TestType::Scene3D => Test3D::test_name(),
+            TestType::Lighting => TestLighting::test_name(),
+            TestType::Shadows => TestShadows::test_name(),
+            TestType::Pbr => TestPbr::test_name(),
+            TestType::Sampling => TestSampling::test_name(),
+            TestType::SpriteAtlas => TestSpriteAtlas::test_name(),
+            TestType::SdfText => TestSdfText::test_name(),
+            TestType::Shapes => TestShapes::test_name(),
+            TestType::DebugDraw => TestDebugDraw::test_name(),
+            TestType::RenderQueue => TestRenderQueue::test_name(),
+            TestType::RenderStates => TestRenderStates::test_name(),
         }
     }
 }
 
+impl TestType {
+    pub fn test_id(&self) -> String {
+        match self {
+            TestType::ClearColor => TestClearColor::test_id(),
+            TestType::Texture => TestTexture::test_id(),
+            TestType::BatchRendering => TestBatchRendering::test_id(),
+            TestType::TextRendering => TestTextRendering::test_id(),
+            TestType::Scene3D => Test3D::test_id(),
+            TestType::Lighting => TestLighting::test_id(),
+            TestType::Shadows => TestShadows::test_id(),
+            TestType::Pbr => TestPbr::test_id(),
+            TestType::Sampling => TestSampling::test_id(),
+            TestType::SpriteAtlas => TestSpriteAtlas::test_id(),
+            TestType::SdfText => TestSdfText::test_id(),
+            TestType::Shapes => TestShapes::test_id(),
+            TestType::DebugDraw => TestDebugDraw::test_id(),
+            TestType::RenderQueue => TestRenderQueue::test_id(),
+            TestType::RenderStates => TestRenderStates::test_id(),
+        }
+    }
+
+    pub fn from_id(id: impl AsRef<str>) -> Option<TestType> {
+        all::<TestType>().find(|test| test.test_id() == id.as_ref())
+    }
+}
+
 pub enum TestTypeInternal {
     ClearColor(TestClearColor),
     Texture(TestTexture),
     BatchRendering(TestBatchRendering),
     TextRendering(TestTextRendering),
+    Scene3D(Test3D),
+    Lighting(TestLighting),
+    Shadows(TestShadows),
+    Pbr(TestPbr),
+    Sampling(TestSampling),
+    SpriteAtlas(TestSpriteAtlas),
+    SdfText(TestSdfText),
+    Shapes(TestShapes),
+    DebugDraw(TestDebugDraw),
+    RenderQueue(TestRenderQueue),
+    RenderStates(TestRenderStates),
 }
 
 impl Testable for TestTypeInternal {
@@ -56,6 +120,17 @@ impl Testable for TestTypeInternal {
             Self::ClearColor(t) => t.render(screen_size, renderer),
             Self::BatchRendering(t) => t.render(screen_size, renderer),
             Self::TextRendering(t) => t.render(screen_size, renderer),
+            Self::Scene3D(t) => t.render(screen_size, renderer),
+            Self::Lighting(t) => t.render(screen_size, renderer),
+            Self::Shadows(t) => t.render(screen_size, renderer),
+            Self::Pbr(t) => t.render(screen_size, renderer),
+            Self::Sampling(t) => t.render(screen_size, renderer),
+            Self::SpriteAtlas(t) => t.render(screen_size, renderer),
+            Self::SdfText(t) => t.render(screen_size, renderer),
+            Self::Shapes(t) => t.render(screen_size, renderer),
+            Self::DebugDraw(t) => t.render(screen_size, renderer),
+            Self::RenderQueue(t) => t.render(screen_size, renderer),
+            Self::RenderStates(t) => t.render(screen_size, renderer),
         }
     }
 
@@ -65,15 +140,57 @@ impl Testable for TestTypeInternal {
             Self::ClearColor(t) => t.imgui_render(screen_size, ui),
             Self::BatchRendering(t) => t.imgui_render(screen_size, ui),
             Self::TextRendering(t) => t.imgui_render(screen_size, ui),
+            Self::Scene3D(t) => t.imgui_render(screen_size, ui),
+            Self::Lighting(t) => t.imgui_render(screen_size, ui),
+            Self::Shadows(t) => t.imgui_render(screen_size, ui),
+            Self::Pbr(t) => t.imgui_render(screen_size, ui),
+            Self::Sampling(t) => t.imgui_render(screen_size, ui),
+            Self::SpriteAtlas(t) => t.imgui_render(screen_size, ui),
+            Self::SdfText(t) => t.imgui_render(screen_size, ui),
+            Self::Shapes(t) => t.imgui_render(screen_size, ui),
+            Self::DebugDraw(t) => t.imgui_render(screen_size, ui),
+            Self::RenderQueue(t) => t.imgui_render(screen_size, ui),
+            Self::RenderStates(t) => t.imgui_render(screen_size, ui),
         }
     }
 
-    fn update(&mut self, delta_time: f32) {
+    fn update(&mut self, delta_time: f32, input: &Input) {
         match self {
-            Self::Texture(t) => t.update(delta_time),
-            Self::ClearColor(t) => t.update(delta_time),
-            Self::BatchRendering(t) => t.update(delta_time),
-            Self::TextRendering(t) => t.update(delta_time),
+            Self::Texture(t) => t.update(delta_time, input),
+            Self::ClearColor(t) => t.update(delta_time, input),
+            Self::BatchRendering(t) => t.update(delta_time, input),
+            Self::TextRendering(t) => t.update(delta_time, input),
+            Self::Scene3D(t) => t.update(delta_time, input),
+            Self::Lighting(t) => t.update(delta_time, input),
+            Self::Shadows(t) => t.update(delta_time, input),
+            Self::Pbr(t) => t.update(delta_time, input),
+            Self::Sampling(t) => t.update(delta_time, input),
+            Self::SpriteAtlas(t) => t.update(delta_time, input),
+            Self::SdfText(t) => t.update(delta_time, input),
+            Self::Shapes(t) => t.update(delta_time, input),
+            Self::DebugDraw(t) => t.update(delta_time, input),
+            Self::RenderQueue(t) => t.update(delta_time, input),
+            Self::RenderStates(t) => t.update(delta_time, input),
+        }
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        match self {
+            Self::Texture(t) => t.resize(screen_size),
+            Self::ClearColor(t) => t.resize(screen_size),
+            Self::BatchRendering(t) => t.resize(screen_size),
+            Self::TextRendering(t) => t.resize(screen_size),
+            Self::Scene3D(t) => t.resize(screen_size),
+            Self::Lighting(t) => t.resize(screen_size),
+            Self::Shadows(t) => t.resize(screen_size),
+            Self::Pbr(t) => t.resize(screen_size),
+            Self::Sampling(t) => t.resize(screen_size),
+            Self::SpriteAtlas(t) => t.resize(screen_size),
+            Self::SdfText(t) => t.resize(screen_size),
+            Self::Shapes(t) => t.resize(screen_size),
+            Self::DebugDraw(t) => t.resize(screen_size),
+            Self::RenderQueue(t) => t.resize(screen_size),
+            Self::RenderStates(t) => t.resize(screen_size),
         }
     }
 }
@@ -95,6 +212,17 @@ impl TestTypeInternal {
             TestTypeInternal::Texture(t) => Box::new(t),
             TestTypeInternal::BatchRendering(t) => Box::new(t),
             TestTypeInternal::TextRendering(t) => Box::new(t),
+            TestTypeInternal::Scene3D(t) => Box::new(t),
+            TestTypeInternal::Lighting(t) => Box::new(t),
+            TestTypeInternal::Shadows(t) => Box::new(t),
+            TestTypeInternal::Pbr(t) => Box::new(t),
+            TestTypeInternal::Sampling(t) => Box::new(t),
+            TestTypeInternal::SpriteAtlas(t) => Box::new(t),
+            TestTypeInternal::SdfText(t) => Box::new(t),
+            TestTypeInternal::Shapes(t) => Box::new(t),
+            TestTypeInternal::DebugDraw(t) => Box::new(t),
+            TestTypeInternal::RenderQueue(t) => Box::new(t),
+            TestTypeInternal::RenderStates(t) => Box::new(t),
         }
     }
 
@@ -104,6 +232,17 @@ impl TestTypeInternal {
             TestTypeInternal::Texture(_) => TestTexture::test_name(),
             TestTypeInternal::BatchRendering(_) => TestBatchRendering::test_name(),
             TestTypeInternal::TextRendering(_) => TestTextRendering::test_name(),
+            TestTypeInternal::Scene3D(_) => Test3D::test_name(),
+            TestTypeInternal::Lighting(_) => TestLighting::test_name(),
+            TestTypeInternal::Shadows(_) => TestShadows::test_name(),
+            TestTypeInternal::Pbr(_) => TestPbr::test_name(),
+            TestTypeInternal::Sampling(_) => TestSampling::test_name(),
+            TestTypeInternal::SpriteAtlas(_) => TestSpriteAtlas::test_name(),
+            TestTypeInternal::SdfText(_) => TestSdfText::test_name(),
+            TestTypeInternal::Shapes(_) => TestShapes::test_name(),
+            TestTypeInternal::DebugDraw(_) => TestDebugDraw::test_name(),
+            TestTypeInternal::RenderQueue(_) => TestRenderQueue::test_name(),
+            TestTypeInternal::RenderStates(_) => TestRenderStates::test_name(),
         }
     }
 
@@ -113,14 +252,35 @@ impl TestTypeInternal {
             TestTypeInternal::Texture(_) => TestTexture::test_id(),
             TestTypeInternal::BatchRendering(_) => TestBatchRendering::test_id(),
             TestTypeInternal::TextRendering(_) => TestTextRendering::test_id(),
+            TestTypeInternal::Scene3D(_) => Test3D::test_id(),
+            TestTypeInternal::Lighting(_) => TestLighting::test_id(),
+            TestTypeInternal::Shadows(_) => TestShadows::test_id(),
+            TestTypeInternal::Pbr(_) => TestPbr::test_id(),
+            TestTypeInternal::Sampling(_) => TestSampling::test_id(),
+            TestTypeInternal::SpriteAtlas(_) => TestSpriteAtlas::test_id(),
+            TestTypeInternal::SdfText(_) => TestSdfText::test_id(),
+            TestTypeInternal::Shapes(_) => TestShapes::test_id(),
+            TestTypeInternal::DebugDraw(_) => TestDebugDraw::test_id(),
+            TestTypeInternal::RenderQueue(_) => TestRenderQueue::test_id(),
+            TestTypeInternal::RenderStates(_) => TestRenderStates::test_id(),
         }
     }
 }
 
 pub mod menu;
 
+pub mod test_3d;
 pub mod test_batch_rendering;
 pub mod test_clear_color;
+pub mod test_debug_draw;
+pub mod test_lighting;
+pub mod test_pbr;
+pub mod test_render_queue;
+pub mod test_render_states;
+pub mod test_sampling;
+pub mod test_sdf_text;
+pub mod test_shadows;
+pub mod test_shapes;
+pub mod test_sprite_atlas;
 pub mod test_text_rendering;
 pub mod test_texture;
-pub mod test_3d;
@@ -1,3 +1,222 @@
+use std::rc::Rc;
+
+use gl::types::GLuint;
+use imgui_glfw_rs::imgui::Ui;
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::{Camera, PerspectiveCamera},
+    camera_controller::FlyCameraController,
+    gl_component::GLComponent,
+    index_buffer::IndexBuffer,
+    input::Input,
+    material::Material,
+    renderer::{CullFace, Renderer},
+    str_to_imstr,
+    texture::Texture,
+    vertex_array::VertexArray,
+    vertex_buffer::VertexBuffer,
+    vertex_buffer_layout::VertexBufferLayout,
+    ShaderBuilder,
+};
+
+use super::{Testable, TestableID};
+
+pub fn gen_cube_vertices(size: f32) -> Vec<f32> {
+    let h = size / 2.0;
+
+    // Four corners per face so every face gets its own texture coordinates,
+    // wound counter-clockwise when looking at the face from outside the cube
+    #[rustfmt::skip]
+    let vertices = vec![
+        // Front (+z)
+        -h, -h,  h,   0.0, 0.0,
+         h, -h,  h,   1.0, 0.0,
+         h,  h,  h,   1.0, 1.0,
+        -h,  h,  h,   0.0, 1.0,
+        // Back (-z)
+         h, -h, -h,   0.0, 0.0,
+        -h, -h, -h,   1.0, 0.0,
+        -h,  h, -h,   1.0, 1.0,
+         h,  h, -h,   0.0, 1.0,
+        // Left (-x)
+        -h, -h, -h,   0.0, 0.0,
+        -h, -h,  h,   1.0, 0.0,
+        -h,  h,  h,   1.0, 1.0,
+        -h,  h, -h,   0.0, 1.0,
+        // Right (+x)
+         h, -h,  h,   0.0, 0.0,
+         h, -h, -h,   1.0, 0.0,
+         h,  h, -h,   1.0, 1.0,
+         h,  h,  h,   0.0, 1.0,
+        // Top (+y)
+        -h,  h,  h,   0.0, 0.0,
+         h,  h,  h,   1.0, 0.0,
+         h,  h, -h,   1.0, 1.0,
+        -h,  h, -h,   0.0, 1.0,
+        // Bottom (-y)
+        -h, -h, -h,   0.0, 0.0,
+         h, -h, -h,   1.0, 0.0,
+         h, -h,  h,   1.0, 1.0,
+        -h, -h,  h,   0.0, 1.0,
+    ];
+
+    vertices
+}
+
+pub fn gen_cube_indices() -> Vec<GLuint> {
+    (0..6)
+        .flat_map(|face| [0, 1, 2, 2, 3, 0].map(|i| i + face * 4))
+        .collect()
+}
+
 pub struct Test3D {
+    vao: VertexArray,
+    ibo: IndexBuffer,
+    material: Rc<Material>,
+
+    camera_controller: FlyCameraController,
+
+    rotation: f32,
+    rotation_speed: f32,
+    axis: [f32; 3],
+
+    depth_test: bool,
+    face_culling: bool,
+    cull_front_faces: bool,
+}
+
+impl Default for Test3D {
+    fn default() -> Self {
+        let vertices = gen_cube_vertices(1.0);
+        let indices = gen_cube_indices();
+
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/Default.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+
+        let layout = VertexBufferLayout::default().with_floats(3).with_floats(2);
+
+        let mut vao = VertexArray::new();
+        let vbo = VertexBuffer::new(&vertices);
+        let ibo = IndexBuffer::new(&indices);
+        vao.add_buffer(&vbo, &layout);
+
+        vao.unbind();
+        vbo.unbind();
+        ibo.unbind();
+
+        let texture = Texture::new("src/res/textures/rust.png", 0);
+        let material = Material::new(shader).with_texture("u_Texture", Rc::new(texture));
+
+        let camera = PerspectiveCamera::new(45.0, 1280.0 / 960.0, 0.1, 100.0);
+
+        Self {
+            vao,
+            ibo,
+            material: Rc::new(material),
+            camera_controller: FlyCameraController::new(camera),
+            rotation: 0.0,
+            rotation_speed: 45.0,
+            axis: [0.5, 1.0, 0.0],
+            depth_test: true,
+            face_culling: true,
+            cull_front_faces: false,
+        }
+    }
+}
+
+impl Testable for Test3D {
+    fn render(&self, _: (f32, f32), renderer: &Renderer) {
+        let axis = glm::Vec3::from(self.axis);
+        let axis = if glm::length(&axis) > 0.0 {
+            glm::normalize(&axis)
+        } else {
+            glm::vec3(0.0, 1.0, 0.0)
+        };
+        let model = glm::rotate(&glm::Mat4::identity(), self.rotation.to_radians(), &axis);
+        let mvp = self.camera_controller.camera().view_projection() * model;
+
+        renderer.set_depth_test(self.depth_test);
+        renderer.set_face_culling(match (self.face_culling, self.cull_front_faces) {
+            (false, _) => None,
+            (true, false) => Some(CullFace::Back),
+            (true, true) => Some(CullFace::Front),
+        });
+
+        let material = Material::instance(&self.material).with_uniform("u_MVP", mvp);
+        renderer.draw(&self.vao, &self.ibo, &material);
+        material.shader().unbind();
+
+        renderer.set_depth_test(false);
+        renderer.set_face_culling(None);
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        let camera = self.camera_controller.camera_mut();
+
+        let mut fov = camera.fov();
+        if ui
+            .slider_float(&str_to_imstr("FOV"), &mut fov, 10.0, 120.0)
+            .build()
+        {
+            camera.set_fov(fov);
+        }
+
+        let mut position: [f32; 3] = camera.position().into();
+        if ui
+            .drag_float3(&str_to_imstr("Camera Position"), &mut position)
+            .speed(0.05)
+            .build()
+        {
+            camera.set_position(glm::Vec3::from(position));
+        }
+
+        if ui.button(&str_to_imstr("Look At Cube"), [150., 20.]) {
+            camera.look_at(&glm::vec3(0.0, 0.0, 0.0));
+        }
+
+        ui.separator();
+        ui.slider_float(
+            &str_to_imstr("Rotation Speed"),
+            &mut self.rotation_speed,
+            -360.0,
+            360.0,
+        )
+        .build();
+        ui.slider_float3(&str_to_imstr("Rotation Axis"), &mut self.axis, -1.0, 1.0)
+            .build();
+
+        ui.separator();
+        ui.checkbox(&str_to_imstr("Depth Test"), &mut self.depth_test);
+        ui.checkbox(&str_to_imstr("Face Culling"), &mut self.face_culling);
+        if self.face_culling {
+            ui.checkbox(
+                &str_to_imstr("Cull Front Faces"),
+                &mut self.cull_front_faces,
+            );
+        }
+
+        ui.text("Hold the right mouse button to look around, WASD to move");
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.rotation = (self.rotation + self.rotation_speed * delta_time) % 360.0;
+        self.camera_controller.update(delta_time, input);
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
+}
+
+impl TestableID for Test3D {
+    fn test_id() -> String {
+        "3d_scene".into()
+    }
 
+    fn test_name() -> String {
+        "3D Scene".into()
+    }
 }
@@ -6,8 +6,8 @@ use memoffset::offset_of;
 use nalgebra_glm as glm;
 
 use crate::{
-    gl_component::GLComponent, shader::Shader, str_to_imstr, tests::Testable, texture::Texture,
-    ShaderBuilder,
+    camera::Camera, camera_controller::OrthographicCameraController, gl_component::GLComponent,
+    input::Input, shader::Shader, str_to_imstr, tests::Testable, texture::Texture, ShaderBuilder,
 };
 
 use super::TestableID;
@@ -69,6 +69,7 @@ pub struct TestBatchRendering {
     vbo: u32,
     shader: Shader,
 
+    camera_controller: OrthographicCameraController,
     model: glm::Vec3,
 
     phone_texture: Texture,
@@ -176,6 +177,7 @@ impl Default for TestBatchRendering {
             shader,
             vao,
             vbo,
+            camera_controller: OrthographicCameraController::new((1280.0, 960.0)),
             model: glm::vec3(200., 200., 0.),
             phone_texture,
             rust_texture,
@@ -185,7 +187,7 @@ impl Default for TestBatchRendering {
 }
 
 impl Testable for TestBatchRendering {
-    fn render(&self, (width, height): (f32, f32), _renderer: &crate::renderer::Renderer) {
+    fn render(&self, _: (f32, f32), _renderer: &crate::renderer::Renderer) {
         let x = self.quad_0_position[0];
         let y = self.quad_0_position[1];
 
@@ -205,11 +207,9 @@ impl Testable for TestBatchRendering {
             gl::ClearColor(0.2, 0.2, 0.2, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
         });
-        let proj = glm::ortho(0.0, width, 0.0, height, -1.0, 1.0);
-        let view = glm::translate(&glm::Mat4::identity(), &glm::vec3(0.0, 0.0, 0.0));
         let model = glm::translate(&glm::Mat4::identity(), &self.model);
 
-        let mvp = proj * view * model;
+        let mvp = self.camera_controller.camera().view_projection() * model;
 
         self.shader.bind();
         self.shader.uniform_mat4("u_MVP", &mvp);
@@ -235,9 +235,20 @@ impl Testable for TestBatchRendering {
 
         ui.drag_float2(&str_to_imstr("Quad 1 Position"), &mut self.quad_0_position)
             .build();
+
+        ui.text(format!("Zoom: {:.2}", self.camera_controller.zoom()));
+        if ui.button(&str_to_imstr("Reset Camera"), [150., 20.]) {
+            self.camera_controller.reset();
+        }
     }
 
-    fn update(&mut self, _delta_time: f32) {}
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.camera_controller.update(delta_time, input);
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
 }
 
 impl TestableID for TestBatchRendering {
@@ -1,7 +1,7 @@
 use glcall_macro::gl_call;
 use imgui_glfw_rs::imgui::Ui;
 
-use crate::{renderer::Renderer, str_to_imstr};
+use crate::{input::Input, renderer::Renderer, str_to_imstr};
 
 use super::{Testable, TestableID};
 
@@ -24,7 +24,7 @@ impl Testable for TestClearColor {
             .build();
     }
 
-    fn update(&mut self, _delta_time: f32) {}
+    fn update(&mut self, _delta_time: f32, _input: &Input) {}
 }
 
 impl TestableID for TestClearColor {
@@ -0,0 +1,199 @@
+use std::rc::Rc;
+
+use imgui_glfw_rs::imgui::Ui;
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::{Camera, PerspectiveCamera},
+    camera_controller::FlyCameraController,
+    debug_draw::{DebugDraw, DebugDrawRenderer, DebugOptions},
+    gl_component::GLComponent,
+    index_buffer::IndexBuffer,
+    input::Input,
+    material::Material,
+    renderer::Renderer,
+    str_to_imstr,
+    texture::Texture,
+    vertex_array::VertexArray,
+    vertex_buffer::VertexBuffer,
+    vertex_buffer_layout::VertexBufferLayout,
+    ShaderBuilder,
+};
+
+use super::{
+    test_3d::{gen_cube_indices, gen_cube_vertices},
+    Testable, TestableID,
+};
+
+pub struct TestDebugDraw {
+    vao: VertexArray,
+    ibo: IndexBuffer,
+    material: Rc<Material>,
+
+    camera_controller: FlyCameraController,
+    debug_renderer: DebugDrawRenderer,
+
+    time: f32,
+    overlay: bool,
+    show_grid: bool,
+    show_frustum: bool,
+    marker_duration: f32,
+    lines: usize,
+}
+
+impl Default for TestDebugDraw {
+    fn default() -> Self {
+        let vertices = gen_cube_vertices(1.0);
+        let indices = gen_cube_indices();
+
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/Default.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+
+        let layout = VertexBufferLayout::default().with_floats(3).with_floats(2);
+
+        let mut vao = VertexArray::new();
+        let vbo = VertexBuffer::new(&vertices);
+        let ibo = IndexBuffer::new(&indices);
+        vao.add_buffer(&vbo, &layout);
+
+        vao.unbind();
+        vbo.unbind();
+        ibo.unbind();
+
+        let texture = Texture::new("src/res/textures/rust.png", 0);
+        let material = Material::new(shader).with_texture("u_Texture", Rc::new(texture));
+
+        let mut camera = PerspectiveCamera::new(45.0, 1280.0 / 960.0, 0.1, 100.0);
+        camera.set_position(glm::vec3(4.0, 3.0, 6.0));
+        camera.look_at(&glm::vec3(0.0, 0.0, 0.0));
+
+        Self {
+            vao,
+            ibo,
+            material: Rc::new(material),
+            camera_controller: FlyCameraController::new(camera),
+            debug_renderer: DebugDrawRenderer::default(),
+            time: 0.0,
+            overlay: false,
+            show_grid: true,
+            show_frustum: true,
+            marker_duration: 3.0,
+            lines: 0,
+        }
+    }
+}
+
+impl TestDebugDraw {
+    /// Where the orbiting sphere is at `time`
+    fn orbit(time: f32) -> glm::Vec3 {
+        glm::vec3(
+            time.cos() * 2.5,
+            0.5 + (time * 2.0).sin() * 0.5,
+            time.sin() * 2.5,
+        )
+    }
+}
+
+impl Testable for TestDebugDraw {
+    fn render(&self, _: (f32, f32), renderer: &Renderer) {
+        let view_projection = self.camera_controller.camera().view_projection();
+
+        renderer.set_depth_test(true);
+        let material = Material::instance(&self.material).with_uniform("u_MVP", *view_projection);
+        renderer.draw(&self.vao, &self.ibo, &material);
+        material.shader().unbind();
+
+        self.debug_renderer.flush(view_projection);
+        renderer.set_depth_test(false);
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        ui.checkbox(&str_to_imstr("Draw On Top"), &mut self.overlay);
+        ui.checkbox(&str_to_imstr("Grid"), &mut self.show_grid);
+        ui.checkbox(&str_to_imstr("Frustum"), &mut self.show_frustum);
+
+        ui.separator();
+        ui.slider_float(
+            &str_to_imstr("Marker Duration"),
+            &mut self.marker_duration,
+            0.5,
+            10.0,
+        )
+        .build();
+        if ui.button(&str_to_imstr("Drop Marker"), [150., 20.]) {
+            let position = Self::orbit(self.time);
+            let options = DebugOptions::overlay().with_duration(self.marker_duration);
+            DebugDraw::with(|debug| {
+                debug.scoped(options, |debug| {
+                    debug.arrow(
+                        &glm::vec3(0.0, 0.0, 0.0),
+                        &position,
+                        glm::vec4(1.0, 0.4, 0.8, 1.0),
+                    );
+                    debug.point(&position, 0.3, glm::vec4(1.0, 0.4, 0.8, 1.0));
+                });
+            });
+        }
+
+        ui.text(format!("{} lines", self.lines));
+        ui.text("Hold the right mouse button to look around, WASD to move");
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.time += delta_time;
+        self.camera_controller.update(delta_time, input);
+
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        let yellow = glm::vec4(0.95, 0.77, 0.06, 1.0);
+        let cyan = glm::vec4(0.2, 0.8, 0.9, 1.0);
+        let orbit = Self::orbit(self.time);
+
+        let options = DebugOptions::default().with_depth_test(!self.overlay);
+        self.lines = DebugDraw::with(|debug| {
+            if self.show_grid {
+                debug.grid(
+                    &glm::vec3(0.0, -0.5, 0.0),
+                    10.0,
+                    20,
+                    glm::vec4(0.5, 0.5, 0.5, 0.5),
+                );
+            }
+            debug.axes(&glm::translation(&glm::vec3(0.0, -0.5, 0.0)), 1.5);
+
+            debug.scoped(options, |debug| {
+                debug.aabb(
+                    &glm::vec3(-0.5, -0.5, -0.5),
+                    &glm::vec3(0.5, 0.5, 0.5),
+                    white,
+                );
+                debug.sphere(&orbit, 0.3, yellow);
+                debug.line(&glm::vec3(0.0, 0.0, 0.0), &orbit, yellow);
+            });
+
+            if self.show_frustum {
+                let mut camera = PerspectiveCamera::new(30.0, 16.0 / 9.0, 0.5, 3.0);
+                camera.set_position(glm::vec3(-3.0, 1.0, -3.0));
+                camera.look_at(&orbit);
+                debug.frustum(camera.view_projection(), cyan);
+            }
+
+            debug.len()
+        });
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
+}
+
+impl TestableID for TestDebugDraw {
+    fn test_id() -> String {
+        "debug_draw".into()
+    }
+
+    fn test_name() -> String {
+        "Debug Draw".into()
+    }
+}
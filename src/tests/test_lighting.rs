@@ -0,0 +1,417 @@
+use std::rc::Rc;
+
+use imgui_glfw_rs::imgui::Ui;
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::{Camera, PerspectiveCamera},
+    camera_controller::OrbitCameraController,
+    gl_component::GLComponent,
+    input::Input,
+    light::{
+        normal_matrix, Attenuation, DirectionalLight, LightEnvironment, PointLight, SpotLight,
+        LIGHTS_BINDING,
+    },
+    material::Material,
+    mesh::{GpuMesh, Mesh},
+    renderer::{CullFace, Renderer},
+    str_to_imstr,
+    texture::Texture,
+    uniform_buffer::UniformBuffer,
+    ShaderBuilder,
+};
+
+use super::{Testable, TestableID};
+
+struct SceneObject {
+    position: glm::Vec3,
+    scale: glm::Vec3,
+    color: glm::Vec4,
+    textured: bool,
+}
+
+pub struct TestLighting {
+    cube: GpuMesh,
+    plane: GpuMesh,
+    material: Rc<Material>,
+    lights_buffer: UniformBuffer,
+
+    camera_controller: OrbitCameraController,
+    objects: Vec<SceneObject>,
+
+    lights: LightEnvironment,
+    point_light_ranges: Vec<f32>,
+    spot_range: f32,
+    directional_enabled: bool,
+    flashlight: bool,
+    animate_lights: bool,
+    time: f32,
+
+    shininess: f32,
+    specular: [f32; 3],
+    blinn_phong: bool,
+}
+
+impl Default for TestLighting {
+    fn default() -> Self {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/Lit.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        shader.bind_uniform_block("Lights", LIGHTS_BINDING);
+
+        let texture = Texture::new("src/res/textures/rust.png", 0);
+        let material = Material::new(shader)
+            .with_texture("u_DiffuseTexture", Rc::new(texture))
+            .with_uniform("u_DiffuseColor", glm::vec4(1.0, 1.0, 1.0, 1.0))
+            .with_uniform("u_HasDiffuseTexture", 0)
+            .with_uniform("u_Emissive", glm::vec3(0.0, 0.0, 0.0));
+
+        let camera = PerspectiveCamera::new(45.0, 1280.0 / 960.0, 0.1, 100.0);
+        let camera_controller = OrbitCameraController::new(camera, glm::vec3(0.0, 0.5, 0.0), 8.0);
+
+        let objects = vec![
+            SceneObject {
+                position: glm::vec3(0.0, 0.5, 0.0),
+                scale: glm::vec3(1.0, 1.0, 1.0),
+                color: glm::vec4(1.0, 1.0, 1.0, 1.0),
+                textured: true,
+            },
+            SceneObject {
+                position: glm::vec3(2.0, 0.75, -1.5),
+                scale: glm::vec3(1.0, 1.5, 1.0),
+                color: glm::vec4(0.8, 0.3, 0.3, 1.0),
+                textured: false,
+            },
+            SceneObject {
+                position: glm::vec3(-2.0, 0.25, 1.0),
+                scale: glm::vec3(2.0, 0.5, 0.75),
+                color: glm::vec4(0.3, 0.5, 0.8, 1.0),
+                textured: false,
+            },
+        ];
+
+        let point_light_ranges = vec![10.0, 10.0];
+        let lights = LightEnvironment {
+            point_lights: vec![
+                PointLight {
+                    position: glm::vec3(2.0, 2.0, 2.0),
+                    color: glm::vec3(1.0, 0.6, 0.3),
+                    intensity: 1.5,
+                    attenuation: Attenuation::from_range(point_light_ranges[0]),
+                },
+                PointLight {
+                    position: glm::vec3(-2.0, 2.0, -2.0),
+                    color: glm::vec3(0.3, 0.6, 1.0),
+                    intensity: 1.5,
+                    attenuation: Attenuation::from_range(point_light_ranges[1]),
+                },
+            ],
+            ..Default::default()
+        };
+
+        Self {
+            cube: Mesh::cube(1.0).upload(),
+            plane: Mesh::plane(20.0, 10.0).upload(),
+            material: Rc::new(material),
+            lights_buffer: UniformBuffer::new(LightEnvironment::std140_size(), LIGHTS_BINDING),
+            camera_controller,
+            objects,
+            lights,
+            point_light_ranges,
+            spot_range: 20.0,
+            directional_enabled: true,
+            flashlight: false,
+            animate_lights: true,
+            time: 0.0,
+            shininess: 32.0,
+            specular: [0.5, 0.5, 0.5],
+            blinn_phong: true,
+        }
+    }
+}
+
+impl TestLighting {
+    fn draw_mesh(
+        &self,
+        renderer: &Renderer,
+        mesh: &GpuMesh,
+        frame: &Rc<Material>,
+        model: &glm::Mat4,
+        configure: impl FnOnce(Material) -> Material,
+    ) {
+        let material = Material::instance(frame)
+            .with_uniform("u_Model", *model)
+            .with_uniform("u_NormalMatrix", normal_matrix(model));
+        mesh.draw(renderer, &configure(material));
+    }
+}
+
+impl Testable for TestLighting {
+    fn render(&self, _: (f32, f32), renderer: &Renderer) {
+        self.lights.upload(&self.lights_buffer);
+
+        let camera = self.camera_controller.camera();
+        let frame = Rc::new(
+            Material::instance(&self.material)
+                .with_uniform("u_ViewProjection", *camera.view_projection())
+                .with_uniform("u_ViewPosition", camera.position())
+                .with_uniform("u_SpecularColor", glm::Vec3::from(self.specular))
+                .with_uniform("u_Shininess", self.shininess)
+                .with_uniform("u_BlinnPhong", self.blinn_phong as i32),
+        );
+
+        renderer.set_depth_test(true);
+        renderer.set_face_culling(Some(CullFace::Back));
+
+        self.draw_mesh(
+            renderer,
+            &self.plane,
+            &frame,
+            &glm::Mat4::identity(),
+            |material| material.with_uniform("u_DiffuseColor", glm::vec4(0.6, 0.6, 0.6, 1.0)),
+        );
+
+        for object in &self.objects {
+            let model = glm::translate(&glm::Mat4::identity(), &object.position);
+            let model = glm::scale(&model, &object.scale);
+            self.draw_mesh(renderer, &self.cube, &frame, &model, |material| {
+                material
+                    .with_uniform("u_DiffuseColor", object.color)
+                    .with_uniform("u_HasDiffuseTexture", object.textured as i32)
+            });
+        }
+
+        // Unlit markers so the point lights can be found in the scene
+        for light in &self.lights.point_lights {
+            let model = glm::translate(&glm::Mat4::identity(), &light.position);
+            let model = glm::scale(&model, &glm::vec3(0.15, 0.15, 0.15));
+            self.draw_mesh(renderer, &self.cube, &frame, &model, |material| {
+                material
+                    .with_uniform("u_DiffuseColor", glm::vec4(0.0, 0.0, 0.0, 1.0))
+                    .with_uniform("u_SpecularColor", glm::vec3(0.0, 0.0, 0.0))
+                    .with_uniform("u_Emissive", light.color)
+            });
+        }
+
+        frame.shader().unbind();
+        renderer.set_depth_test(false);
+        renderer.set_face_culling(None);
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        let mut ambient: [f32; 3] = self.lights.ambient_color.into();
+        if ui
+            .color_edit(&str_to_imstr("Ambient Color"), &mut ambient)
+            .build()
+        {
+            self.lights.ambient_color = ambient.into();
+        }
+        ui.slider_float(
+            &str_to_imstr("Ambient Intensity"),
+            &mut self.lights.ambient_intensity,
+            0.0,
+            1.0,
+        )
+        .build();
+
+        ui.separator();
+        ui.checkbox(&str_to_imstr("Blinn-Phong"), &mut self.blinn_phong);
+        ui.slider_float(&str_to_imstr("Shininess"), &mut self.shininess, 1.0, 256.0)
+            .build();
+        ui.color_edit(&str_to_imstr("Specular Color"), &mut self.specular)
+            .build();
+
+        if ui
+            .collapsing_header(&str_to_imstr("Directional Light"))
+            .default_open(true)
+            .build()
+        {
+            if ui.checkbox(
+                &str_to_imstr("Enabled##directional"),
+                &mut self.directional_enabled,
+            ) {
+                self.lights.directional = self.directional_enabled.then(DirectionalLight::default);
+            }
+            if let Some(light) = self.lights.directional.as_mut() {
+                let mut direction: [f32; 3] = light.direction.into();
+                if ui
+                    .slider_float3(
+                        &str_to_imstr("Direction##directional"),
+                        &mut direction,
+                        -1.0,
+                        1.0,
+                    )
+                    .build()
+                {
+                    light.direction = direction.into();
+                }
+                let mut color: [f32; 3] = light.color.into();
+                if ui
+                    .color_edit(&str_to_imstr("Color##directional"), &mut color)
+                    .build()
+                {
+                    light.color = color.into();
+                }
+                ui.slider_float(
+                    &str_to_imstr("Intensity##directional"),
+                    &mut light.intensity,
+                    0.0,
+                    5.0,
+                )
+                .build();
+            }
+        }
+
+        if ui
+            .collapsing_header(&str_to_imstr("Point Lights"))
+            .default_open(true)
+            .build()
+        {
+            ui.checkbox(&str_to_imstr("Animate"), &mut self.animate_lights);
+            for (i, (light, range)) in self
+                .lights
+                .point_lights
+                .iter_mut()
+                .zip(self.point_light_ranges.iter_mut())
+                .enumerate()
+            {
+                ui.text(format!("Point Light {}", i));
+                let mut position: [f32; 3] = light.position.into();
+                if ui
+                    .drag_float3(
+                        &str_to_imstr(format!("Position##point{}", i)),
+                        &mut position,
+                    )
+                    .speed(0.05)
+                    .build()
+                {
+                    light.position = position.into();
+                }
+                let mut color: [f32; 3] = light.color.into();
+                if ui
+                    .color_edit(&str_to_imstr(format!("Color##point{}", i)), &mut color)
+                    .build()
+                {
+                    light.color = color.into();
+                }
+                ui.slider_float(
+                    &str_to_imstr(format!("Intensity##point{}", i)),
+                    &mut light.intensity,
+                    0.0,
+                    5.0,
+                )
+                .build();
+                if ui
+                    .slider_float(
+                        &str_to_imstr(format!("Range##point{}", i)),
+                        range,
+                        1.0,
+                        50.0,
+                    )
+                    .build()
+                {
+                    light.attenuation = Attenuation::from_range(*range);
+                }
+            }
+
+            if self.lights.point_lights.len() < crate::light::MAX_POINT_LIGHTS
+                && ui.button(&str_to_imstr("Add Point Light"), [150., 20.])
+            {
+                self.lights.point_lights.push(PointLight::default());
+                self.point_light_ranges.push(20.0);
+            }
+            if !self.lights.point_lights.is_empty()
+                && ui.button(&str_to_imstr("Remove Point Light"), [150., 20.])
+            {
+                self.lights.point_lights.pop();
+                self.point_light_ranges.pop();
+            }
+        }
+
+        if ui.collapsing_header(&str_to_imstr("Flashlight")).build() {
+            if ui.checkbox(&str_to_imstr("Enabled##spot"), &mut self.flashlight) {
+                self.lights.spot_lights.clear();
+                if self.flashlight {
+                    self.lights.spot_lights.push(SpotLight {
+                        attenuation: Attenuation::from_range(self.spot_range),
+                        ..Default::default()
+                    });
+                }
+            }
+            if let Some(light) = self.lights.spot_lights.first_mut() {
+                ui.slider_float(
+                    &str_to_imstr("Inner Angle"),
+                    &mut light.inner_angle,
+                    1.0,
+                    60.0,
+                )
+                .build();
+                ui.slider_float(
+                    &str_to_imstr("Outer Angle"),
+                    &mut light.outer_angle,
+                    1.0,
+                    60.0,
+                )
+                .build();
+                ui.slider_float(
+                    &str_to_imstr("Intensity##spot"),
+                    &mut light.intensity,
+                    0.0,
+                    5.0,
+                )
+                .build();
+                if ui
+                    .slider_float(
+                        &str_to_imstr("Range##spot"),
+                        &mut self.spot_range,
+                        1.0,
+                        50.0,
+                    )
+                    .build()
+                {
+                    light.attenuation = Attenuation::from_range(self.spot_range);
+                }
+            }
+        }
+
+        ui.text("Drag with the left mouse button to orbit, middle to pan, scroll to zoom");
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.camera_controller.update(delta_time, input);
+
+        if self.animate_lights {
+            self.time += delta_time;
+            let count = self.lights.point_lights.len().max(1) as f32;
+            for (i, light) in self.lights.point_lights.iter_mut().enumerate() {
+                let angle = self.time * 0.5 + i as f32 / count * std::f32::consts::TAU;
+                let radius = glm::length(&glm::vec2(light.position.x, light.position.z)).max(1.0);
+                light.position.x = angle.cos() * radius;
+                light.position.z = angle.sin() * radius;
+            }
+        }
+
+        // The flashlight follows the camera
+        let camera = self.camera_controller.camera();
+        let (position, forward) = (camera.position(), camera.forward());
+        if let Some(light) = self.lights.spot_lights.first_mut() {
+            light.position = position;
+            light.direction = forward;
+        }
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
+}
+
+impl TestableID for TestLighting {
+    fn test_id() -> String {
+        "lighting".into()
+    }
+
+    fn test_name() -> String {
+        "Lighting".into()
+    }
+}
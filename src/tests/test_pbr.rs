@@ -0,0 +1,246 @@
+use std::{path::Path, rc::Rc};
+
+use enum_iterator::all;
+use imgui_glfw_rs::imgui::{ImString, Ui};
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::{Camera, PerspectiveCamera},
+    camera_controller::OrbitCameraController,
+    gl_component::GLComponent,
+    ibl::{Environment, IblSettings, SkySettings, Skybox},
+    input::Input,
+    light::{normal_matrix, DirectionalLight, LightEnvironment, LIGHTS_BINDING},
+    material::Material,
+    mesh::{GpuMesh, Mesh},
+    pbr::{PbrMaterial, ToneMapOperator, ToneMapping},
+    renderer::{CullFace, Renderer},
+    str_to_imstr,
+    texture::Texture,
+    uniform_buffer::UniformBuffer,
+    ShaderBuilder,
+};
+
+use super::{Testable, TestableID};
+
+const ENVIRONMENT_PATH: &str = "src/res/textures/environment.hdr";
+const GRID_SIZE: usize = 7;
+const SPACING: f32 = 1.2;
+
+pub struct TestPbr {
+    sphere: GpuMesh,
+    material: Rc<Material>,
+    lights_buffer: UniformBuffer,
+    environment: Environment,
+    skybox: Skybox,
+
+    camera_controller: OrbitCameraController,
+    lights: LightEnvironment,
+
+    albedo: [f32; 3],
+    tone_mapping: ToneMapping,
+    use_ibl: bool,
+    environment_intensity: f32,
+    skybox_lod: f32,
+    show_skybox: bool,
+}
+
+impl Default for TestPbr {
+    fn default() -> Self {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/Pbr.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        shader.bind_uniform_block("Lights", LIGHTS_BINDING);
+
+        // Captures cover every pixel, the clear color never shows up in the maps
+        let renderer = Renderer::new((0.0, 0.0, 0.0, 1.0));
+        let settings = IblSettings::default();
+        let sky = SkySettings::default();
+
+        // Falls back to the procedural sky so the test runs without any HDR asset
+        let environment = if Path::new(ENVIRONMENT_PATH).exists() {
+            let hdr = Rc::new(Texture::new(ENVIRONMENT_PATH, 0));
+            Environment::from_equirectangular(&renderer, hdr, &settings)
+        } else {
+            Environment::from_sky(&renderer, &sky, &settings)
+        }
+        .expect("Failed to build the environment maps");
+
+        let camera = PerspectiveCamera::new(45.0, 1280.0 / 960.0, 0.1, 100.0);
+        let camera_controller = OrbitCameraController::new(camera, glm::vec3(0.0, 0.0, 0.0), 12.0);
+
+        Self {
+            sphere: Mesh::uv_sphere(0.5, 64, 32).upload(),
+            material: Rc::new(Material::new(shader)),
+            lights_buffer: UniformBuffer::new(LightEnvironment::std140_size(), LIGHTS_BINDING),
+            environment,
+            skybox: Skybox::default(),
+            camera_controller,
+            lights: LightEnvironment {
+                ambient_intensity: 0.0,
+                directional: Some(DirectionalLight {
+                    direction: sky.sun_direction,
+                    color: sky.sun_color,
+                    intensity: 3.0,
+                }),
+                ..Default::default()
+            },
+            albedo: [0.9, 0.1, 0.1],
+            tone_mapping: ToneMapping::default(),
+            use_ibl: true,
+            environment_intensity: 1.0,
+            skybox_lod: 0.0,
+            show_skybox: true,
+        }
+    }
+}
+
+impl Testable for TestPbr {
+    fn render(&self, _: (f32, f32), renderer: &Renderer) {
+        self.lights.upload(&self.lights_buffer);
+
+        renderer.set_depth_test(true);
+        renderer.set_face_culling(Some(CullFace::Back));
+
+        let camera = self.camera_controller.camera();
+        // The maps stay bound with IBL off, leaving the cube samplers without a texture
+        // would alias them with the 2D units
+        let mut frame = Material::instance(&self.material)
+            .with_uniform("u_ViewProjection", *camera.view_projection())
+            .with_uniform("u_ViewPosition", camera.position())
+            .with_uniform("u_EnvironmentIntensity", self.environment_intensity);
+        frame = self.environment.apply(frame);
+        frame = self.tone_mapping.apply(frame);
+        frame.set_uniform("u_UseIbl", self.use_ibl as i32);
+        let frame = Rc::new(frame);
+
+        // Metalness increases along the rows, roughness along the columns
+        let offset = (GRID_SIZE - 1) as f32 * SPACING / 2.0;
+        for row in 0..GRID_SIZE {
+            for column in 0..GRID_SIZE {
+                let parameters = PbrMaterial {
+                    albedo: glm::vec4(self.albedo[0], self.albedo[1], self.albedo[2], 1.0),
+                    metallic: row as f32 / (GRID_SIZE - 1) as f32,
+                    roughness: (column as f32 / (GRID_SIZE - 1) as f32).max(0.05),
+                    ..Default::default()
+                };
+                let model = glm::translate(
+                    &glm::Mat4::identity(),
+                    &glm::vec3(
+                        column as f32 * SPACING - offset,
+                        row as f32 * SPACING - offset,
+                        0.0,
+                    ),
+                );
+                let material = parameters.apply(
+                    Material::instance(&frame)
+                        .with_uniform("u_Model", model)
+                        .with_uniform("u_NormalMatrix", normal_matrix(&model)),
+                );
+                self.sphere.draw(renderer, &material);
+            }
+        }
+        frame.shader().unbind();
+
+        if self.show_skybox {
+            self.skybox.draw(
+                renderer,
+                camera,
+                self.environment.environment(),
+                self.skybox_lod,
+                &self.tone_mapping,
+            );
+        }
+
+        renderer.set_depth_test(false);
+        renderer.set_face_culling(None);
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        ui.color_edit(&str_to_imstr("Albedo"), &mut self.albedo)
+            .build();
+
+        ui.separator();
+        ui.checkbox(&str_to_imstr("Image Based Lighting"), &mut self.use_ibl);
+        ui.slider_float(
+            &str_to_imstr("Environment Intensity"),
+            &mut self.environment_intensity,
+            0.0,
+            3.0,
+        )
+        .build();
+        if let Some(sun) = self.lights.directional.as_mut() {
+            ui.slider_float(
+                &str_to_imstr("Sun Intensity"),
+                &mut sun.intensity,
+                0.0,
+                10.0,
+            )
+            .build();
+        }
+
+        ui.separator();
+        let operators: Vec<ToneMapOperator> = all::<ToneMapOperator>().collect();
+        let labels: Vec<ImString> = operators
+            .iter()
+            .map(|operator| str_to_imstr(operator.name()))
+            .collect();
+        let labels: Vec<_> = labels.iter().map(|label| label.as_ref()).collect();
+        let mut selected = operators
+            .iter()
+            .position(|&operator| operator == self.tone_mapping.operator)
+            .unwrap_or(0) as i32;
+        if ui.combo(
+            &str_to_imstr("Tone Mapping"),
+            &mut selected,
+            &labels,
+            labels.len() as i32,
+        ) {
+            self.tone_mapping.operator = operators[selected as usize];
+        }
+        ui.slider_float(
+            &str_to_imstr("Exposure"),
+            &mut self.tone_mapping.exposure,
+            0.1,
+            5.0,
+        )
+        .build();
+
+        ui.separator();
+        ui.checkbox(&str_to_imstr("Skybox"), &mut self.show_skybox);
+        ui.slider_float(
+            &str_to_imstr("Skybox Blur"),
+            &mut self.skybox_lod,
+            0.0,
+            self.environment.prefilter_mip_levels() as f32,
+        )
+        .build();
+
+        if !Path::new(ENVIRONMENT_PATH).exists() {
+            ui.text(format!(
+                "Place an equirectangular HDR image at {} to light the scene with it",
+                ENVIRONMENT_PATH
+            ));
+        }
+        ui.text("Drag with the left mouse button to orbit, scroll to zoom");
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.camera_controller.update(delta_time, input);
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
+}
+
+impl TestableID for TestPbr {
+    fn test_id() -> String {
+        "pbr".into()
+    }
+
+    fn test_name() -> String {
+        "PBR".into()
+    }
+}
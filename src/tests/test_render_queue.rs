@@ -0,0 +1,188 @@
+use std::{cell::Cell, rc::Rc, time::Instant};
+
+use imgui_glfw_rs::imgui::Ui;
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::{Camera, PerspectiveCamera},
+    camera_controller::FlyCameraController,
+    gl_component::GLComponent,
+    index_buffer::IndexBuffer,
+    input::Input,
+    material::Material,
+    render_queue::{RenderQueue, RenderStats},
+    renderer::{CullFace, Renderer},
+    str_to_imstr,
+    texture::Texture,
+    vertex_array::VertexArray,
+    vertex_buffer::VertexBuffer,
+    vertex_buffer_layout::VertexBufferLayout,
+    ShaderBuilder,
+};
+
+use super::{
+    test_3d::{gen_cube_indices, gen_cube_vertices},
+    Testable, TestableID,
+};
+
+const TEXTURES: [&str; 3] = [
+    "src/res/textures/rust.png",
+    "src/res/textures/heart.png",
+    "src/res/textures/phone.png",
+];
+const FAR_PLANE: f32 = 200.0;
+
+/// A cube mesh, each size gets its own vertex array
+struct Cube {
+    vao: VertexArray,
+    ibo: IndexBuffer,
+}
+
+impl Cube {
+    fn new(size: f32) -> Self {
+        let layout = VertexBufferLayout::default().with_floats(3).with_floats(2);
+        let mut vao = VertexArray::new();
+        let vbo = VertexBuffer::new(&gen_cube_vertices(size));
+        let ibo = IndexBuffer::new(&gen_cube_indices());
+        vao.add_buffer(&vbo, &layout);
+
+        vao.unbind();
+        vbo.unbind();
+        ibo.unbind();
+        Self { vao, ibo }
+    }
+}
+
+pub struct TestRenderQueue {
+    cubes: [Cube; 2],
+    materials: Vec<Rc<Material>>,
+    camera_controller: FlyCameraController,
+
+    grid_size: i32,
+    use_queue: bool,
+    stats: Cell<RenderStats>,
+    cpu_time: Cell<f32>,
+    frame_time: f32,
+}
+
+impl Default for TestRenderQueue {
+    fn default() -> Self {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/Default.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        let materials = TEXTURES
+            .into_iter()
+            .map(|path| {
+                let texture = Texture::new(path, 0);
+                Rc::new(Material::new(shader.clone()).with_texture("u_Texture", Rc::new(texture)))
+            })
+            .collect();
+
+        let mut camera = PerspectiveCamera::new(45.0, 1280.0 / 960.0, 0.1, FAR_PLANE);
+        camera.set_position(glm::vec3(0.0, 20.0, 45.0));
+        camera.look_at(&glm::vec3(0.0, 0.0, 0.0));
+
+        Self {
+            cubes: [Cube::new(1.0), Cube::new(0.6)],
+            materials,
+            camera_controller: FlyCameraController::new(camera),
+            grid_size: 40,
+            use_queue: true,
+            stats: Cell::new(RenderStats::default()),
+            cpu_time: Cell::new(0.0),
+            frame_time: 0.0,
+        }
+    }
+}
+
+impl Testable for TestRenderQueue {
+    fn render(&self, _: (f32, f32), renderer: &Renderer) {
+        let start = Instant::now();
+        let camera = self.camera_controller.camera();
+        let view_projection = camera.view_projection();
+
+        // Neighbouring cubes alternate mesh and material, the worst order for immediate draws
+        let half = self.grid_size as f32 / 2.0;
+        let objects: Vec<_> = (0..self.grid_size * self.grid_size)
+            .map(|i| {
+                let (x, z) = (i % self.grid_size, i / self.grid_size);
+                let position = glm::vec3(x as f32 - half, 0.0, z as f32 - half) * 1.5;
+                let model = glm::translation(&position);
+                let material = Material::instance(&self.materials[i as usize % TEXTURES.len()])
+                    .with_uniform("u_MVP", view_projection * model);
+                let depth = glm::distance(&position, &camera.position()) / FAR_PLANE;
+                (&self.cubes[i as usize % 2], material, depth)
+            })
+            .collect();
+
+        renderer.set_depth_test(true);
+        renderer.set_face_culling(Some(CullFace::Back));
+        let stats = if self.use_queue {
+            let mut queue = RenderQueue::default();
+            for (cube, material, depth) in &objects {
+                queue.submit(0, *depth, &cube.vao, &cube.ibo, material);
+            }
+            renderer.execute(&mut queue)
+        } else {
+            for (cube, material, _) in &objects {
+                renderer.draw(&cube.vao, &cube.ibo, material);
+            }
+            RenderStats {
+                draw_calls: objects.len(),
+                material_changes: objects.len(),
+                ..Default::default()
+            }
+        };
+        renderer.set_face_culling(None);
+        renderer.set_depth_test(false);
+
+        self.stats.set(stats);
+        self.cpu_time.set(start.elapsed().as_secs_f32());
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        ui.checkbox(&str_to_imstr("Use Render Queue"), &mut self.use_queue);
+        ui.slider_int(&str_to_imstr("Grid Size"), &mut self.grid_size, 1, 150)
+            .build();
+
+        let stats = self.stats.get();
+        ui.separator();
+        ui.text(format!(
+            "{} draw calls, {} material changes",
+            stats.draw_calls, stats.material_changes
+        ));
+        if self.use_queue {
+            ui.text(format!(
+                "{} binds issued, {} skipped",
+                stats.binds.issued, stats.binds.skipped
+            ));
+        }
+        ui.text(format!(
+            "Submitting and drawing: {:.2} ms, frame: {:.2} ms",
+            self.cpu_time.get() * 1000.0,
+            self.frame_time * 1000.0
+        ));
+        ui.text("Hold the right mouse button to look around, WASD to move");
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        // Smoothed so the numbers stay readable
+        self.frame_time += (delta_time - self.frame_time) * 0.05;
+        self.camera_controller.update(delta_time, input);
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
+}
+
+impl TestableID for TestRenderQueue {
+    fn test_id() -> String {
+        "render_queue".into()
+    }
+
+    fn test_name() -> String {
+        "Render Queue".into()
+    }
+}
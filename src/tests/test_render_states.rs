@@ -0,0 +1,278 @@
+use std::rc::Rc;
+
+use imgui_glfw_rs::imgui::Ui;
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::{Camera, PerspectiveCamera},
+    camera_controller::FlyCameraController,
+    gl_component::GLComponent,
+    index_buffer::IndexBuffer,
+    input::Input,
+    material::Material,
+    render_state::{
+        BlendState, CompareFunc, DepthState, PolygonMode, RasterizerState, RenderState,
+        StencilState,
+    },
+    renderer::{CullFace, Renderer},
+    str_to_imstr,
+    texture::Texture,
+    vertex_array::VertexArray,
+    vertex_buffer::VertexBuffer,
+    vertex_buffer_layout::VertexBufferLayout,
+    ShaderBuilder,
+};
+
+use super::{
+    test_3d::{gen_cube_indices, gen_cube_vertices},
+    test_sampling::combo,
+    Testable, TestableID,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlendPreset {
+    Opaque,
+    Alpha,
+    Additive,
+    Premultiplied,
+}
+
+const BLEND_PRESETS: [(BlendPreset, &str); 4] = [
+    (BlendPreset::Opaque, "Opaque"),
+    (BlendPreset::Alpha, "Alpha"),
+    (BlendPreset::Additive, "Additive"),
+    (BlendPreset::Premultiplied, "Premultiplied"),
+];
+const POLYGON_MODES: [(PolygonMode, &str); 3] = [
+    (PolygonMode::Fill, "Fill"),
+    (PolygonMode::Line, "Wireframe"),
+    (PolygonMode::Point, "Points"),
+];
+const CULL_FACES: [(Option<CullFace>, &str); 3] = [
+    (None, "None"),
+    (Some(CullFace::Back), "Back"),
+    (Some(CullFace::Front), "Front"),
+];
+const DEPTH_FUNCS: [(CompareFunc, &str); 4] = [
+    (CompareFunc::Less, "Less"),
+    (CompareFunc::LessEqual, "Less Equal"),
+    (CompareFunc::Greater, "Greater"),
+    (CompareFunc::Always, "Always"),
+];
+
+pub struct TestRenderStates {
+    vao: VertexArray,
+    ibo: IndexBuffer,
+    materials: [Rc<Material>; 2],
+    outline_material: Rc<Material>,
+    camera_controller: FlyCameraController,
+    rotation: f32,
+
+    blend: BlendPreset,
+    polygon_mode: PolygonMode,
+    cull: Option<CullFace>,
+    depth_test: bool,
+    depth_write: bool,
+    depth_func: CompareFunc,
+    scissor: bool,
+    scissor_size: f32,
+    outline: bool,
+    outline_scale: f32,
+    outline_color: [f32; 4],
+}
+
+impl Default for TestRenderStates {
+    fn default() -> Self {
+        let layout = VertexBufferLayout::default().with_floats(3).with_floats(2);
+        let mut vao = VertexArray::new();
+        let vbo = VertexBuffer::new(&gen_cube_vertices(1.0));
+        let ibo = IndexBuffer::new(&gen_cube_indices());
+        vao.add_buffer(&vbo, &layout);
+
+        vao.unbind();
+        vbo.unbind();
+        ibo.unbind();
+
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/Default.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        let material = |path: &str| {
+            let texture = Texture::new(path, 0);
+            Rc::new(Material::new(shader.clone()).with_texture("u_Texture", Rc::new(texture)))
+        };
+        let outline_shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/FlatColor.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+
+        let mut camera = PerspectiveCamera::new(45.0, 1280.0 / 960.0, 0.1, 100.0);
+        camera.set_position(glm::vec3(0.0, 1.0, 5.0));
+        camera.look_at(&glm::vec3(0.0, 0.0, 0.0));
+
+        Self {
+            vao,
+            ibo,
+            materials: [
+                material("src/res/textures/rust.png"),
+                material("src/res/textures/heart.png"),
+            ],
+            outline_material: Rc::new(Material::new(outline_shader)),
+            camera_controller: FlyCameraController::new(camera),
+            rotation: 0.0,
+            blend: BlendPreset::Alpha,
+            polygon_mode: PolygonMode::Fill,
+            cull: None,
+            depth_test: true,
+            depth_write: true,
+            depth_func: CompareFunc::Less,
+            scissor: false,
+            scissor_size: 0.5,
+            outline: true,
+            outline_scale: 1.08,
+            outline_color: [1.0, 0.6, 0.1, 1.0],
+        }
+    }
+}
+
+impl TestRenderStates {
+    fn render_state(&self, screen_size: (f32, f32)) -> RenderState {
+        let blend = match self.blend {
+            BlendPreset::Opaque => BlendState::opaque(),
+            BlendPreset::Alpha => BlendState::alpha(),
+            BlendPreset::Additive => BlendState::additive(),
+            BlendPreset::Premultiplied => BlendState::premultiplied(),
+        };
+        let depth = DepthState {
+            test: self.depth_test,
+            write: self.depth_write,
+            func: self.depth_func,
+        };
+        let mut rasterizer = RasterizerState::default()
+            .with_cull(self.cull)
+            .with_polygon_mode(self.polygon_mode);
+        if self.scissor {
+            let (width, height) = (
+                screen_size.0 * self.scissor_size,
+                screen_size.1 * self.scissor_size,
+            );
+            rasterizer = rasterizer.with_scissor(
+                ((screen_size.0 - width) / 2.0) as i32,
+                ((screen_size.1 - height) / 2.0) as i32,
+                width as i32,
+                height as i32,
+            );
+        }
+
+        RenderState::default()
+            .with_blend(blend)
+            .with_depth(depth)
+            .with_rasterizer(rasterizer)
+    }
+}
+
+impl Testable for TestRenderStates {
+    fn render(&self, screen_size: (f32, f32), renderer: &Renderer) {
+        let view_projection = self.camera_controller.camera().view_projection();
+        let state = self.render_state(screen_size);
+        let spin = glm::rotate(
+            &glm::Mat4::identity(),
+            self.rotation.to_radians(),
+            &glm::vec3(0.5, 1.0, 0.0).normalize(),
+        );
+        let sides = [
+            glm::translation(&glm::vec3(-1.2, 0.0, -0.6)) * spin,
+            glm::translation(&glm::vec3(1.2, 0.0, -0.6)) * spin,
+        ];
+
+        renderer.set_render_state(&state);
+        for (model, material) in sides.iter().zip(&self.materials) {
+            let material =
+                Material::instance(material).with_uniform("u_MVP", view_projection * model);
+            renderer.draw(&self.vao, &self.ibo, &material);
+        }
+
+        // The centre cube marks the stencil buffer, the outline is drawn where it did not
+        if self.outline {
+            renderer.set_stencil_state(&StencilState::write(1));
+        }
+        let material =
+            Material::instance(&self.materials[0]).with_uniform("u_MVP", view_projection * spin);
+        renderer.draw(&self.vao, &self.ibo, &material);
+
+        if self.outline {
+            let outline = glm::scale(&spin, &glm::vec3(1.0, 1.0, 1.0).scale(self.outline_scale));
+            let material = Material::instance(&self.outline_material)
+                .with_uniform("u_MVP", view_projection * outline)
+                .with_uniform("u_Color", glm::Vec4::from(self.outline_color));
+            renderer.set_render_state(
+                &state
+                    .with_depth(DepthState::disabled())
+                    .with_stencil(StencilState::not_equal(1)),
+            );
+            renderer.draw(&self.vao, &self.ibo, &material);
+        }
+        material.shader().unbind();
+
+        renderer.set_render_state(&RenderState::default());
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        combo(ui, "Blend", &mut self.blend, &BLEND_PRESETS);
+        combo(ui, "Polygon Mode", &mut self.polygon_mode, &POLYGON_MODES);
+        combo(ui, "Cull Faces", &mut self.cull, &CULL_FACES);
+
+        ui.separator();
+        ui.checkbox(&str_to_imstr("Depth Test"), &mut self.depth_test);
+        ui.same_line(0.0);
+        ui.checkbox(&str_to_imstr("Depth Write"), &mut self.depth_write);
+        combo(ui, "Depth Func", &mut self.depth_func, &DEPTH_FUNCS);
+
+        ui.separator();
+        ui.checkbox(&str_to_imstr("Scissor"), &mut self.scissor);
+        if self.scissor {
+            ui.slider_float(
+                &str_to_imstr("Scissor Size"),
+                &mut self.scissor_size,
+                0.1,
+                1.0,
+            )
+            .build();
+        }
+
+        ui.separator();
+        ui.checkbox(&str_to_imstr("Stencil Outline"), &mut self.outline);
+        if self.outline {
+            ui.slider_float(
+                &str_to_imstr("Outline Scale"),
+                &mut self.outline_scale,
+                1.0,
+                1.3,
+            )
+            .build();
+            ui.color_edit(&str_to_imstr("Outline Color"), &mut self.outline_color)
+                .build();
+        }
+
+        ui.text("Hold the right mouse button to look around, WASD to move");
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.rotation = (self.rotation + 30.0 * delta_time) % 360.0;
+        self.camera_controller.update(delta_time, input);
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
+}
+
+impl TestableID for TestRenderStates {
+    fn test_id() -> String {
+        "render_states".into()
+    }
+
+    fn test_name() -> String {
+        "Render States".into()
+    }
+}
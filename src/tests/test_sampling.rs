@@ -0,0 +1,270 @@
+use std::rc::Rc;
+
+use imgui_glfw_rs::imgui::{ImString, Ui};
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::{Camera, PerspectiveCamera},
+    camera_controller::FlyCameraController,
+    gl_component::GLComponent,
+    input::Input,
+    light::{normal_matrix, DirectionalLight, LightEnvironment, LIGHTS_BINDING},
+    material::Material,
+    mesh::{GpuMesh, Mesh},
+    renderer::{CullFace, Renderer},
+    sampler::{max_anisotropy, Sampler, TextureFilter, TextureSpec, WrapMode},
+    str_to_imstr,
+    texture::{Texture, TextureObject},
+    uniform_buffer::UniformBuffer,
+    ShaderBuilder,
+};
+
+use super::{Testable, TestableID};
+
+const FILTERS: [TextureFilter; 2] = [TextureFilter::Nearest, TextureFilter::Linear];
+const WRAP_MODES: [WrapMode; 4] = [
+    WrapMode::Repeat,
+    WrapMode::MirroredRepeat,
+    WrapMode::ClampToEdge,
+    WrapMode::ClampToBorder,
+];
+
+pub fn combo<T: Copy + PartialEq>(
+    ui: &Ui,
+    label: &str,
+    value: &mut T,
+    items: &[(T, &str)],
+) -> bool {
+    let labels: Vec<ImString> = items.iter().map(|(_, name)| str_to_imstr(*name)).collect();
+    let labels: Vec<_> = labels.iter().map(|label| label.as_ref()).collect();
+    let mut selected = items
+        .iter()
+        .position(|(item, _)| item == value)
+        .unwrap_or(0) as i32;
+    let changed = ui.combo(
+        &str_to_imstr(label),
+        &mut selected,
+        &labels,
+        labels.len() as i32,
+    );
+    *value = items[selected as usize].0;
+    changed
+}
+
+pub struct TestSampling {
+    floor_plane: GpuMesh,
+    sprite_plane: GpuMesh,
+    material: Rc<Material>,
+    lights_buffer: UniformBuffer,
+    lights: LightEnvironment,
+    camera_controller: FlyCameraController,
+
+    floor: Rc<Texture>,
+    floor_spec: TextureSpec,
+    floor_sampler: Rc<Sampler>,
+    sprite_crisp: Rc<Texture>,
+    sprite_smooth: Rc<Texture>,
+}
+
+impl Default for TestSampling {
+    fn default() -> Self {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/Lit.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        shader.bind_uniform_block("Lights", LIGHTS_BINDING);
+        let material = Material::new(shader)
+            .with_uniform("u_DiffuseColor", glm::vec4(1.0, 1.0, 1.0, 1.0))
+            .with_uniform("u_HasDiffuseTexture", 1)
+            .with_uniform("u_Emissive", glm::vec3(0.0, 0.0, 0.0))
+            .with_uniform("u_SpecularColor", glm::vec3(0.0, 0.0, 0.0))
+            .with_uniform("u_Shininess", 1.0)
+            .with_uniform("u_BlinnPhong", 1);
+
+        // The floor gets mipmaps once, the sampler object decides whether they are used
+        let floor = Texture::new("src/res/textures/rust.png", 0).with_spec(TextureSpec::tiled());
+        let floor_spec = TextureSpec::tiled();
+
+        // The same sprite with its own parameters, once crisp and once smoothed
+        let sprite_crisp =
+            Texture::new("src/res/textures/heart.png", 0).with_spec(TextureSpec::pixel_art());
+        let sprite_smooth = Texture::new("src/res/textures/heart.png", 0);
+
+        let mut camera = PerspectiveCamera::new(45.0, 1280.0 / 960.0, 0.1, 200.0);
+        camera.set_position(glm::vec3(0.0, 1.5, 6.0));
+        camera.look_at(&glm::vec3(0.0, 1.0, 0.0));
+
+        Self {
+            // Repeating the texture 40 times shows aliasing in the distance
+            floor_plane: Mesh::plane(120.0, 40.0).upload(),
+            sprite_plane: Mesh::plane(2.0, 1.0).upload(),
+            material: Rc::new(material),
+            lights_buffer: UniformBuffer::new(LightEnvironment::std140_size(), LIGHTS_BINDING),
+            lights: LightEnvironment {
+                ambient_intensity: 0.4,
+                directional: Some(DirectionalLight::default()),
+                ..Default::default()
+            },
+            camera_controller: FlyCameraController::new(camera),
+            floor: Rc::new(floor),
+            floor_spec,
+            floor_sampler: Rc::new(Sampler::new(floor_spec)),
+            sprite_crisp: Rc::new(sprite_crisp),
+            sprite_smooth: Rc::new(sprite_smooth),
+        }
+    }
+}
+
+impl TestSampling {
+    fn draw_plane(
+        &self,
+        renderer: &Renderer,
+        plane: &GpuMesh,
+        frame: &Rc<Material>,
+        model: glm::Mat4,
+        customize: impl FnOnce(Material) -> Material,
+    ) {
+        let material = Material::instance(frame)
+            .with_uniform("u_Model", model)
+            .with_uniform("u_NormalMatrix", normal_matrix(&model));
+        plane.draw(renderer, &customize(material));
+    }
+}
+
+impl Testable for TestSampling {
+    fn render(&self, _: (f32, f32), renderer: &Renderer) {
+        self.lights.upload(&self.lights_buffer);
+
+        renderer.set_depth_test(true);
+        renderer.set_face_culling(Some(CullFace::Back));
+
+        let camera = self.camera_controller.camera();
+        let frame = Rc::new(
+            Material::instance(&self.material)
+                .with_uniform("u_View", *camera.view())
+                .with_uniform("u_ViewProjection", *camera.view_projection())
+                .with_uniform("u_ViewPosition", camera.position()),
+        );
+
+        let floor = glm::Mat4::identity();
+        self.draw_plane(renderer, &self.floor_plane, &frame, floor, |material| {
+            material
+                .with_texture("u_DiffuseTexture", self.floor.clone())
+                .with_sampler("u_DiffuseTexture", self.floor_sampler.clone())
+        });
+
+        let sprites = [(-1.2, &self.sprite_crisp), (1.2, &self.sprite_smooth)];
+        for (x, sprite) in sprites {
+            let model = glm::translate(&glm::Mat4::identity(), &glm::vec3(x, 1.2, 0.0));
+            let model = glm::rotate(&model, 90f32.to_radians(), &glm::vec3(1.0, 0.0, 0.0));
+            self.draw_plane(renderer, &self.sprite_plane, &frame, model, |material| {
+                material.with_texture("u_DiffuseTexture", sprite.clone())
+            });
+        }
+
+        frame.shader().unbind();
+        renderer.set_depth_test(false);
+        renderer.set_face_culling(None);
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        let filters: Vec<_> = FILTERS
+            .iter()
+            .map(|&filter| {
+                let name = match filter {
+                    TextureFilter::Nearest => "Nearest",
+                    TextureFilter::Linear => "Linear",
+                };
+                (filter, name)
+            })
+            .collect();
+        let mipmap_filters: Vec<_> = std::iter::once((None, "None"))
+            .chain(filters.iter().map(|&(filter, name)| (Some(filter), name)))
+            .collect();
+        let wrap_modes: Vec<_> = WRAP_MODES
+            .iter()
+            .map(|&mode| {
+                let name = match mode {
+                    WrapMode::Repeat => "Repeat",
+                    WrapMode::MirroredRepeat => "Mirrored Repeat",
+                    WrapMode::ClampToEdge => "Clamp To Edge",
+                    WrapMode::ClampToBorder => "Clamp To Border",
+                };
+                (mode, name)
+            })
+            .collect();
+
+        let spec = &mut self.floor_spec;
+        let mut changed = false;
+        ui.text("Floor sampler");
+        changed |= combo(ui, "Min Filter", &mut spec.min_filter, &filters);
+        changed |= combo(ui, "Mag Filter", &mut spec.mag_filter, &filters);
+        changed |= combo(
+            ui,
+            "Mipmap Filter",
+            &mut spec.mipmap_filter,
+            &mipmap_filters,
+        );
+        changed |= combo(ui, "Wrap S", &mut spec.wrap_s, &wrap_modes);
+        changed |= combo(ui, "Wrap T", &mut spec.wrap_t, &wrap_modes);
+        let mut border = [
+            spec.border_color[0],
+            spec.border_color[1],
+            spec.border_color[2],
+        ];
+        if ui
+            .color_edit(&str_to_imstr("Border Color"), &mut border)
+            .build()
+        {
+            spec.border_color = [border[0], border[1], border[2], 1.0];
+            changed = true;
+        }
+        match max_anisotropy() {
+            Some(max) => {
+                changed |= ui
+                    .slider_float(&str_to_imstr("Anisotropy"), &mut spec.anisotropy, 1.0, max)
+                    .build();
+            }
+            None => ui.text("Anisotropic filtering is not supported"),
+        }
+        changed |= ui
+            .slider_float(&str_to_imstr("LOD Bias"), &mut spec.lod_bias, -2.0, 4.0)
+            .build();
+
+        if ui.button(&str_to_imstr("Pixel Art"), [0.0, 0.0]) {
+            *spec = TextureSpec::pixel_art().with_wrap(WrapMode::Repeat);
+            changed = true;
+        }
+        ui.same_line(0.0);
+        if ui.button(&str_to_imstr("Tiled"), [0.0, 0.0]) {
+            *spec = TextureSpec::tiled();
+            changed = true;
+        }
+
+        if changed {
+            self.floor_sampler = Rc::new(Sampler::new(self.floor_spec));
+        }
+
+        ui.separator();
+        ui.text("Left sprite: nearest filtering, right sprite: linear filtering");
+        ui.text("Hold the right mouse button to look around, WASD to move");
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.camera_controller.update(delta_time, input);
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
+}
+
+impl TestableID for TestSampling {
+    fn test_id() -> String {
+        "sampling".into()
+    }
+
+    fn test_name() -> String {
+        "Texture Sampling".into()
+    }
+}
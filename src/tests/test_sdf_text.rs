@@ -0,0 +1,198 @@
+use std::path::PathBuf;
+
+use imgui_glfw_rs::imgui::Ui;
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::Camera,
+    camera_controller::OrthographicCameraController,
+    font::{FontId, FontLibrary},
+    input::Input,
+    renderer::Renderer,
+    sdf_text::{SdfFont, SdfFontBuilder, SdfTextRenderer, SdfTextStyle},
+    str_to_imstr,
+    text::TextRenderer,
+};
+
+use super::{Testable, TestableID};
+
+const SIZES: [f32; 5] = [12.0, 18.0, 32.0, 64.0, 128.0];
+
+pub struct TestSdfText {
+    camera_controller: OrthographicCameraController,
+    sdf_renderer: SdfTextRenderer,
+    bitmap_renderer: TextRenderer,
+    source: String,
+
+    color: [f32; 4],
+    outline_width: f32,
+    outline_color: [f32; 4],
+    glow_width: f32,
+    glow_color: [f32; 4],
+    shadow_offset: [f32; 2],
+    shadow_softness: f32,
+    shadow_color: [f32; 4],
+}
+
+impl TestSdfText {
+    /// The precomputed font is kept outside the repository, it is generated on first run
+    fn cache_path() -> PathBuf {
+        std::env::temp_dir().join("glfw_app_default.sdf.json")
+    }
+
+    fn generate() -> SdfFont {
+        SdfFontBuilder::new(&FontLibrary::default(), FontId::default())
+            .build()
+            .expect("Failed to generate the distance field font")
+    }
+
+    fn style(&self) -> SdfTextStyle {
+        SdfTextStyle::default()
+            .with_outline(self.outline_width, self.outline_color.into())
+            .with_glow(self.glow_width, self.glow_color.into())
+            .with_shadow(
+                self.shadow_offset.into(),
+                self.shadow_softness,
+                self.shadow_color.into(),
+            )
+    }
+}
+
+impl Default for TestSdfText {
+    fn default() -> Self {
+        let (font, source) = match SdfFont::load(Self::cache_path()) {
+            Ok(font) => (font, "loaded from disk".to_string()),
+            Err(_) => {
+                let font = Self::generate();
+                let source = match font.save(Self::cache_path()) {
+                    Ok(()) => "generated and saved".to_string(),
+                    Err(e) => format!("generated, saving failed: {}", e),
+                };
+                (font, source)
+            }
+        };
+
+        Self {
+            camera_controller: OrthographicCameraController::new((1280.0, 960.0)),
+            sdf_renderer: SdfTextRenderer::new(font)
+                .expect("Failed to upload the distance field font"),
+            bitmap_renderer: TextRenderer::default(),
+            source,
+            color: [1.0, 1.0, 1.0, 1.0],
+            outline_width: 2.0,
+            outline_color: [0.1, 0.1, 0.4, 1.0],
+            glow_width: 0.0,
+            glow_color: [1.0, 0.6, 0.1, 0.8],
+            shadow_offset: [3.0, 3.0],
+            shadow_softness: 2.0,
+            shadow_color: [0.0, 0.0, 0.0, 0.6],
+        }
+    }
+}
+
+impl Testable for TestSdfText {
+    fn render(&self, _: (f32, f32), _: &Renderer) {
+        let view_projection = self.camera_controller.camera().view_projection();
+        self.bitmap_renderer.draw(view_projection);
+        self.sdf_renderer.draw(view_projection, &self.style());
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        ui.text("Zoom in to compare distance field and bitmap text");
+        ui.color_edit(&str_to_imstr("Color"), &mut self.color)
+            .build();
+
+        ui.separator();
+        ui.slider_float(
+            &str_to_imstr("Outline Width"),
+            &mut self.outline_width,
+            0.0,
+            8.0,
+        )
+        .build();
+        ui.color_edit(&str_to_imstr("Outline Color"), &mut self.outline_color)
+            .build();
+        ui.slider_float(&str_to_imstr("Glow Width"), &mut self.glow_width, 0.0, 8.0)
+            .build();
+        ui.color_edit(&str_to_imstr("Glow Color"), &mut self.glow_color)
+            .build();
+        ui.drag_float2(&str_to_imstr("Shadow Offset"), &mut self.shadow_offset)
+            .build();
+        ui.slider_float(
+            &str_to_imstr("Shadow Softness"),
+            &mut self.shadow_softness,
+            0.0,
+            8.0,
+        )
+        .build();
+        ui.color_edit(&str_to_imstr("Shadow Color"), &mut self.shadow_color)
+            .build();
+
+        ui.separator();
+        let font = self.sdf_renderer.font();
+        ui.text(format!(
+            "{} glyphs at {}px, spread {}px, {}x{} atlas {}",
+            font.glyphs.len(),
+            font.size,
+            font.spread,
+            font.image.width(),
+            font.image.height(),
+            self.source
+        ));
+        if ui.button(&str_to_imstr("Regenerate and save"), [0.0, 0.0]) {
+            let font = Self::generate();
+            self.source = match font.save(Self::cache_path()) {
+                Ok(()) => format!("saved to {}", Self::cache_path().display()),
+                Err(e) => format!("saving failed: {}", e),
+            };
+            self.sdf_renderer =
+                SdfTextRenderer::new(font).expect("Failed to upload the distance field font");
+        }
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.camera_controller.update(delta_time, input);
+
+        let color = glm::Vec4::from(self.color);
+        let mut y = 920.0;
+        for size in SIZES {
+            let text = format!("Distance field {}px", size);
+            self.sdf_renderer
+                .queue(&text, glm::vec2(40.0, y), size, color);
+            self.bitmap_renderer.queue(
+                "Bitmap",
+                glm::vec2(
+                    40.0 + self.sdf_renderer.font().measure(&text, size).x + size * 0.5,
+                    y,
+                ),
+                size,
+                glm::vec4(0.6, 0.6, 0.6, 1.0),
+            );
+            y -= self.sdf_renderer.font().line_height(size) + 16.0;
+        }
+
+        self.sdf_renderer.queue(
+            "Outlines, glows and shadows\nscale with the text.",
+            glm::vec2(40.0, y),
+            40.0,
+            color,
+        );
+
+        self.sdf_renderer.prepare();
+        self.bitmap_renderer.prepare();
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
+}
+
+impl TestableID for TestSdfText {
+    fn test_id() -> String {
+        "sdf_text".into()
+    }
+
+    fn test_name() -> String {
+        "SDF Text".into()
+    }
+}
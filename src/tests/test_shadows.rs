@@ -0,0 +1,412 @@
+use std::rc::Rc;
+
+use imgui_glfw_rs::imgui::{ImString, TextureId, Ui};
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::{Camera, PerspectiveCamera},
+    camera_controller::FlyCameraController,
+    gl_component::GLComponent,
+    input::Input,
+    light::{
+        normal_matrix, Attenuation, DirectionalLight, LightEnvironment, SpotLight, LIGHTS_BINDING,
+    },
+    material::Material,
+    mesh::{GpuMesh, Mesh},
+    renderer::{CullFace, Renderer},
+    shadow::{spot_light_space, CascadedShadowMap, ShadowMap, ShadowSettings, MAX_CASCADES},
+    str_to_imstr,
+    uniform_buffer::UniformBuffer,
+    ShaderBuilder,
+};
+
+use super::{Testable, TestableID};
+
+const RESOLUTIONS: [usize; 4] = [512, 1024, 2048, 4096];
+
+struct Caster {
+    model: glm::Mat4,
+    color: glm::Vec4,
+}
+
+pub struct TestShadows {
+    cube: GpuMesh,
+    plane: GpuMesh,
+    lit_material: Rc<Material>,
+    depth_material: Rc<Material>,
+    lights_buffer: UniformBuffer,
+
+    camera_controller: FlyCameraController,
+    casters: Vec<Caster>,
+
+    lights: LightEnvironment,
+    sun_azimuth: f32,
+    sun_elevation: f32,
+    animate_sun: bool,
+    spot_enabled: bool,
+
+    settings: ShadowSettings,
+    cascades: CascadedShadowMap,
+    spot_shadow: ShadowMap,
+    cascade_count: i32,
+    show_cascades: bool,
+    debug_map: i32,
+}
+
+impl Default for TestShadows {
+    fn default() -> Self {
+        let lit_shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/Lit.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        lit_shader.bind_uniform_block("Lights", LIGHTS_BINDING);
+        let lit_material = Material::new(lit_shader)
+            .with_uniform("u_HasDiffuseTexture", 0)
+            .with_uniform("u_Emissive", glm::vec3(0.0, 0.0, 0.0))
+            .with_uniform("u_SpecularColor", glm::vec3(0.2, 0.2, 0.2))
+            .with_uniform("u_Shininess", 16.0)
+            .with_uniform("u_BlinnPhong", 1);
+
+        let depth_shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/ShadowDepth.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+
+        let mut camera = PerspectiveCamera::new(45.0, 1280.0 / 960.0, 0.1, 100.0);
+        camera.set_position(glm::vec3(-8.0, 5.0, 10.0));
+        camera.look_at(&glm::vec3(0.0, 0.0, 0.0));
+
+        // A grid of pillars stretching into the distance shows every cascade at once
+        let mut casters = Vec::new();
+        for x in -3..=3 {
+            for z in -6..=1 {
+                let height = 0.5 + ((x * 7 + z * 13i32).rem_euclid(5)) as f32 * 0.5;
+                let model = glm::translate(
+                    &glm::Mat4::identity(),
+                    &glm::vec3(x as f32 * 3.0, height / 2.0, z as f32 * 4.0),
+                );
+                casters.push(Caster {
+                    model: glm::scale(&model, &glm::vec3(0.8, height, 0.8)),
+                    color: glm::vec4(
+                        0.4 + (x + 3) as f32 * 0.08,
+                        0.5,
+                        0.4 + (z + 6) as f32 * 0.06,
+                        1.0,
+                    ),
+                });
+            }
+        }
+
+        let settings = ShadowSettings::default();
+        let cascade_count = 3;
+
+        let mut instance = Self {
+            cube: Mesh::cube(1.0).upload(),
+            plane: Mesh::plane(80.0, 1.0).upload(),
+            lit_material: Rc::new(lit_material),
+            depth_material: Rc::new(Material::new(depth_shader)),
+            lights_buffer: UniformBuffer::new(LightEnvironment::std140_size(), LIGHTS_BINDING),
+            camera_controller: FlyCameraController::new(camera),
+            casters,
+            lights: LightEnvironment {
+                ambient_intensity: 0.15,
+                ..Default::default()
+            },
+            sun_azimuth: 45.0,
+            sun_elevation: 50.0,
+            animate_sun: false,
+            spot_enabled: false,
+            settings,
+            cascades: CascadedShadowMap::new(cascade_count, settings.resolution)
+                .expect("Failed to create the cascaded shadow map"),
+            spot_shadow: ShadowMap::new(settings.resolution)
+                .expect("Failed to create the spot light shadow map"),
+            cascade_count: cascade_count as i32,
+            show_cascades: false,
+            debug_map: 0,
+        };
+        instance.update_lights();
+        instance
+    }
+}
+
+impl TestShadows {
+    fn sun_direction(&self) -> glm::Vec3 {
+        let (azimuth, elevation) = (
+            self.sun_azimuth.to_radians(),
+            self.sun_elevation.to_radians(),
+        );
+        -glm::vec3(
+            azimuth.cos() * elevation.cos(),
+            elevation.sin(),
+            azimuth.sin() * elevation.cos(),
+        )
+    }
+
+    fn update_lights(&mut self) {
+        let direction = self.sun_direction();
+        if let Some(sun) = self.lights.directional.as_mut() {
+            sun.direction = direction;
+        }
+        self.cascades
+            .update(self.camera_controller.camera(), &direction);
+
+        self.lights.spot_lights.clear();
+        if self.spot_enabled {
+            let spot = SpotLight {
+                position: glm::vec3(4.0, 6.0, 4.0),
+                direction: glm::vec3(-0.5, -1.0, -0.6),
+                color: glm::vec3(1.0, 0.85, 0.6),
+                intensity: 2.0,
+                inner_angle: 20.0,
+                outer_angle: 30.0,
+                attenuation: Attenuation::from_range(30.0),
+            };
+            self.spot_shadow
+                .set_light_space(spot_light_space(&spot, 0.5, 30.0));
+            self.lights.spot_lights.push(spot);
+        }
+    }
+
+    fn recreate_shadow_maps(&mut self) {
+        let mut cascades =
+            CascadedShadowMap::new(self.cascade_count as usize, self.settings.resolution)
+                .expect("Failed to create the cascaded shadow map");
+        cascades.split_lambda = self.cascades.split_lambda;
+        cascades.max_distance = self.cascades.max_distance;
+        cascades.caster_margin = self.cascades.caster_margin;
+        self.cascades = cascades;
+        self.spot_shadow = ShadowMap::new(self.settings.resolution)
+            .expect("Failed to create the spot light shadow map");
+        self.debug_map = self.debug_map.min(self.cascade_count);
+        self.update_lights();
+    }
+
+    fn render_shadow_map(&self, renderer: &Renderer, shadow_map: &ShadowMap) {
+        shadow_map.begin(renderer);
+        for caster in &self.casters {
+            let material = Material::instance(&self.depth_material)
+                .with_uniform("u_LightSpace", *shadow_map.light_space())
+                .with_uniform("u_Model", caster.model);
+            self.cube.draw(renderer, &material);
+        }
+        shadow_map.end();
+    }
+
+    fn debug_maps(&self) -> Vec<(String, &ShadowMap)> {
+        let mut maps: Vec<_> = self
+            .cascades
+            .cascades()
+            .iter()
+            .enumerate()
+            .map(|(i, cascade)| (format!("Cascade {}", i), cascade))
+            .collect();
+        if self.spot_enabled {
+            maps.push(("Spot Light".into(), &self.spot_shadow));
+        }
+        maps
+    }
+}
+
+impl Testable for TestShadows {
+    fn render(&self, (width, height): (f32, f32), renderer: &Renderer) {
+        self.lights.upload(&self.lights_buffer);
+
+        renderer.set_depth_test(true);
+        renderer.set_face_culling(Some(CullFace::Back));
+
+        for cascade in self.cascades.cascades() {
+            self.render_shadow_map(renderer, cascade);
+        }
+        if self.spot_enabled {
+            self.render_shadow_map(renderer, &self.spot_shadow);
+        }
+        renderer.set_viewport(0, 0, width as i32, height as i32);
+
+        let camera = self.camera_controller.camera();
+        let mut frame = Material::instance(&self.lit_material)
+            .with_uniform("u_View", *camera.view())
+            .with_uniform("u_ViewProjection", *camera.view_projection())
+            .with_uniform("u_ViewPosition", camera.position())
+            .with_uniform("u_ShowCascades", self.show_cascades as i32);
+        frame = self.settings.apply(frame);
+        frame = self.cascades.apply(frame);
+        if self.spot_enabled {
+            frame = self.spot_shadow.apply_spot(frame);
+        }
+        let frame = Rc::new(frame);
+
+        let plane = Material::instance(&frame)
+            .with_uniform("u_Model", glm::Mat4::identity())
+            .with_uniform("u_NormalMatrix", glm::Mat3::identity())
+            .with_uniform("u_DiffuseColor", glm::vec4(0.7, 0.7, 0.7, 1.0));
+        self.plane.draw(renderer, &plane);
+
+        for caster in &self.casters {
+            let material = Material::instance(&frame)
+                .with_uniform("u_Model", caster.model)
+                .with_uniform("u_NormalMatrix", normal_matrix(&caster.model))
+                .with_uniform("u_DiffuseColor", caster.color);
+            self.cube.draw(renderer, &material);
+        }
+
+        frame.shader().unbind();
+        renderer.set_depth_test(false);
+        renderer.set_face_culling(None);
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        let mut lights_changed = false;
+
+        lights_changed |= ui
+            .slider_float(
+                &str_to_imstr("Sun Azimuth"),
+                &mut self.sun_azimuth,
+                0.0,
+                360.0,
+            )
+            .build();
+        lights_changed |= ui
+            .slider_float(
+                &str_to_imstr("Sun Elevation"),
+                &mut self.sun_elevation,
+                5.0,
+                90.0,
+            )
+            .build();
+        ui.checkbox(&str_to_imstr("Animate Sun"), &mut self.animate_sun);
+        if let Some(sun) = self.lights.directional.as_mut() {
+            ui.slider_float(&str_to_imstr("Sun Intensity"), &mut sun.intensity, 0.0, 3.0)
+                .build();
+        }
+        let mut sun_enabled = self.lights.directional.is_some();
+        if ui.checkbox(&str_to_imstr("Sun Enabled"), &mut sun_enabled) {
+            self.lights.directional = sun_enabled.then(DirectionalLight::default);
+            lights_changed = true;
+        }
+        lights_changed |= ui.checkbox(&str_to_imstr("Spot Light"), &mut self.spot_enabled);
+
+        ui.separator();
+        let labels: Vec<ImString> = RESOLUTIONS
+            .iter()
+            .map(|resolution| str_to_imstr(format!("{0}x{0}", resolution)))
+            .collect();
+        let labels: Vec<_> = labels.iter().map(|label| label.as_ref()).collect();
+        let mut resolution = RESOLUTIONS
+            .iter()
+            .position(|&resolution| resolution == self.settings.resolution)
+            .unwrap_or(0) as i32;
+        let mut recreate = false;
+        if ui.combo(&str_to_imstr("Resolution"), &mut resolution, &labels, 4) {
+            self.settings.resolution = RESOLUTIONS[resolution as usize];
+            recreate = true;
+        }
+        if ui
+            .slider_int(
+                &str_to_imstr("Cascades"),
+                &mut self.cascade_count,
+                1,
+                MAX_CASCADES as i32,
+            )
+            .build()
+        {
+            recreate = true;
+        }
+        if recreate {
+            self.recreate_shadow_maps();
+        }
+
+        lights_changed |= ui
+            .slider_float(
+                &str_to_imstr("Split Lambda"),
+                &mut self.cascades.split_lambda,
+                0.0,
+                1.0,
+            )
+            .build();
+        lights_changed |= ui
+            .slider_float(
+                &str_to_imstr("Shadow Distance"),
+                &mut self.cascades.max_distance,
+                5.0,
+                100.0,
+            )
+            .build();
+        ui.slider_float(&str_to_imstr("Bias"), &mut self.settings.bias, 0.0, 0.02)
+            .display_format(&str_to_imstr("%.4f"))
+            .build();
+        ui.slider_float(
+            &str_to_imstr("Normal Bias"),
+            &mut self.settings.normal_bias,
+            0.0,
+            0.2,
+        )
+        .build();
+        ui.slider_int(
+            &str_to_imstr("PCF Radius"),
+            &mut self.settings.pcf_radius,
+            0,
+            3,
+        )
+        .build();
+        ui.checkbox(&str_to_imstr("Show Cascades"), &mut self.show_cascades);
+
+        if lights_changed {
+            self.update_lights();
+        }
+
+        if ui.collapsing_header(&str_to_imstr("Shadow Map")).build() {
+            let maps = self.debug_maps();
+            let labels: Vec<ImString> = maps
+                .iter()
+                .map(|(name, _)| str_to_imstr(name.as_str()))
+                .collect();
+            let labels: Vec<_> = labels.iter().map(|label| label.as_ref()).collect();
+            let mut selected = self.debug_map.min(maps.len() as i32 - 1);
+            ui.combo(
+                &str_to_imstr("Map"),
+                &mut selected,
+                &labels,
+                labels.len() as i32,
+            );
+
+            let (_, shadow_map) = maps[selected as usize];
+            ui.image(
+                TextureId::from(shadow_map.texture().renderer_id() as usize),
+                [256.0, 256.0],
+            )
+            .uv0([0.0, 1.0])
+            .uv1([1.0, 0.0])
+            .build();
+            if let Some(split) = self.cascades.splits().get(selected as usize) {
+                ui.text(format!("Covers up to {:.2} units from the camera", split));
+            }
+            self.debug_map = selected;
+        }
+
+        ui.text("Hold the right mouse button to look around, WASD to move");
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.camera_controller.update(delta_time, input);
+        if self.animate_sun {
+            self.sun_azimuth = (self.sun_azimuth + 10.0 * delta_time) % 360.0;
+        }
+        self.update_lights();
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+        self.update_lights();
+    }
+}
+
+impl TestableID for TestShadows {
+    fn test_id() -> String {
+        "shadows".into()
+    }
+
+    fn test_name() -> String {
+        "Shadow Mapping".into()
+    }
+}
@@ -0,0 +1,235 @@
+use std::f32::consts::{PI, TAU};
+
+use imgui_glfw_rs::imgui::Ui;
+use nalgebra_glm as glm;
+
+use crate::{
+    camera::Camera,
+    camera_controller::OrthographicCameraController,
+    input::Input,
+    renderer::Renderer,
+    shapes::{ShapeRenderer, ShapeStyle},
+    str_to_imstr,
+    tessellation::{LineCap, LineJoin, StrokeStyle},
+};
+
+use super::{Testable, TestableID};
+
+const CAPS: [(LineCap, &str); 3] = [
+    (LineCap::Butt, "Butt"),
+    (LineCap::Square, "Square"),
+    (LineCap::Round, "Round"),
+];
+const JOINS: [(LineJoin, &str); 3] = [
+    (LineJoin::Miter, "Miter"),
+    (LineJoin::Bevel, "Bevel"),
+    (LineJoin::Round, "Round"),
+];
+
+fn star(center: glm::Vec2, outer: f32, inner: f32, points: usize, rotation: f32) -> Vec<glm::Vec2> {
+    (0..points * 2)
+        .map(|i| {
+            let radius = if i % 2 == 0 { outer } else { inner };
+            let angle = rotation + i as f32 * PI / points as f32;
+            center + glm::vec2(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+pub struct TestShapes {
+    camera_controller: OrthographicCameraController,
+    shapes: ShapeRenderer,
+
+    width: f32,
+    cap: LineCap,
+    join: LineJoin,
+    filled: bool,
+    tolerance: f32,
+    time: f32,
+    triangles: usize,
+}
+
+impl Default for TestShapes {
+    fn default() -> Self {
+        Self {
+            camera_controller: OrthographicCameraController::new((1280.0, 960.0)),
+            shapes: ShapeRenderer::default(),
+            width: 12.0,
+            cap: LineCap::Round,
+            join: LineJoin::Miter,
+            filled: true,
+            tolerance: 0.25,
+            time: 0.0,
+            triangles: 0,
+        }
+    }
+}
+
+impl TestShapes {
+    fn style(&self) -> ShapeStyle {
+        if self.filled {
+            ShapeStyle::Fill
+        } else {
+            self.stroke().into()
+        }
+    }
+
+    fn stroke(&self) -> StrokeStyle {
+        StrokeStyle::new(self.width)
+            .with_cap(self.cap)
+            .with_join(self.join)
+    }
+}
+
+impl Testable for TestShapes {
+    fn render(&self, _: (f32, f32), _: &Renderer) {
+        self.shapes
+            .draw(self.camera_controller.camera().view_projection());
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        ui.slider_float(&str_to_imstr("Line Width"), &mut self.width, 1.0, 40.0)
+            .build();
+        for (i, (cap, name)) in CAPS.into_iter().enumerate() {
+            if i > 0 {
+                ui.same_line(0.0);
+            }
+            if ui.radio_button_bool(&str_to_imstr(format!("{} Cap", name)), self.cap == cap) {
+                self.cap = cap;
+            }
+        }
+        for (i, (join, name)) in JOINS.into_iter().enumerate() {
+            if i > 0 {
+                ui.same_line(0.0);
+            }
+            if ui.radio_button_bool(&str_to_imstr(format!("{} Join", name)), self.join == join) {
+                self.join = join;
+            }
+        }
+        ui.checkbox(&str_to_imstr("Filled"), &mut self.filled);
+        ui.slider_float(&str_to_imstr("Tolerance"), &mut self.tolerance, 0.05, 8.0)
+            .build();
+        ui.text(format!("{} triangles in one draw call", self.triangles));
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.time += delta_time;
+        self.camera_controller.update(delta_time, input);
+        self.shapes.set_tolerance(self.tolerance);
+
+        let stroke = self.stroke();
+        let style = self.style();
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        let red = glm::vec4(0.91, 0.3, 0.24, 1.0);
+        let green = glm::vec4(0.18, 0.8, 0.44, 1.0);
+        let blue = glm::vec4(0.2, 0.6, 0.86, 1.0);
+        let yellow = glm::vec4(0.95, 0.77, 0.06, 1.0);
+
+        // Lines and a zig zag polyline showing caps and joins
+        for i in 0..4 {
+            let y = 880.0 - i as f32 * 40.0;
+            self.shapes.line(
+                glm::vec2(60.0, y),
+                glm::vec2(360.0, y - 20.0),
+                &stroke,
+                white,
+            );
+        }
+        let zig_zag: Vec<_> = (0..7)
+            .map(|i| glm::vec2(440.0 + i as f32 * 60.0, 740.0 + (i % 2) as f32 * 140.0))
+            .collect();
+        self.shapes.polyline(&zig_zag, false, &stroke, yellow);
+        let wave: Vec<_> = (0..=64)
+            .map(|i| {
+                let x = i as f32 / 64.0;
+                glm::vec2(
+                    880.0 + x * 340.0,
+                    810.0 + (x * TAU * 2.0 + self.time).sin() * 60.0,
+                )
+            })
+            .collect();
+        self.shapes.polyline(&wave, false, &stroke, blue);
+
+        // Round shapes
+        self.shapes
+            .circle(glm::vec2(140.0, 560.0), 80.0, style, red);
+        self.shapes.ellipse(
+            glm::vec2(360.0, 560.0),
+            glm::vec2(110.0, 60.0),
+            style,
+            green,
+        );
+        let sweep = (self.time.sin() * 0.5 + 0.5) * TAU;
+        self.shapes
+            .arc(glm::vec2(600.0, 560.0), 80.0, 0.0, sweep, style, blue);
+        self.shapes.arc(
+            glm::vec2(820.0, 560.0),
+            80.0,
+            PI * 0.25,
+            PI * 1.75,
+            stroke,
+            yellow,
+        );
+        self.shapes.rounded_rect(
+            glm::vec2(940.0, 490.0),
+            glm::vec2(1220.0, 630.0),
+            30.0,
+            style,
+            white,
+        );
+
+        // Triangles and polygons, the star and comb are concave
+        self.shapes.triangle(
+            glm::vec2(60.0, 180.0),
+            glm::vec2(260.0, 180.0),
+            glm::vec2(160.0, 360.0),
+            style,
+            green,
+        );
+        let star = star(glm::vec2(420.0, 270.0), 110.0, 45.0, 5, self.time * 0.5);
+        self.shapes.polygon(&star, style, yellow);
+        let comb: Vec<_> = [
+            (0.0, 0.0),
+            (5.0, 0.0),
+            (5.0, 5.0),
+            (4.0, 1.5),
+            (3.0, 5.0),
+            (2.0, 1.5),
+            (1.0, 5.0),
+            (0.0, 5.0),
+        ]
+        .into_iter()
+        .map(|(x, y)| glm::vec2(620.0 + x * 40.0, 170.0 + y * 40.0))
+        .collect();
+        self.shapes.polygon(&comb, style, red);
+        self.shapes.rect(
+            glm::vec2(880.0, 180.0),
+            glm::vec2(1040.0, 360.0),
+            style,
+            blue,
+        );
+        self.shapes.rect(
+            glm::vec2(960.0, 240.0),
+            glm::vec2(1220.0, 300.0),
+            style,
+            glm::vec4(1.0, 1.0, 1.0, 0.5),
+        );
+
+        self.triangles = self.shapes.triangles();
+        self.shapes.prepare();
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
+}
+
+impl TestableID for TestShapes {
+    fn test_id() -> String {
+        "shapes".into()
+    }
+
+    fn test_name() -> String {
+        "Shapes".into()
+    }
+}
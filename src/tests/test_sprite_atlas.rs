@@ -0,0 +1,361 @@
+use std::{ffi::c_void, rc::Rc};
+
+use glcall_macro::gl_call;
+use image::{Rgba, RgbaImage};
+use imgui_glfw_rs::imgui::Ui;
+use memoffset::offset_of;
+
+use crate::{
+    atlas::{AtlasRegion, TextureAtlas, TextureAtlasBuilder},
+    camera::Camera,
+    camera_controller::OrthographicCameraController,
+    gl_component::GLComponent,
+    index_buffer::IndexBuffer,
+    input::Input,
+    renderer::Renderer,
+    sampler::TextureSpec,
+    shader::Shader,
+    sprite_sheet::SpriteSheet,
+    str_to_imstr,
+    texture::{Texture, TextureObject, TextureOptions},
+    ShaderBuilder,
+};
+
+use super::{test_batch_rendering::gen_quad_indices, Testable, TestableID};
+
+const MAX_SPRITES: usize = 1000;
+const SPRITE_SIZE: f32 = 32.0;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SpriteVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+    texture_coords: [f32; 2],
+}
+
+fn push_quad(
+    vertices: &mut Vec<SpriteVertex>,
+    (x, y): (f32, f32),
+    (width, height): (f32, f32),
+    color: [f32; 4],
+    region: &AtlasRegion,
+) {
+    let corners = [
+        (x, y),
+        (x + width, y),
+        (x + width, y + height),
+        (x, y + height),
+    ];
+    for ((x, y), texture_coords) in corners.into_iter().zip(region.uvs()) {
+        vertices.push(SpriteVertex {
+            position: [x, y, 0.0],
+            color,
+            texture_coords,
+        });
+    }
+}
+
+/// A few procedural images so the atlas holds more than the bundled textures
+fn generated_images() -> Vec<(String, RgbaImage)> {
+    let colors = [
+        [231, 76, 60],
+        [46, 204, 113],
+        [52, 152, 219],
+        [241, 196, 15],
+    ];
+    let mut images: Vec<_> = colors
+        .iter()
+        .enumerate()
+        .map(|(i, [r, g, b])| {
+            let size = 12 + 8 * i as u32;
+            let image = RgbaImage::from_fn(size, size, |x, y| {
+                let border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+                let shade = if border { 0.6 } else { 1.0 };
+                let channel = |c: u8| (c as f32 * shade) as u8;
+                Rgba([channel(*r), channel(*g), channel(*b), 255])
+            });
+            (format!("square_{}", i), image)
+        })
+        .collect();
+
+    let checker = RgbaImage::from_fn(24, 24, |x, y| {
+        let value = if (x / 4 + y / 4) % 2 == 0 { 255 } else { 40 };
+        Rgba([value, value, value, 255])
+    });
+    images.push(("checker".into(), checker));
+    images
+}
+
+/// Four 16x16 frames of a bar spinning a quarter turn, laid out in a row
+fn spinner_sheet() -> RgbaImage {
+    RgbaImage::from_fn(64, 16, |x, y| {
+        let frame = x / 16;
+        let (dx, dy) = ((x % 16) as f32 - 7.5, y as f32 - 7.5);
+        let angle = frame as f32 * std::f32::consts::FRAC_PI_4;
+        let along = dx * angle.cos() + dy * angle.sin();
+        let across = -dx * angle.sin() + dy * angle.cos();
+        if along.abs() < 7.0 && across.abs() < 2.0 {
+            Rgba([255, 255, 255, 255])
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    })
+}
+
+pub struct TestSpriteAtlas {
+    vao: u32,
+    vbo: u32,
+    ibo: IndexBuffer,
+    shader: Shader,
+    camera_controller: OrthographicCameraController,
+
+    atlas: TextureAtlas,
+    names: Vec<String>,
+    spinner: SpriteSheet,
+
+    sprite_count: i32,
+    padding: i32,
+    extrusion: i32,
+    time: f32,
+}
+
+impl TestSpriteAtlas {
+    fn build_atlas(padding: usize, extrusion: usize) -> TextureAtlas {
+        let options = TextureOptions::default().with_spec(TextureSpec::pixel_art());
+        let builder = TextureAtlasBuilder::new(512, 512)
+            .with_padding(padding)
+            .with_extrusion(extrusion)
+            .with_options(options)
+            .with_file("phone", "src/res/textures/phone.png")
+            .and_then(|builder| builder.with_file("heart", "src/res/textures/heart.png"))
+            .expect("Failed to load atlas images");
+
+        generated_images()
+            .into_iter()
+            .fold(builder, |builder, (name, image)| {
+                builder.with_image(name, image)
+            })
+            .build()
+            .expect("Failed to build atlas")
+    }
+
+    fn draw_batch(&self, texture: &Texture, vertices: &[SpriteVertex]) {
+        if vertices.is_empty() {
+            return;
+        }
+
+        texture.bind_unit(0);
+        gl_call!({
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                std::mem::size_of_val(vertices) as isize,
+                vertices.as_ptr() as *const c_void,
+            );
+            gl::DrawElements(
+                gl::TRIANGLES,
+                (vertices.len() / 4 * 6) as i32,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+            );
+        });
+    }
+}
+
+impl Default for TestSpriteAtlas {
+    fn default() -> Self {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("../res/shaders/Sprite.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        shader.bind();
+        shader.uniform_1i("u_Texture", 0);
+        shader.unbind();
+
+        let mut vao = 0;
+        let mut vbo = 0;
+        let stride = std::mem::size_of::<SpriteVertex>() as i32;
+        gl_call!({
+            gl::GenVertexArrays(1, &mut vao);
+            gl::BindVertexArray(vao);
+
+            gl::CreateBuffers(1, &mut vbo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (std::mem::size_of::<SpriteVertex>() * 4 * MAX_SPRITES) as isize,
+                std::ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(
+                0,
+                3,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset_of!(SpriteVertex, position) as *const c_void,
+            );
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(
+                1,
+                4,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset_of!(SpriteVertex, color) as *const c_void,
+            );
+            gl::EnableVertexAttribArray(2);
+            gl::VertexAttribPointer(
+                2,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset_of!(SpriteVertex, texture_coords) as *const c_void,
+            );
+        });
+        let ibo = IndexBuffer::new(&gen_quad_indices(MAX_SPRITES as u32));
+        gl_call!({
+            gl::BindVertexArray(0);
+        });
+
+        let atlas = Self::build_atlas(1, 1);
+        let names = atlas.regions().map(|(name, _)| name.clone()).collect();
+
+        let options = TextureOptions::default().with_spec(TextureSpec::pixel_art());
+        let spinner = Texture::from_image(spinner_sheet().into(), &options)
+            .expect("Failed to upload sprite sheet");
+        let spinner = SpriteSheet::from_grid(Rc::new(spinner), 16, 16, 0);
+
+        Self {
+            vao,
+            vbo,
+            ibo,
+            shader,
+            camera_controller: OrthographicCameraController::new((1280.0, 960.0)),
+            atlas,
+            names,
+            spinner,
+            sprite_count: 400,
+            padding: 1,
+            extrusion: 1,
+            time: 0.0,
+        }
+    }
+}
+
+impl Drop for TestSpriteAtlas {
+    fn drop(&mut self) {
+        gl_call!({
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        });
+    }
+}
+
+impl Testable for TestSpriteAtlas {
+    fn render(&self, _: (f32, f32), _: &Renderer) {
+        self.shader.bind();
+        self.shader.uniform_mat4(
+            "u_ViewProjection",
+            self.camera_controller.camera().view_projection(),
+        );
+        gl_call!({
+            gl::BindVertexArray(self.vao);
+        });
+        self.ibo.bind();
+
+        // Sprites are grouped by page, each page is one draw call
+        let mut batches = vec![Vec::new(); self.atlas.pages().len()];
+        let columns = 40;
+        for i in 0..self.sprite_count as usize {
+            let region = self
+                .atlas
+                .region(&self.names[i % self.names.len()])
+                .unwrap();
+            let (column, row) = ((i % columns) as f32, (i / columns) as f32);
+            let bob = (self.time * 2.0 + i as f32 * 0.3).sin() * 4.0;
+            let position = (20.0 + column * 30.0, 60.0 + row * 36.0 + bob);
+            push_quad(
+                &mut batches[region.page],
+                position,
+                (SPRITE_SIZE * 0.8, SPRITE_SIZE * 0.8),
+                [1.0, 1.0, 1.0, 1.0],
+                region,
+            );
+        }
+        for (page, vertices) in self.atlas.pages().iter().zip(&batches) {
+            self.draw_batch(page, vertices);
+        }
+
+        let mut spinners = Vec::new();
+        let frame = self.spinner.frame_at("default", self.time).unwrap();
+        for i in 0..8 {
+            let position = (20.0 + i as f32 * 48.0, 10.0);
+            let color = [1.0, 0.5 + i as f32 / 16.0, 0.2, 1.0];
+            push_quad(&mut spinners, position, (40.0, 40.0), color, &frame.region);
+        }
+        self.draw_batch(self.spinner.texture(), &spinners);
+
+        gl_call!({
+            gl::BindVertexArray(0);
+        });
+        self.shader.unbind();
+    }
+
+    fn imgui_render(&mut self, _: (f32, f32), ui: &Ui) {
+        ui.slider_int(
+            &str_to_imstr("Sprites"),
+            &mut self.sprite_count,
+            0,
+            MAX_SPRITES as i32,
+        )
+        .build();
+
+        let mut changed = ui
+            .slider_int(&str_to_imstr("Padding"), &mut self.padding, 0, 8)
+            .build();
+        changed |= ui
+            .slider_int(&str_to_imstr("Extrusion"), &mut self.extrusion, 0, 8)
+            .build();
+        if changed {
+            self.atlas = Self::build_atlas(self.padding as usize, self.extrusion as usize);
+        }
+
+        ui.text(format!(
+            "{} images on {} page(s), {} draw call(s) for the sprites",
+            self.names.len(),
+            self.atlas.pages().len(),
+            self.atlas.pages().len()
+        ));
+        for (name, region) in self.atlas.regions() {
+            ui.text(format!(
+                "{}: {}x{} at ({}, {}) on page {}",
+                name, region.width, region.height, region.x, region.y, region.page
+            ));
+        }
+    }
+
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        self.time += delta_time;
+        self.camera_controller.update(delta_time, input);
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera_controller.resize(screen_size);
+    }
+}
+
+impl TestableID for TestSpriteAtlas {
+    fn test_id() -> String {
+        "sprite_atlas".into()
+    }
+
+    fn test_name() -> String {
+        "Sprite Atlas".into()
+    }
+}
@@ -1,256 +1,230 @@
+use imgui_glfw_rs::imgui::Ui;
+use nalgebra_glm as glm;
+
 use crate::{
-    gl_component::GLComponent, index_buffer::IndexBuffer, shader::Shader,
-    vertex_array::VertexArray, vertex_buffer::VertexBuffer,
-    vertex_buffer_layout::VertexBufferLayout, ShaderBuilder,
+    camera::{Camera, OrthographicCamera},
+    font::FontLibrary,
+    input::Input,
+    renderer::Renderer,
+    str_to_imstr,
+    text::TextRenderer,
+    text_layout::{HorizontalAlign, LayoutOptions, TextLayout, TextSpan, VerticalAlign},
 };
 
 use super::{Testable, TestableID};
-use glcall_macro::gl_call;
-use image::{EncodableLayout, Rgba, RgbaImage};
-use memoffset::offset_of;
-use nalgebra_glm as glm;
-use rusttype::{point, Font, Scale, VMetrics};
-use std::{ffi::c_void, collections::HashMap};
-
-#[repr(C)]
-#[derive(Clone)]
-pub struct Vertex {
-    pub position: [f32; 3],
-    pub texture_coords: [f32; 2],
-    pub texture_index: f32,
-
-    renderer_id: u32,
-}
-
-impl Vertex {
-    pub fn new(position: [f32; 3], texture_coords: [f32; 2], texture_index: f32) -> Self {
-        Self {
-            position,
-            texture_coords,
-            texture_index,
-
-            renderer_id: 0,
-        }
-    }
-}
 
-pub fn gen_quad_vertices(x: f32, y: f32, width: f32, height: f32, texture_index: f32) -> Vec<Vertex> {
-    let size = 300.0;
+const PARAGRAPH: &str = "The quick brown fox jumps over the lazy dog.\n\
+    Kerning pairs: AV To Wa Yo LT\n\
+    Glyphs are rasterized once and batched into one draw call.";
+
+const WRAPPED: &str = "Text can be wrapped to a maximum width, aligned left, centered, right \
+    aligned or justified, and cut off with an ellipsis once it runs out of lines. \
+    Spans can mix sizes and colors within the same paragraph.";
+
+const FALLBACK: &str = "Fallback: Grüße, Καλημέρα, Привет, こんにちは, 你好, ★ ♥ ✓";
+
+/// Common system fonts for characters the bundled font is missing, whichever exist are used
+const FALLBACK_FONTS: [(&str, &str); 6] = [
+    ("dejavu", "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf"),
+    (
+        "noto_cjk",
+        "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    ),
+    (
+        "droid",
+        "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    ),
+    ("segoe_symbol", "C:/Windows/Fonts/seguisym.ttf"),
+    ("yahei", "C:/Windows/Fonts/msyh.ttc"),
+    ("arial_unicode", "/Library/Fonts/Arial Unicode.ttf"),
+];
+
+const ALIGNMENTS: [(HorizontalAlign, &str); 4] = [
+    (HorizontalAlign::Left, "Left"),
+    (HorizontalAlign::Center, "Center"),
+    (HorizontalAlign::Right, "Right"),
+    (HorizontalAlign::Justify, "Justify"),
+];
+const VERTICAL_ALIGNMENTS: [(VerticalAlign, &str); 3] = [
+    (VerticalAlign::Top, "Top"),
+    (VerticalAlign::Middle, "Middle"),
+    (VerticalAlign::Bottom, "Bottom"),
+];
 
-    #[rustfmt::skip]
-    let vertices = vec![
-        Vertex::new([x,         x,          0.0], [0.0, 1.0], texture_index),
-        Vertex::new([x + width, x,          0.0], [1.0, 1.0], texture_index),
-        Vertex::new([x + width, x + height, 0.0], [1.0, 0.0], texture_index),
-        Vertex::new([x,         x + height, 0.0], [0.0, 0.0], texture_index),
-    ];
-
-    vertices
-}
-
-struct TextRenderer { 
-    font: Font<'static>,
-    scale: Scale,
-    v_metrics: VMetrics,    
+pub struct TestTextRendering {
+    camera: OrthographicCamera,
+    text_renderer: TextRenderer,
 
-    texture_ids: Vec<u32>,
+    test_number: usize,
+    size: f32,
+    color: [f32; 4],
+
+    align: HorizontalAlign,
+    vertical_align: VerticalAlign,
+    box_width: f32,
+    max_lines: i32,
+    ellipsis: bool,
 }
 
-impl Default for TextRenderer {
+impl Default for TestTextRendering {
     fn default() -> Self {
-        let font_data = include_bytes!("../res/fonts/Default.ttf").to_vec();
-        let font = Font::try_from_vec(font_data).unwrap();
-
-        let height = 12.4;
-        let scale = Scale::uniform(42.0);
-        let v_metrics = font.v_metrics(scale);
-
         Self {
-            font, scale, v_metrics, texture_ids: vec![],
+            camera: OrthographicCamera::from_screen((1280.0, 960.0)),
+            text_renderer: TextRenderer::new(Self::load_fonts()),
+            test_number: 0,
+            size: 42.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            align: HorizontalAlign::Left,
+            vertical_align: VerticalAlign::Top,
+            box_width: 500.0,
+            max_lines: 0,
+            ellipsis: true,
         }
     }
 }
 
-impl TextRenderer {
-    pub fn generate_texture(&mut self, s: impl AsRef<str>) -> image::ImageBuffer<Rgba<u8>, Vec<u8>> {
-        let offset = point(0.0, self.v_metrics.ascent);
-        let glyphs = self.font.layout(s.as_ref(), self.scale, offset);
-        let mut image = RgbaImage::new(1280, 960);
-
-        for g in glyphs {
-            if let Some(bounding_box) = g.pixel_bounding_box() {
-                g.draw(|x, y, v| {
-                    image.put_pixel(
-                        x + bounding_box.min.x as u32,
-                        y + bounding_box.min.y as u32,
-                        Rgba([255, 255, 255, (v * 255.0) as u8]),
-                    )
-                });
+impl TestTextRendering {
+    fn load_fonts() -> FontLibrary {
+        let mut fonts = FontLibrary::default();
+        for (name, path) in FALLBACK_FONTS {
+            if let Ok(id) = fonts.load(name, path) {
+                fonts.add_fallback(id);
             }
         }
-
-        let mut texture_id = 0;
-        gl_call!({
-            gl::GenTextures(1, &mut texture_id);
-            gl::BindTexture(gl::TEXTURE_2D, texture_id);
-
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA as i32,
-                image.width() as i32,
-                image.height() as i32,
-                0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                image.as_bytes().as_ptr() as *const c_void,
-            );
-        });
-
-        self.texture_ids.push(texture_id);
-
-        image
-    }
-
-    pub fn bind_units(&self) {
-        for (i, id) in self.texture_ids.iter().enumerate() {
-            gl_call!({
-                gl::BindTextureUnit(i as u32, *id);
-            });
-        }
-    }
-
-    pub fn clean_all(&mut self) {
-        gl_call!({
-            gl::DeleteTextures(self.texture_ids.len() as i32, self.texture_ids.as_ptr())
-        });
-
-        self.texture_ids = vec![];
-    }
-
-    pub fn ids(&self) -> Vec<u32> {
-        self.texture_ids.clone()
+        fonts
     }
 }
 
-impl Drop for TextRenderer {
-    fn drop(&mut self) {
-        self.clean_all();
+impl Testable for TestTextRendering {
+    fn render(&self, _screen_size: (f32, f32), _renderer: &Renderer) {
+        self.text_renderer.draw(self.camera.view_projection());
     }
-}
-
-pub struct TestTextRendering {
-    vbo: VertexBuffer,
-    ibo: IndexBuffer,
-    vao: VertexArray,
-
-    shader: Shader,
-
-    text_renderer: TextRenderer,
-    
-    test_text: Option<image::ImageBuffer<Rgba<u8>, Vec<u8>>>,
-    test_number: usize,
-}
-
-impl Default for TestTextRendering {
-    fn default() -> Self {
-        let mut text_renderer = TextRenderer::default();
-
-        let vertices = gen_quad_vertices(0., 0., 1280., 960., 0.);
-        let indices = vec![0, 1, 2, 2, 3, 0];
-
-        let vao = VertexArray::new();
-
-        let vbo = VertexBuffer::new(&vertices);
-        gl_call!({
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(
-                0,
-                3,
-                gl::FLOAT,
-                gl::FALSE,
-                std::mem::size_of::<Vertex>() as i32,
-                offset_of!(Vertex, position) as *const c_void,
-            );
-
-            gl::EnableVertexAttribArray(1);
-            gl::VertexAttribPointer(
-                1,
-                2,
-                gl::FLOAT,
-                gl::FALSE,
-                std::mem::size_of::<Vertex>() as i32,
-                offset_of!(Vertex, texture_coords) as *const c_void,
-            );
-
-            gl::EnableVertexAttribArray(2);
-            gl::VertexAttribPointer(
-                2,
-                1,
-                gl::FLOAT,
-                gl::FALSE,
-                std::mem::size_of::<Vertex>() as i32,
-                offset_of!(Vertex, texture_index) as *const c_void,
-            );
-        });
-
-        let ibo = IndexBuffer::new(&indices);
 
-        let shader = ShaderBuilder::default()
-            .with_shader("src/res/shaders/Text.glsl")
-            .unwrap()
+    fn imgui_render(&mut self, _screen_size: (f32, f32), ui: &Ui) {
+        ui.slider_float(&str_to_imstr("Size"), &mut self.size, 8.0, 128.0)
+            .build();
+        ui.color_edit(&str_to_imstr("Color"), &mut self.color)
             .build();
 
-        shader.bind();
-        shader.uniform_1i("u_Texture", 0);
-        shader.unbind();
-
-        TestTextRendering {
-            vao,
-            ibo,
-            vbo,
-            shader,
-            text_renderer,
-            test_text: None,
-            test_number: 0,
+        ui.separator();
+        ui.text("Wrapped paragraph");
+        for (i, (align, name)) in ALIGNMENTS.into_iter().enumerate() {
+            if i > 0 {
+                ui.same_line(0.0);
+            }
+            if ui.radio_button_bool(&str_to_imstr(name), self.align == align) {
+                self.align = align;
+            }
         }
+        for (i, (align, name)) in VERTICAL_ALIGNMENTS.into_iter().enumerate() {
+            if i > 0 {
+                ui.same_line(0.0);
+            }
+            if ui.radio_button_bool(&str_to_imstr(name), self.vertical_align == align) {
+                self.vertical_align = align;
+            }
+        }
+        ui.slider_float(
+            &str_to_imstr("Box Width"),
+            &mut self.box_width,
+            50.0,
+            1200.0,
+        )
+        .build();
+        ui.slider_int(
+            &str_to_imstr("Max Lines (0 = all)"),
+            &mut self.max_lines,
+            0,
+            10,
+        )
+        .build();
+        ui.checkbox(&str_to_imstr("Ellipsis"), &mut self.ellipsis);
+
+        let fonts = self.text_renderer.fonts();
+        let fallbacks: Vec<_> = fonts.fallbacks().iter().map(|&id| fonts.name(id)).collect();
+        ui.text(format!(
+            "Fonts: {}, fallbacks: {}",
+            fonts.ids().count(),
+            if fallbacks.is_empty() {
+                "none found".to_string()
+            } else {
+                fallbacks.join(", ")
+            }
+        ));
+
+        let cache = self.text_renderer.cache();
+        ui.text(format!(
+            "Glyph cache: {} glyphs in {}x{}",
+            cache.len(),
+            cache.texture().width(),
+            cache.texture().height()
+        ));
     }
-}
-
-impl Testable for TestTextRendering {
-    fn render(&self, screen_size: (f32, f32), _renderer: &crate::renderer::Renderer) {
-        let (width, height) = screen_size;
-        self.text_renderer.bind_units();
-        self.vao.bind();
-        self.shader.bind();
 
-        let proj = glm::ortho(0.0, width, 0.0, height, -1.0, 1.0);
-        let view = glm::translate(&glm::Mat4::identity(), &glm::vec3(0.0, 0.0, 0.0));
-        let model = glm::translate(&glm::Mat4::identity(), &glm::vec3(0.0, 0.0, 0.0));
-
-        let mvp = proj * view * model;
+    fn update(&mut self, _delta_time: f32, _input: &Input) {
+        let color = glm::Vec4::from(self.color);
+        let line_height = self.text_renderer.line_height(self.size);
+        let mut y = 940.0;
+
+        let counters = [
+            format!("Number: {}", self.test_number),
+            format!("Number 2: {}", self.test_number * 2),
+        ];
+        for counter in counters {
+            self.text_renderer
+                .queue(&counter, glm::vec2(20.0, y), self.size, color);
+            y -= line_height;
+        }
 
-        self.shader.uniform_mat4("u_MVP", &mvp);
-        self.shader.uniform_1iv("u_Textures", vec![0,1]);
+        let paragraph_size = (self.size * 0.5).max(8.0);
+        self.text_renderer.queue(
+            PARAGRAPH,
+            glm::vec2(20.0, y - line_height * 0.5),
+            paragraph_size,
+            glm::vec4(0.9, 0.8, 0.4, 1.0),
+        );
+
+        self.text_renderer.queue(
+            FALLBACK,
+            glm::vec2(20.0, y - line_height * 0.5 - paragraph_size * 4.5),
+            paragraph_size,
+            glm::vec4(0.8, 0.6, 1.0, 1.0),
+        );
+
+        let label = "Right aligned";
+        let width = self.text_renderer.measure(label, self.size).x;
+        self.text_renderer.queue(
+            label,
+            glm::vec2(1260.0 - width, 60.0),
+            self.size,
+            glm::vec4(0.4, 0.8, 1.0, 1.0),
+        );
+
+        let white = glm::vec4(1.0, 1.0, 1.0, 1.0);
+        let spans = [
+            TextSpan::new("Layout ", 32.0, glm::vec4(1.0, 0.5, 0.3, 1.0)),
+            TextSpan::new(WRAPPED, 20.0, white),
+            TextSpan::new(" The end.", 26.0, glm::vec4(0.5, 1.0, 0.5, 1.0)),
+        ];
+        let mut options = LayoutOptions::default()
+            .with_max_width(self.box_width)
+            .with_height(240.0)
+            .with_align(self.align)
+            .with_vertical_align(self.vertical_align)
+            .with_ellipsis(self.ellipsis);
+        if self.max_lines > 0 {
+            options = options.with_max_lines(self.max_lines as usize);
+        }
+        let layout = TextLayout::new(self.text_renderer.fonts(), &spans, &options);
+        self.text_renderer
+            .queue_layout(&layout, glm::vec2(20.0, 460.0));
 
-        gl_call!({
-            gl::DrawElements(gl::TRIANGLES, 12, gl::UNSIGNED_INT, std::ptr::null());
-        });
+        self.text_renderer.prepare();
+        self.test_number += 1;
     }
-    fn imgui_render(&mut self, _screen_size: (f32, f32), _ui: &imgui_glfw_rs::imgui::Ui) {}
-    fn update(&mut self, _delta_time: f32) {
-        self.text_renderer.clean_all();
 
-        let image = self.text_renderer.generate_texture(format!("Number: {}", self.test_number));
-        let image2 = self.text_renderer.generate_texture(format!("Number 2: {}", self.test_number * 2));
-        self.test_text = Some(image);
-
-        self.test_number += 1;
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera.resize(screen_size);
     }
 }
 
@@ -1,10 +1,22 @@
 use crate::{
-    gl_component::GLComponent, index_buffer::IndexBuffer, renderer::Renderer, shader::Shader,
-    str_to_imstr, texture::Texture, vertex_array::VertexArray, vertex_buffer::VertexBuffer,
-    vertex_buffer_layout::VertexBufferLayout, ShaderBuilder,
+    camera::{Camera, OrthographicCamera},
+    gl_component::GLComponent,
+    index_buffer::IndexBuffer,
+    input::{ActionMap, Binding, Input},
+    material::Material,
+    renderer::Renderer,
+    str_to_imstr,
+    texture::Texture,
+    vertex_array::VertexArray,
+    vertex_buffer::VertexBuffer,
+    vertex_buffer_layout::VertexBufferLayout,
+    ShaderBuilder,
 };
+use std::rc::Rc;
+
 use gl::types::GLuint;
 use glcall_macro::gl_call;
+use imgui_glfw_rs::glfw::{GamepadAxis, GamepadButton, Key};
 use nalgebra_glm as glm;
 
 use super::{Testable, TestableID};
@@ -12,10 +24,12 @@ use super::{Testable, TestableID};
 pub struct TestTexture {
     vao: VertexArray,
     ibo: IndexBuffer,
-    shader: Shader,
-    texture: Texture,
-    view: glm::Mat4,
+    material: Rc<Material>,
+    camera: OrthographicCamera,
     model: glm::Vec3,
+
+    actions: ActionMap,
+    speed: f32,
 }
 
 impl Default for TestTexture {
@@ -52,45 +66,52 @@ impl Default for TestTexture {
         ibo.unbind();
 
         let texture = Texture::new("src/res/textures/phone.png", 0);
-        texture.bind(0);
-        shader.bind();
-        shader.uniform_1i("u_Texture", 0);
-        shader.unbind();
+        let material = Material::new(shader).with_texture("u_Texture", Rc::new(texture));
 
-        let view = glm::translate(&glm::Mat4::identity(), &glm::vec3(0., 0., 0.));
+        let camera = OrthographicCamera::from_screen((1280.0, 960.0));
         let model = glm::vec3(100., 100., 0.);
 
+        let actions = ActionMap::default()
+            .with_binding("left", Binding::Key(Key::A))
+            .with_binding("left", Binding::Key(Key::Left))
+            .with_binding("left", Binding::GamepadAxis(GamepadAxis::AxisLeftX, -0.5))
+            .with_binding("right", Binding::Key(Key::D))
+            .with_binding("right", Binding::Key(Key::Right))
+            .with_binding("right", Binding::GamepadAxis(GamepadAxis::AxisLeftX, 0.5))
+            .with_binding("down", Binding::Key(Key::S))
+            .with_binding("down", Binding::Key(Key::Down))
+            .with_binding("down", Binding::GamepadAxis(GamepadAxis::AxisLeftY, 0.5))
+            .with_binding("up", Binding::Key(Key::W))
+            .with_binding("up", Binding::Key(Key::Up))
+            .with_binding("up", Binding::GamepadAxis(GamepadAxis::AxisLeftY, -0.5))
+            .with_binding("reset", Binding::Key(Key::R))
+            .with_binding("reset", Binding::GamepadButton(GamepadButton::ButtonA));
+
         Self {
             vao,
             ibo,
-            shader,
-            view,
+            material: Rc::new(material),
+            camera,
             model,
-            texture,
+            actions,
+            speed: 300.0,
         }
     }
 }
 
 impl Testable for TestTexture {
-    fn render(&self, screen_size: (f32, f32), renderer: &Renderer) {
-        let (width, height) = screen_size;
-        let proj = glm::ortho(0.0, width, 0.0, height, -1.0, 1.0);
+    fn render(&self, _: (f32, f32), renderer: &Renderer) {
         let model = glm::translate(&glm::Mat4::identity(), &self.model);
-        let mvp = proj * self.view * model;
+        let mvp = self.camera.view_projection() * model;
 
         gl_call!({
             gl::ClearColor(0.2, 0.3, 0.8, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT);
         });
 
-        self.shader.bind();
-        self.texture.bind(0);
-        self.shader.uniform_mat4("u_MVP", &mvp);
-
-        self.vao.bind();
-
-        renderer.draw(&self.vao, &self.ibo, &self.shader);
-        self.shader.unbind();
+        let material = Material::instance(&self.material).with_uniform("u_MVP", mvp);
+        renderer.draw(&self.vao, &self.ibo, &material);
+        material.shader().unbind();
     }
 
     fn imgui_render(&mut self, (width, height): (f32, f32), ui: &imgui_glfw_rs::imgui::Ui) {
@@ -101,9 +122,27 @@ impl Testable for TestTexture {
             width.max(height),
         )
         .build();
+        ui.slider_float(&str_to_imstr("Speed"), &mut self.speed, 0.0, 1000.0)
+            .build();
+        ui.text("Move with WASD, the arrow keys or a gamepad, R resets");
     }
 
-    fn update(&mut self, _delta_time: f32) {}
+    fn update(&mut self, delta_time: f32, input: &Input) {
+        if self.actions.is_pressed(input, "reset") {
+            self.model = glm::vec3(100., 100., 0.);
+        }
+
+        let direction = glm::vec3(
+            self.actions.axis(input, "left", "right"),
+            self.actions.axis(input, "down", "up"),
+            0.0,
+        );
+        self.model += direction * self.speed * delta_time;
+    }
+
+    fn resize(&mut self, screen_size: (f32, f32)) {
+        self.camera.resize(screen_size);
+    }
 }
 
 impl TestableID for TestTexture {
@@ -0,0 +1,523 @@
+use std::{collections::HashMap, ffi::c_void};
+
+use glcall_macro::gl_call;
+use memoffset::offset_of;
+use nalgebra_glm as glm;
+use rusttype::{point, GlyphId, PositionedGlyph, Scale, VMetrics};
+
+use crate::{
+    atlas::{AtlasRegion, SkylinePacker},
+    font::{FontId, FontLibrary},
+    gl_component::GLComponent,
+    index_buffer::IndexBuffer,
+    shader::Shader,
+    text_layout::{LayoutOptions, TextLayout, TextSpan},
+    texture::{Texture, TextureError, TextureFormat, TextureObject},
+    ShaderBuilder,
+};
+
+/// Horizontal subpixel positions each glyph is rasterized at, vertical positions snap to pixels
+const SUBPIXEL_STEPS: u32 = 4;
+const MAX_CACHE_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: FontId,
+    glyph: GlyphId,
+    scale: (u32, u32),
+    subpixel: u32,
+}
+
+/// Where a rasterized glyph is stored in the cache and where it sits relative to its origin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CachedGlyph {
+    pub region: AtlasRegion,
+    /// Offset of the bitmap's top left corner from the glyph origin on the baseline, y down
+    pub offset: (i32, i32),
+}
+
+///
+/// Glyph bitmaps rasterized on first use into a single-channel atlas texture
+///
+/// Glyphs are keyed by font, id, scale and subpixel position so each is rasterized once. When
+/// the atlas runs full it doubles in size up to 4096x4096, after that it is emptied and
+/// only the glyphs still in use are rasterized again.
+///
+pub struct GlyphCache {
+    texture: Texture,
+    packer: SkylinePacker,
+    glyphs: HashMap<GlyphKey, Option<CachedGlyph>>,
+}
+
+impl GlyphCache {
+    pub fn new(width: usize, height: usize) -> Result<Self, TextureError> {
+        let instance = Self {
+            texture: Texture::empty(width, height, TextureFormat::R8)?,
+            packer: SkylinePacker::new(width, height),
+            glyphs: HashMap::new(),
+        };
+        instance.clear_texture();
+        Ok(instance)
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Number of glyphs currently rasterized, including empty ones like spaces
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Forgets every glyph, they are rasterized again when next drawn
+    pub fn clear(&mut self) {
+        self.glyphs.clear();
+        self.packer = SkylinePacker::new(self.texture.width(), self.texture.height());
+        self.clear_texture();
+    }
+
+    fn clear_texture(&self) {
+        let (width, height) = (self.texture.width(), self.texture.height());
+        self.texture
+            .update_region(0, 0, width, height, &vec![0; width * height])
+            .expect("Glyph cache texture has the wrong size");
+    }
+
+    /// Splits a glyph's x position into a whole pixel and the subpixel step it is cached at
+    fn key(font: FontId, glyph: &PositionedGlyph) -> (GlyphKey, i32, i32) {
+        let position = glyph.position();
+        let whole = position.x.floor();
+        let steps = ((position.x - whole) * SUBPIXEL_STEPS as f32).round() as u32;
+        let x = whole as i32 + (steps / SUBPIXEL_STEPS) as i32;
+        let scale = glyph.scale();
+        let key = GlyphKey {
+            font,
+            glyph: glyph.id(),
+            scale: (scale.x.to_bits(), scale.y.to_bits()),
+            subpixel: steps % SUBPIXEL_STEPS,
+        };
+        (key, x, position.y.round() as i32)
+    }
+
+    /// Looks a glyph up without rasterizing it, `Some(None)` for glyphs without pixels
+    fn get(
+        &self,
+        font: FontId,
+        glyph: &PositionedGlyph,
+    ) -> Option<(Option<CachedGlyph>, i32, i32)> {
+        let (key, x, y) = Self::key(font, glyph);
+        self.glyphs.get(&key).map(|cached| (*cached, x, y))
+    }
+
+    /// Rasterizes a glyph unless it is cached, `false` if there is no space left
+    fn insert(&mut self, font: FontId, glyph: &PositionedGlyph) -> bool {
+        let (key, _, _) = Self::key(font, glyph);
+        if self.glyphs.contains_key(&key) {
+            return true;
+        }
+
+        let subpixel = key.subpixel as f32 / SUBPIXEL_STEPS as f32;
+        let glyph = glyph
+            .unpositioned()
+            .clone()
+            .positioned(point(subpixel, 0.0));
+        let Some(bounds) = glyph.pixel_bounding_box() else {
+            self.glyphs.insert(key, None);
+            return true;
+        };
+
+        let (width, height) = (bounds.width() as usize, bounds.height() as usize);
+        // One pixel of padding keeps linear filtering from reaching into neighbours
+        let Some((x, y)) = self.packer.pack(width + 1, height + 1) else {
+            return false;
+        };
+
+        let mut pixels = vec![0; width * height];
+        glyph.draw(|gx, gy, coverage| {
+            pixels[gy as usize * width + gx as usize] = (coverage * 255.0).round() as u8;
+        });
+        self.texture
+            .update_region(x, y, width, height, &pixels)
+            .expect("Glyph does not fit the region it was packed into");
+
+        let size = (self.texture.width(), self.texture.height());
+        let cached = CachedGlyph {
+            region: AtlasRegion::new(0, (x, y, width, height), size, false),
+            offset: (bounds.min.x, bounds.min.y),
+        };
+        self.glyphs.insert(key, Some(cached));
+        true
+    }
+
+    ///
+    /// Makes sure every glyph is rasterized, growing or emptying the cache if needed
+    ///
+    /// Returns `false` if the glyphs don't fit even into an empty cache of the maximum size,
+    /// in which case only some of them are cached.
+    ///
+    fn prepare<'a>(
+        &mut self,
+        glyphs: impl Iterator<Item = (FontId, &'a PositionedGlyph<'a>)> + Clone,
+    ) -> bool {
+        loop {
+            if glyphs.clone().all(|(font, glyph)| self.insert(font, glyph)) {
+                return true;
+            }
+
+            let size = self.texture.width().max(self.texture.height());
+            if size >= MAX_CACHE_SIZE {
+                // Glyphs from earlier frames may be taking up the space
+                self.clear();
+                return glyphs.clone().all(|(font, glyph)| self.insert(font, glyph));
+            }
+
+            let size = (size * 2).min(MAX_CACHE_SIZE);
+            self.texture
+                .resize(size, size)
+                .expect("Failed to grow the glyph cache");
+            self.clear();
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GlyphVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+    pub texture_coords: [f32; 2],
+}
+
+impl GlyphVertex {
+    /// The corners of a quad from its bottom left and top right, in the order of `AtlasRegion::uvs`
+    pub fn quad(
+        (left, bottom): (f32, f32),
+        (right, top): (f32, f32),
+        region: &AtlasRegion,
+        color: glm::Vec4,
+    ) -> [Self; 4] {
+        let corners = [[left, bottom], [right, bottom], [right, top], [left, top]];
+        let uvs = region.uvs();
+        std::array::from_fn(|i| Self {
+            position: corners[i],
+            color: [color.x, color.y, color.z, color.w],
+            texture_coords: uvs[i],
+        })
+    }
+}
+
+fn quad_indices(count: usize) -> Vec<u32> {
+    (0..count as u32)
+        .flat_map(|quad| [0, 1, 2, 2, 3, 0].map(|i| i + quad * 4))
+        .collect()
+}
+
+/// Vertex and index buffers of glyph quads, shared by the bitmap and distance field renderers
+pub(crate) struct GlyphBatch {
+    vao: u32,
+    vbo: u32,
+    ibo: IndexBuffer,
+    capacity: usize,
+    quads: usize,
+}
+
+impl GlyphBatch {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        let mut vbo = 0;
+        let stride = std::mem::size_of::<GlyphVertex>() as i32;
+        gl_call!({
+            gl::GenVertexArrays(1, &mut vao);
+            gl::BindVertexArray(vao);
+            gl::CreateBuffers(1, &mut vbo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(
+                0,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset_of!(GlyphVertex, position) as *const c_void,
+            );
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(
+                1,
+                4,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset_of!(GlyphVertex, color) as *const c_void,
+            );
+            gl::EnableVertexAttribArray(2);
+            gl::VertexAttribPointer(
+                2,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                offset_of!(GlyphVertex, texture_coords) as *const c_void,
+            );
+            gl::BindVertexArray(0);
+        });
+
+        let mut instance = Self {
+            vao,
+            vbo,
+            ibo: IndexBuffer::new(&quad_indices(1)),
+            capacity: 0,
+            quads: 0,
+        };
+        instance.reserve(256);
+        instance
+    }
+
+    pub fn quads(&self) -> usize {
+        self.quads
+    }
+
+    /// Replaces the quads, four vertices each
+    pub fn upload(&mut self, vertices: &[GlyphVertex]) {
+        self.quads = vertices.len() / 4;
+        if vertices.is_empty() {
+            return;
+        }
+        self.reserve(self.quads);
+        gl_call!({
+            gl::NamedBufferSubData(
+                self.vbo,
+                0,
+                std::mem::size_of_val(vertices) as isize,
+                vertices.as_ptr() as *const c_void,
+            );
+        });
+    }
+
+    /// Draws the uploaded quads with whichever shader and texture are bound
+    pub fn draw(&self) {
+        gl_call!({
+            gl::BindVertexArray(self.vao);
+        });
+        self.ibo.bind();
+        gl_call!({
+            gl::DrawElements(
+                gl::TRIANGLES,
+                (self.quads * 6) as i32,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+            );
+            gl::BindVertexArray(0);
+        });
+    }
+
+    /// Grows the vertex and index buffers to hold at least `quads` quads
+    fn reserve(&mut self, quads: usize) {
+        if quads <= self.capacity {
+            return;
+        }
+        self.capacity = quads.next_power_of_two();
+        gl_call!({
+            gl::NamedBufferData(
+                self.vbo,
+                (self.capacity * 4 * std::mem::size_of::<GlyphVertex>()) as isize,
+                std::ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+        });
+        self.ibo = IndexBuffer::new(&quad_indices(self.capacity));
+    }
+}
+
+impl Drop for GlyphBatch {
+    fn drop(&mut self) {
+        gl_call!({
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        });
+    }
+}
+
+///
+/// Draws text as one textured quad per glyph, batched into a single draw call
+///
+/// Glyphs are laid out by `TextLayout` with the fonts of a `FontLibrary`, including
+/// kerning and fallback fonts, and rasterized once into a `GlyphCache`. Positions are in
+/// world units with y up, which matches `OrthographicCamera::from_screen` where a unit is
+/// a pixel.
+///
+/// # Usage
+/// ```ignore
+/// let mut text = TextRenderer::default();
+/// text.queue("Hello\nWorld", glm::vec2(20.0, 900.0), 32.0, glm::vec4(1.0, 1.0, 1.0, 1.0));
+/// text.flush(camera.view_projection());
+///
+/// // or split between `update` and `render`
+/// text.prepare();
+/// text.draw(camera.view_projection());
+///
+/// // other fonts are loaded into the library and referred to by id
+/// let mono = text.fonts_mut().load("mono", "src/res/fonts/Mono.ttf")?;
+/// text.queue_span(TextSpan::new("let x = 1;", 18.0, white).with_font(mono), position);
+/// ```
+///
+pub struct TextRenderer {
+    fonts: FontLibrary,
+    font_generation: u64,
+    cache: GlyphCache,
+    queued: Vec<(PositionedGlyph<'static>, FontId, glm::Vec2, glm::Vec4)>,
+    batch: GlyphBatch,
+    shader: Shader,
+}
+
+impl Default for TextRenderer {
+    fn default() -> Self {
+        Self::new(FontLibrary::default())
+    }
+}
+
+impl TextRenderer {
+    pub fn new(fonts: FontLibrary) -> Self {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("res/shaders/Glyph.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        shader.bind();
+        shader.uniform_1i("u_GlyphCache", 0);
+        shader.unbind();
+
+        Self {
+            font_generation: fonts.generation(),
+            fonts,
+            cache: GlyphCache::new(512, 512).expect("Failed to create the glyph cache"),
+            queued: Vec::new(),
+            batch: GlyphBatch::new(),
+            shader,
+        }
+    }
+
+    pub fn fonts(&self) -> &FontLibrary {
+        &self.fonts
+    }
+
+    /// Fonts can be loaded at any time, replacing one empties the glyph cache on the next
+    /// `prepare`
+    pub fn fonts_mut(&mut self) -> &mut FontLibrary {
+        &mut self.fonts
+    }
+
+    pub fn cache(&self) -> &GlyphCache {
+        &self.cache
+    }
+
+    /// Forgets every rasterized glyph, they are rasterized again by the next `prepare`
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Vertical metrics of the default font
+    pub fn v_metrics(&self, size: f32) -> VMetrics {
+        self.fonts
+            .font(FontId::default())
+            .v_metrics(Scale::uniform(size))
+    }
+
+    /// Distance between the baselines of two lines of text
+    pub fn line_height(&self, size: f32) -> f32 {
+        let metrics = self.v_metrics(size);
+        metrics.ascent - metrics.descent + metrics.line_gap
+    }
+
+    /// Width and height of `text` at `size` without wrapping, in pixels
+    pub fn measure(&self, text: &str, size: f32) -> glm::Vec2 {
+        TextLayout::measure(&self.fonts, text, size)
+    }
+
+    ///
+    /// Adds `text` to the next `prepare`, `position` being the top left corner of the first
+    /// line. Lines only break at `\n`, see `queue_layout` for wrapping and alignment
+    ///
+    pub fn queue(&mut self, text: &str, position: glm::Vec2, size: f32, color: glm::Vec4) {
+        self.queue_span(TextSpan::new(text, size, color), position);
+    }
+
+    /// Like `queue`, with the font of the span
+    pub fn queue_span(&mut self, span: TextSpan, position: glm::Vec2) {
+        let layout = TextLayout::new(&self.fonts, &[span], &LayoutOptions::default());
+        self.queue_layout(&layout, position);
+    }
+
+    ///
+    /// Adds laid out text to the next `prepare`, `position` being the top left corner of
+    /// its box
+    ///
+    pub fn queue_layout(&mut self, layout: &TextLayout, position: glm::Vec2) {
+        self.queued.extend(
+            layout
+                .glyphs
+                .iter()
+                .map(|(glyph, font, color)| (glyph.clone(), *font, position, *color)),
+        );
+    }
+
+    ///
+    /// Rasterizes the queued glyphs and uploads their quads, replacing what was prepared
+    /// before
+    ///
+    /// Separate from `draw` so the mutable work can happen in `update` and the drawing in
+    /// `render`.
+    ///
+    pub fn prepare(&mut self) {
+        // Replaced fonts keep their id, so their old glyphs would still be found
+        if self.fonts.generation() != self.font_generation {
+            self.font_generation = self.fonts.generation();
+            self.cache.clear();
+        }
+        self.cache
+            .prepare(self.queued.iter().map(|(glyph, font, _, _)| (*font, glyph)));
+
+        let mut vertices = Vec::with_capacity(self.queued.len() * 4);
+        for (glyph, font, origin, color) in self.queued.drain(..) {
+            let Some((Some(cached), x, y)) = self.cache.get(font, &glyph) else {
+                continue;
+            };
+            let left = origin.x + (x + cached.offset.0) as f32;
+            let top = origin.y - (y + cached.offset.1) as f32;
+            let (right, bottom) = (
+                left + cached.region.width as f32,
+                top - cached.region.height as f32,
+            );
+            vertices.extend(GlyphVertex::quad(
+                (left, bottom),
+                (right, top),
+                &cached.region,
+                color,
+            ));
+        }
+        self.batch.upload(&vertices);
+    }
+
+    /// Draws the glyphs of the last `prepare`
+    pub fn draw(&self, view_projection: &glm::Mat4) {
+        if self.batch.quads() == 0 {
+            return;
+        }
+
+        self.shader.bind();
+        self.shader
+            .uniform_mat4("u_ViewProjection", view_projection);
+        self.cache.texture().bind_unit(0);
+        self.batch.draw();
+        self.shader.unbind();
+    }
+
+    /// Prepares and draws everything queued since the last flush
+    pub fn flush(&mut self, view_projection: &glm::Mat4) {
+        self.prepare();
+        self.draw(view_projection);
+    }
+}
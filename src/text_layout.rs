@@ -0,0 +1,789 @@
+use nalgebra_glm as glm;
+use rusttype::{point, GlyphId, PositionedGlyph, Scale};
+
+use crate::font::{FontId, FontLibrary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HorizontalAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Stretches the spaces of wrapped lines to fill the width, the last line of a
+    /// paragraph stays left aligned
+    Justify,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// A run of text sharing a font, size and color
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextSpan {
+    pub text: String,
+    pub size: f32,
+    pub color: glm::Vec4,
+    /// Characters this font has no glyph for use the library's fallbacks
+    pub font: FontId,
+}
+
+impl TextSpan {
+    pub fn new(text: impl Into<String>, size: f32, color: glm::Vec4) -> Self {
+        Self {
+            text: text.into(),
+            size,
+            color,
+            font: FontId::default(),
+        }
+    }
+
+    pub fn with_font(mut self, font: FontId) -> Self {
+        self.font = font;
+        self
+    }
+}
+
+///
+/// Where and how text is laid out
+///
+/// Without a max width lines only break at `\n`. A box height, or the widest line if
+/// there is none, is what vertical alignment is relative to.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutOptions {
+    pub max_width: Option<f32>,
+    /// Wrap at word boundaries when a line is wider than `max_width`, otherwise lines are
+    /// only cut when `ellipsis` is set
+    pub wrap: bool,
+    pub max_lines: Option<usize>,
+    pub height: Option<f32>,
+    pub align: HorizontalAlign,
+    pub vertical_align: VerticalAlign,
+    /// End truncated text with "…"
+    pub ellipsis: bool,
+    /// Multiplies the line height given by the font
+    pub line_spacing: f32,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            max_width: None,
+            wrap: true,
+            max_lines: None,
+            height: None,
+            align: HorizontalAlign::Left,
+            vertical_align: VerticalAlign::Top,
+            ellipsis: false,
+            line_spacing: 1.0,
+        }
+    }
+}
+
+impl LayoutOptions {
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
+    /// Height of the box, lines that don't fit are dropped
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn with_align(mut self, align: HorizontalAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_vertical_align(mut self, vertical_align: VerticalAlign) -> Self {
+        self.vertical_align = vertical_align;
+        self
+    }
+
+    pub fn with_ellipsis(mut self, ellipsis: bool) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    pub fn with_line_spacing(mut self, line_spacing: f32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+}
+
+/// A character measured for layout, before it is positioned
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    character: char,
+    glyph: GlyphId,
+    /// The span's font, or the fallback that has the glyph
+    font: FontId,
+    span: usize,
+    advance: f32,
+}
+
+impl Item {
+    fn is_space(&self) -> bool {
+        self.character.is_whitespace()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    items: Vec<Item>,
+    /// Whether the line ends a paragraph, those are never justified
+    last_of_paragraph: bool,
+}
+
+/// A laid out line, glyph indices into `TextLayout::glyphs`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutLine {
+    pub glyphs: std::ops::Range<usize>,
+    /// Width without trailing spaces
+    pub width: f32,
+    /// Distance of the baseline from the top of the box
+    pub baseline: f32,
+    pub height: f32,
+}
+
+///
+/// Text broken into lines and positioned relative to the top left of its box, y down
+///
+/// Layout and measurement are pure CPU work, `TextRenderer::queue_layout` draws the result.
+///
+#[derive(Clone)]
+pub struct TextLayout {
+    pub glyphs: Vec<(PositionedGlyph<'static>, FontId, glm::Vec4)>,
+    pub lines: Vec<LayoutLine>,
+    /// Width of the widest line and height of all lines
+    pub size: glm::Vec2,
+    /// Lines or characters were dropped to respect `max_lines`, `height` or `max_width`
+    pub truncated: bool,
+}
+
+impl TextLayout {
+    pub fn new(fonts: &FontLibrary, spans: &[TextSpan], options: &LayoutOptions) -> Self {
+        if spans.is_empty() {
+            return Self {
+                glyphs: Vec::new(),
+                lines: Vec::new(),
+                size: glm::vec2(0.0, 0.0),
+                truncated: false,
+            };
+        }
+
+        let measurer = Measurer { fonts, spans };
+        let mut lines = measurer.break_lines(options);
+
+        // Drop lines past the limits, keeping at least the first one
+        let mut truncated = false;
+        let mut keep = options.max_lines.unwrap_or(usize::MAX).min(lines.len());
+        if let Some(height) = options.height {
+            let mut total = 0.0;
+            let fitting = lines
+                .iter()
+                .take_while(|line| {
+                    total += measurer.line_height(line, options.line_spacing);
+                    total <= height + f32::EPSILON
+                })
+                .count();
+            keep = keep.min(fitting);
+        }
+        let keep = keep.max(1);
+        if keep < lines.len() {
+            lines.truncate(keep);
+            truncated = true;
+            if let Some(line) = lines.last_mut() {
+                line.last_of_paragraph = true;
+            }
+        }
+
+        // Cut lines that are too long when not wrapping, and end the text in an ellipsis
+        let dropped_lines = truncated;
+        for (index, line) in lines.iter_mut().enumerate() {
+            let too_wide = options
+                .max_width
+                .is_some_and(|max| !fits(measurer.width(&line.items), max));
+            let last = index + 1 == keep;
+            if too_wide || (dropped_lines && last) {
+                if options.ellipsis {
+                    measurer.ellipsize(line, options.max_width);
+                } else if let Some(max) = options.max_width {
+                    while !fits(measurer.width(&line.items), max) && line.items.pop().is_some() {}
+                }
+                truncated |= too_wide;
+            }
+        }
+
+        measurer.position(lines, options, truncated)
+    }
+
+    ///
+    /// Width and height of a single span without wrapping
+    ///
+    pub fn measure(fonts: &FontLibrary, text: &str, size: f32) -> glm::Vec2 {
+        let span = TextSpan::new(text, size, glm::vec4(1.0, 1.0, 1.0, 1.0));
+        Self::new(fonts, &[span], &LayoutOptions::default()).size
+    }
+}
+
+struct Measurer<'a> {
+    fonts: &'a FontLibrary,
+    spans: &'a [TextSpan],
+}
+
+impl Measurer<'_> {
+    fn scale(&self, span: usize) -> Scale {
+        Scale::uniform(self.spans[span].size)
+    }
+
+    fn item(&self, character: char, span: usize) -> Item {
+        let font = self.fonts.resolve(self.spans[span].font, character);
+        let glyph = self
+            .fonts
+            .font(font)
+            .glyph(character)
+            .scaled(self.scale(span));
+        Item {
+            character,
+            glyph: glyph.id(),
+            font,
+            span,
+            advance: glyph.h_metrics().advance_width,
+        }
+    }
+
+    /// Kerning between two neighbouring items, only within a span and font since sizes and
+    /// glyph ids differ
+    fn kerning(&self, previous: Option<&Item>, item: &Item) -> f32 {
+        match previous {
+            Some(previous) if previous.span == item.span && previous.font == item.font => self
+                .fonts
+                .font(item.font)
+                .pair_kerning(self.scale(item.span), previous.glyph, item.glyph),
+            _ => 0.0,
+        }
+    }
+
+    /// Width of `items`, ignoring trailing spaces
+    fn width(&self, items: &[Item]) -> f32 {
+        let end = items
+            .iter()
+            .rposition(|item| !item.is_space())
+            .map_or(0, |i| i + 1);
+        let items = &items[..end];
+        let mut width = 0.0;
+        for (i, item) in items.iter().enumerate() {
+            width += self.kerning(i.checked_sub(1).map(|p| &items[p]), item) + item.advance;
+        }
+        width
+    }
+
+    fn line_height(&self, line: &Line, spacing: f32) -> f32 {
+        let (ascent, descent, gap) = self.vertical_metrics(line);
+        (ascent - descent + gap) * spacing
+    }
+
+    /// Largest ascent, descent and line gap of the fonts and spans on a line
+    fn vertical_metrics(&self, line: &Line) -> (f32, f32, f32) {
+        let spans = line.items.iter().map(|item| (item.font, item.span));
+        // Empty lines take the metrics of the first span
+        let spans: Vec<_> = if line.items.is_empty() {
+            vec![(self.spans[0].font, 0)]
+        } else {
+            spans.collect()
+        };
+        spans
+            .into_iter()
+            .fold((0.0f32, 0.0f32, 0.0f32), |(a, d, g), (font, span)| {
+                let metrics = self.fonts.font(font).v_metrics(self.scale(span));
+                (
+                    a.max(metrics.ascent),
+                    d.min(metrics.descent),
+                    g.max(metrics.line_gap),
+                )
+            })
+    }
+
+    /// Splits paragraphs at `\n` and, if wrapping, at spaces
+    fn break_lines(&self, options: &LayoutOptions) -> Vec<Line> {
+        let mut paragraphs = vec![Vec::new()];
+        for (span, text) in self.spans.iter().enumerate() {
+            for character in text.text.chars() {
+                match character {
+                    '\n' => paragraphs.push(Vec::new()),
+                    '\r' => {}
+                    character => paragraphs
+                        .last_mut()
+                        .unwrap()
+                        .push(self.item(character, span)),
+                }
+            }
+        }
+
+        let max_width = options.max_width.filter(|_| options.wrap);
+        let mut lines = Vec::new();
+        for paragraph in paragraphs {
+            let Some(max_width) = max_width else {
+                lines.push(Line {
+                    items: paragraph,
+                    last_of_paragraph: true,
+                });
+                continue;
+            };
+
+            let mut line: Vec<Item> = Vec::new();
+            for word in words(&paragraph) {
+                let mut candidate = line.clone();
+                candidate.extend_from_slice(word);
+                if line.is_empty() || fits(self.width(&candidate), max_width) {
+                    line = candidate;
+                } else {
+                    lines.push(Line {
+                        items: std::mem::take(&mut line),
+                        last_of_paragraph: false,
+                    });
+                    line = word.to_vec();
+                }
+
+                // Words wider than a whole line are broken between characters
+                while !fits(self.width(&line), max_width) && line.len() > 1 {
+                    let mut split = line.len() - 1;
+                    while split > 1 && !fits(self.width(&line[..split]), max_width) {
+                        split -= 1;
+                    }
+                    let rest = line.split_off(split);
+                    lines.push(Line {
+                        items: line,
+                        last_of_paragraph: false,
+                    });
+                    line = rest;
+                }
+            }
+            lines.push(Line {
+                items: line,
+                last_of_paragraph: true,
+            });
+        }
+        lines
+    }
+
+    /// Replaces the end of a line with "…" so it fits `max_width`
+    fn ellipsize(&self, line: &mut Line, max_width: Option<f32>) {
+        let span = line.items.last().map_or(0, |item| item.span);
+        let font = self.fonts.resolve(self.spans[span].font, '…');
+        let ellipsis = if self.fonts.has_glyph(font, '…') {
+            vec![self.item('…', span)]
+        } else {
+            vec![self.item('.', span); 3]
+        };
+
+        let max_width = max_width.unwrap_or(f32::INFINITY);
+        let fits = |items: &[Item]| {
+            let mut candidate = items.to_vec();
+            while candidate.last().is_some_and(Item::is_space) {
+                candidate.pop();
+            }
+            candidate.extend_from_slice(&ellipsis);
+            fits(self.width(&candidate), max_width)
+        };
+        while !line.items.is_empty() && !fits(&line.items) {
+            line.items.pop();
+        }
+        while line.items.last().is_some_and(Item::is_space) {
+            line.items.pop();
+        }
+        line.items.extend(ellipsis);
+    }
+
+    fn position(&self, lines: Vec<Line>, options: &LayoutOptions, truncated: bool) -> TextLayout {
+        let widths: Vec<f32> = lines.iter().map(|line| self.width(&line.items)).collect();
+        let heights: Vec<f32> = lines
+            .iter()
+            .map(|line| self.line_height(line, options.line_spacing))
+            .collect();
+        let widest = widths.iter().copied().fold(0.0, f32::max);
+        let total_height: f32 = heights.iter().sum();
+
+        let box_width = options.max_width.unwrap_or(widest);
+        let box_height = options.height.unwrap_or(total_height);
+        let mut top = match options.vertical_align {
+            VerticalAlign::Top => 0.0,
+            VerticalAlign::Middle => (box_height - total_height) / 2.0,
+            VerticalAlign::Bottom => box_height - total_height,
+        };
+
+        let mut glyphs = Vec::new();
+        let mut layout_lines = Vec::with_capacity(lines.len());
+        for ((line, width), height) in lines.iter().zip(widths).zip(heights) {
+            let (ascent, _, _) = self.vertical_metrics(line);
+            let baseline = top + ascent;
+            let free = (box_width - width).max(0.0);
+
+            let content = line
+                .items
+                .iter()
+                .rposition(|item| !item.is_space())
+                .map_or(0, |i| i + 1);
+            let gaps = line.items[..content]
+                .iter()
+                .filter(|item| item.is_space())
+                .count();
+            let justify =
+                options.align == HorizontalAlign::Justify && !line.last_of_paragraph && gaps > 0;
+            let (mut x, gap) = match options.align {
+                HorizontalAlign::Left => (0.0, 0.0),
+                HorizontalAlign::Center => (free / 2.0, 0.0),
+                HorizontalAlign::Right => (free, 0.0),
+                HorizontalAlign::Justify if justify => (0.0, free / gaps as f32),
+                HorizontalAlign::Justify => (0.0, 0.0),
+            };
+
+            let start = glyphs.len();
+            for (i, item) in line.items.iter().enumerate() {
+                x += self.kerning(i.checked_sub(1).map(|p| &line.items[p]), item);
+                let glyph = self
+                    .fonts
+                    .font(item.font)
+                    .glyph(item.glyph)
+                    .scaled(self.scale(item.span))
+                    .positioned(point(x, baseline));
+                glyphs.push((glyph, item.font, self.spans[item.span].color));
+                x += item.advance;
+                if item.is_space() && i < content {
+                    x += gap;
+                }
+            }
+
+            layout_lines.push(LayoutLine {
+                glyphs: start..glyphs.len(),
+                width,
+                baseline,
+                height,
+            });
+            top += height;
+        }
+
+        TextLayout {
+            glyphs,
+            lines: layout_lines,
+            size: glm::vec2(widest, total_height),
+            truncated,
+        }
+    }
+}
+
+/// A little slack so rounding in summed advances doesn't cut text that exactly fits
+fn fits(width: f32, max_width: f32) -> bool {
+    width <= max_width + 1e-3
+}
+
+/// Splits items into words, each followed by the spaces after it
+fn words(items: &[Item]) -> impl Iterator<Item = &[Item]> {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if start >= items.len() {
+            return None;
+        }
+        let mut end = start;
+        while end < items.len() && !items[end].is_space() {
+            end += 1;
+        }
+        while end < items.len() && items[end].is_space() {
+            end += 1;
+        }
+        let word = &items[start..end];
+        start = end;
+        Some(word)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: f32 = 20.0;
+
+    fn fonts() -> FontLibrary {
+        let mut fonts = FontLibrary::default();
+        fonts
+            .load_from_memory("mono", include_bytes!("res/fonts/Default.ttf").to_vec())
+            .unwrap();
+        fonts
+    }
+
+    fn white() -> glm::Vec4 {
+        glm::vec4(1.0, 1.0, 1.0, 1.0)
+    }
+
+    fn lay_out(fonts: &FontLibrary, text: &str, options: &LayoutOptions) -> TextLayout {
+        TextLayout::new(fonts, &[TextSpan::new(text, SIZE, white())], options)
+    }
+
+    /// The bundled font is monospaced, every character advances the same
+    fn advance(fonts: &FontLibrary) -> f32 {
+        TextLayout::measure(fonts, "x", SIZE).x
+    }
+
+    fn line_height(fonts: &FontLibrary, size: f32) -> f32 {
+        TextLayout::measure(fonts, "x", size).y
+    }
+
+    fn x(layout: &TextLayout, glyph: usize) -> f32 {
+        layout.glyphs[glyph].0.position().x
+    }
+
+    fn text(fonts: &FontLibrary, layout: &TextLayout, line: usize) -> String {
+        let default = fonts.font(FontId::default());
+        layout.glyphs[layout.lines[line].glyphs.clone()]
+            .iter()
+            .map(|(glyph, _, _)| {
+                (' '..='~')
+                    .chain(['…'])
+                    .find(|&c| default.glyph(c).id() == glyph.id())
+                    .unwrap_or('?')
+            })
+            .collect()
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn measure() {
+        let fonts = fonts();
+        let (a, height) = (advance(&fonts), line_height(&fonts, SIZE));
+        assert!(a > 0.0 && height > 0.0);
+
+        assert_close(TextLayout::measure(&fonts, "abc", SIZE).x, 3.0 * a);
+        // Trailing spaces don't count
+        assert_close(TextLayout::measure(&fonts, "ab  ", SIZE).x, 2.0 * a);
+        // Twice the size, twice the width
+        assert_close(TextLayout::measure(&fonts, "ab", SIZE * 2.0).x, 4.0 * a);
+
+        let size = TextLayout::measure(&fonts, "abc\nde", SIZE);
+        assert_close(size.x, 3.0 * a);
+        assert_close(size.y, 2.0 * height);
+    }
+
+    #[test]
+    fn newlines() {
+        let fonts = fonts();
+        let height = line_height(&fonts, SIZE);
+        let layout = lay_out(&fonts, "ab\r\n\ncd\n", &LayoutOptions::default());
+
+        assert_eq!(layout.lines.len(), 4);
+        assert_eq!(text(&fonts, &layout, 0), "ab");
+        assert_eq!(text(&fonts, &layout, 1), "");
+        assert_eq!(text(&fonts, &layout, 2), "cd");
+        // Empty lines still take up their height
+        assert_close(
+            layout.lines[2].baseline - layout.lines[0].baseline,
+            2.0 * height,
+        );
+        assert_close(layout.size.y, 4.0 * height);
+        assert!(!layout.truncated);
+    }
+
+    #[test]
+    fn word_wrap() {
+        let fonts = fonts();
+        let a = advance(&fonts);
+        let options = LayoutOptions::default().with_max_width(7.5 * a);
+        let layout = lay_out(&fonts, "aaa bbb ccc", &options);
+
+        assert_eq!(layout.lines.len(), 2);
+        assert_eq!(text(&fonts, &layout, 0), "aaa bbb ");
+        assert_eq!(text(&fonts, &layout, 1), "ccc");
+        assert_close(layout.lines[0].width, 7.0 * a);
+        assert_close(layout.size.x, 7.0 * a);
+        assert_close(x(&layout, 8), 0.0);
+
+        // Without wrapping the line is kept whole
+        let layout = lay_out(&fonts, "aaa bbb ccc", &options.with_wrap(false));
+        assert_eq!(layout.lines.len(), 1);
+    }
+
+    #[test]
+    fn long_words_break_between_characters() {
+        let fonts = fonts();
+        let options = LayoutOptions::default().with_max_width(4.5 * advance(&fonts));
+        let layout = lay_out(&fonts, "abcdefghij", &options);
+
+        let lines: Vec<_> = (0..layout.lines.len())
+            .map(|line| text(&fonts, &layout, line))
+            .collect();
+        assert_eq!(lines, ["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn horizontal_align() {
+        let fonts = fonts();
+        let a = advance(&fonts);
+        let first_x = |align| {
+            let options = LayoutOptions::default()
+                .with_max_width(10.0 * a)
+                .with_align(align);
+            x(&lay_out(&fonts, "ab", &options), 0)
+        };
+
+        assert_close(first_x(HorizontalAlign::Left), 0.0);
+        assert_close(first_x(HorizontalAlign::Center), 4.0 * a);
+        assert_close(first_x(HorizontalAlign::Right), 8.0 * a);
+        // A single line is the last of its paragraph
+        assert_close(first_x(HorizontalAlign::Justify), 0.0);
+    }
+
+    #[test]
+    fn justify() {
+        let fonts = fonts();
+        let a = advance(&fonts);
+        let options = LayoutOptions::default()
+            .with_max_width(9.0 * a)
+            .with_align(HorizontalAlign::Justify);
+        let layout = lay_out(&fonts, "aa bb cc dd", &options);
+
+        assert_eq!(text(&fonts, &layout, 0), "aa bb cc ");
+        // The two gaps share the free space, the line ends at the max width
+        assert_close(x(&layout, 3), 3.5 * a);
+        assert_close(x(&layout, 7), 8.0 * a);
+        // The last line of the paragraph stays left aligned
+        assert_close(x(&layout, 9), 0.0);
+        assert_close(x(&layout, 10), a);
+
+        let layout = lay_out(&fonts, "aa bb\ncc dd", &options);
+        assert_close(x(&layout, 3), 3.0 * a);
+    }
+
+    #[test]
+    fn vertical_align() {
+        let fonts = fonts();
+        let height = line_height(&fonts, SIZE);
+        let baseline = |align| {
+            let options = LayoutOptions::default()
+                .with_height(100.0)
+                .with_vertical_align(align);
+            lay_out(&fonts, "ab\ncd", &options).lines[0].baseline
+        };
+
+        let top = baseline(VerticalAlign::Top);
+        let ascent = fonts
+            .font(FontId::default())
+            .v_metrics(Scale::uniform(SIZE))
+            .ascent;
+        assert_close(top, ascent);
+        assert_close(
+            baseline(VerticalAlign::Middle),
+            top + (100.0 - 2.0 * height) / 2.0,
+        );
+        assert_close(baseline(VerticalAlign::Bottom), top + 100.0 - 2.0 * height);
+
+        // Without a box height the text is its own box
+        let options = LayoutOptions::default().with_vertical_align(VerticalAlign::Bottom);
+        assert_close(lay_out(&fonts, "ab", &options).lines[0].baseline, top);
+    }
+
+    #[test]
+    fn height_drops_lines() {
+        let fonts = fonts();
+        let height = line_height(&fonts, SIZE);
+        let options = LayoutOptions::default().with_height(2.5 * height);
+        let layout = lay_out(&fonts, "a\nb\nc\nd", &options);
+
+        assert_eq!(layout.lines.len(), 2);
+        assert!(layout.truncated);
+    }
+
+    #[test]
+    fn ellipsis_after_max_lines() {
+        let fonts = fonts();
+        let a = advance(&fonts);
+        let options = LayoutOptions::default()
+            .with_max_width(5.5 * a)
+            .with_max_lines(1);
+
+        let layout = lay_out(&fonts, "aa bb cc dd", &options);
+        assert_eq!(layout.lines.len(), 1);
+        assert_eq!(text(&fonts, &layout, 0), "aa bb ");
+        assert!(layout.truncated);
+
+        let layout = lay_out(&fonts, "aa bb cc dd", &options.with_ellipsis(true));
+        assert_eq!(text(&fonts, &layout, 0), "aa b…");
+        assert!(layout.lines[0].width <= 5.5 * a);
+        assert!(layout.truncated);
+
+        // Text that fits is left alone
+        let layout = lay_out(&fonts, "aa", &options.with_ellipsis(true));
+        assert_eq!(text(&fonts, &layout, 0), "aa");
+        assert!(!layout.truncated);
+    }
+
+    #[test]
+    fn ellipsis_without_wrapping() {
+        let fonts = fonts();
+        let a = advance(&fonts);
+        let options = LayoutOptions::default()
+            .with_max_width(5.0 * a)
+            .with_wrap(false);
+
+        let layout = lay_out(&fonts, "abcdefghij\nabc", &options);
+        assert_eq!(layout.lines.len(), 2);
+        assert_eq!(text(&fonts, &layout, 0), "abcde");
+        assert_eq!(text(&fonts, &layout, 1), "abc");
+        assert!(layout.truncated);
+
+        let layout = lay_out(&fonts, "abcdefghij\nabc", &options.with_ellipsis(true));
+        assert_eq!(text(&fonts, &layout, 0), "abcd…");
+        assert_eq!(text(&fonts, &layout, 1), "abc");
+    }
+
+    #[test]
+    fn span_size_and_color() {
+        let fonts = fonts();
+        let a = advance(&fonts);
+        let (red, blue) = (glm::vec4(1.0, 0.0, 0.0, 1.0), glm::vec4(0.0, 0.0, 1.0, 1.0));
+        let mono = fonts.id("mono").unwrap();
+        let spans = [
+            TextSpan::new("ab", SIZE, red),
+            TextSpan::new("cd", SIZE * 2.0, blue).with_font(mono),
+        ];
+        let layout = TextLayout::new(&fonts, &spans, &LayoutOptions::default());
+
+        let glyphs: Vec<_> = layout
+            .glyphs
+            .iter()
+            .map(|(glyph, font, color)| (glyph.scale().y, *font, *color))
+            .collect();
+        assert_eq!(
+            glyphs,
+            [
+                (SIZE, FontId::default(), red),
+                (SIZE, FontId::default(), red),
+                (SIZE * 2.0, mono, blue),
+                (SIZE * 2.0, mono, blue),
+            ]
+        );
+        assert_close(x(&layout, 2), 2.0 * a);
+        assert_close(layout.size.x, 6.0 * a);
+        // The line is as tall as its largest span
+        assert_close(layout.size.y, line_height(&fonts, SIZE * 2.0));
+    }
+}
@@ -1,15 +1,411 @@
-use std::ffi::c_void;
+use std::{ffi::c_void, fmt::Display, path::Path};
 
 use glcall_macro::gl_call;
+use image::{DynamicImage, RgbaImage};
 use stb_image::image::{Image, LoadResult};
-use stb_image::stb_image::bindgen::stbi_set_flip_vertically_on_load;
+
+use crate::{
+    framebuffer::FramebufferIncompleteError,
+    gl_component::GLComponent,
+    sampler::{TextureFilter, TextureSpec, WrapMode},
+};
+
+#[derive(Debug)]
+pub enum TextureError {
+    Load(String),
+    /// Rendering into the texture failed, e.g. projecting an equirectangular map
+    Framebuffer(FramebufferIncompleteError),
+    /// A width or height of zero or above `GL_MAX_TEXTURE_SIZE`
+    InvalidSize {
+        width: usize,
+        height: usize,
+    },
+    /// A region reaching outside the texture
+    RegionOutOfBounds {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    },
+    /// A layer count or volume depth of zero or above the driver's limit, or a layer index
+    /// past the end of an array
+    InvalidDepth {
+        depth: usize,
+    },
+    /// A layer or face of a multi-image texture differs in size from the first one
+    LayerMismatch {
+        layer: usize,
+    },
+    /// Raw pixel data does not match the given dimensions
+    DataSize {
+        expected: usize,
+        actual: usize,
+    },
+    /// Images with no or more than four channels
+    UnsupportedChannels(usize),
+    /// sRGB was requested for a format that has no sRGB variant
+    SrgbUnsupported {
+        channels: usize,
+        hdr: bool,
+    },
+    /// The requested format does not match the channels or data type of the image
+    FormatMismatch {
+        format: TextureFormat,
+        channels: usize,
+        hdr: bool,
+    },
+}
+
+impl Display for TextureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = |hdr: &bool| if *hdr { "HDR" } else { "8-bit" };
+        match self {
+            Self::Load(e) => write!(f, "Failed to load image: {}", e),
+            Self::Framebuffer(e) => write!(f, "{}", e),
+            Self::InvalidDepth { depth } => write!(f, "Invalid texture depth or layer {}", depth),
+            Self::LayerMismatch { layer } => {
+                write!(f, "Layer {} differs in size from the first layer", layer)
+            }
+            Self::InvalidSize { width, height } => {
+                write!(f, "Invalid texture size {}x{}", width, height)
+            }
+            Self::RegionOutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            } => write!(
+                f,
+                "Region {}x{} at ({}, {}) is outside the texture",
+                width, height, x, y
+            ),
+            Self::DataSize { expected, actual } => write!(
+                f,
+                "Expected {} bytes of pixel data, got {}",
+                expected, actual
+            ),
+            Self::UnsupportedChannels(channels) => {
+                write!(f, "Unsupported number of channels: {}", channels)
+            }
+            Self::SrgbUnsupported { channels, hdr } => write!(
+                f,
+                "No sRGB format for {} images with {} channel(s)",
+                kind(hdr),
+                channels
+            ),
+            Self::FormatMismatch {
+                format,
+                channels,
+                hdr,
+            } => write!(
+                f,
+                "Format {:?} cannot store {} images with {} channel(s)",
+                format,
+                kind(hdr),
+                channels
+            ),
+        }
+    }
+}
+
+impl From<FramebufferIncompleteError> for TextureError {
+    fn from(value: FramebufferIncompleteError) -> Self {
+        Self::Framebuffer(value)
+    }
+}
+
+/// How the color values of an 8-bit image are encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Values are used as they are, e.g. normal, roughness or data textures
+    #[default]
+    Linear,
+    /// Values are gamma encoded and decoded to linear when sampled, e.g. albedo textures
+    Srgb,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    R8,
+    Rg8,
+    Rgb8,
+    Rgba8,
+    Srgb8,
+    Srgb8Alpha8,
+    R16F,
+    Rg16F,
+    Rgb16F,
+    Rgba16F,
+    R32F,
+    Rg32F,
+    Rgb32F,
+    Rgba32F,
+    /// 24-bit depth, e.g. for shadow maps
+    Depth24,
+}
+
+impl TextureFormat {
+    ///
+    /// Picks the format for an image with `channels` channels
+    ///
+    /// HDR images are stored as half floats, which keeps values above 1.0 at half the
+    /// memory of full floats. sRGB only exists for RGB and RGBA 8-bit images.
+    ///
+    pub fn detect(
+        channels: usize,
+        hdr: bool,
+        color_space: ColorSpace,
+    ) -> Result<Self, TextureError> {
+        let format = match (channels, hdr, color_space) {
+            (0, ..) | (5.., ..) => return Err(TextureError::UnsupportedChannels(channels)),
+            (1 | 2, _, ColorSpace::Srgb) | (_, true, ColorSpace::Srgb) => {
+                return Err(TextureError::SrgbUnsupported { channels, hdr })
+            }
+            (1, false, _) => Self::R8,
+            (2, false, _) => Self::Rg8,
+            (3, false, ColorSpace::Linear) => Self::Rgb8,
+            (3, false, ColorSpace::Srgb) => Self::Srgb8,
+            (4, false, ColorSpace::Linear) => Self::Rgba8,
+            (4, false, ColorSpace::Srgb) => Self::Srgb8Alpha8,
+            (1, true, _) => Self::R16F,
+            (2, true, _) => Self::Rg16F,
+            (3, true, _) => Self::Rgb16F,
+            (_, true, _) => Self::Rgba16F,
+        };
+        Ok(format)
+    }
+
+    pub fn channels(&self) -> usize {
+        match self {
+            Self::R8 | Self::R16F | Self::R32F | Self::Depth24 => 1,
+            Self::Rg8 | Self::Rg16F | Self::Rg32F => 2,
+            Self::Rgb8 | Self::Srgb8 | Self::Rgb16F | Self::Rgb32F => 3,
+            Self::Rgba8 | Self::Srgb8Alpha8 | Self::Rgba16F | Self::Rgba32F => 4,
+        }
+    }
+
+    /// Whether the texel data is uploaded as floats rather than bytes
+    pub fn is_float(&self) -> bool {
+        self.data_type() == gl::FLOAT
+    }
+
+    pub fn is_srgb(&self) -> bool {
+        matches!(self, Self::Srgb8 | Self::Srgb8Alpha8)
+    }
+
+    pub fn internal_format(&self) -> u32 {
+        match self {
+            Self::R8 => gl::R8,
+            Self::Rg8 => gl::RG8,
+            Self::Rgb8 => gl::RGB8,
+            Self::Rgba8 => gl::RGBA8,
+            Self::Srgb8 => gl::SRGB8,
+            Self::Srgb8Alpha8 => gl::SRGB8_ALPHA8,
+            Self::R16F => gl::R16F,
+            Self::Rg16F => gl::RG16F,
+            Self::Rgb16F => gl::RGB16F,
+            Self::Rgba16F => gl::RGBA16F,
+            Self::R32F => gl::R32F,
+            Self::Rg32F => gl::RG32F,
+            Self::Rgb32F => gl::RGB32F,
+            Self::Rgba32F => gl::RGBA32F,
+            Self::Depth24 => gl::DEPTH_COMPONENT24,
+        }
+    }
+
+    /// Layout of the pixel data handed to OpenGL
+    pub fn pixel_format(&self) -> u32 {
+        if *self == Self::Depth24 {
+            return gl::DEPTH_COMPONENT;
+        }
+        match self.channels() {
+            1 => gl::RED,
+            2 => gl::RG,
+            3 => gl::RGB,
+            _ => gl::RGBA,
+        }
+    }
+
+    pub fn data_type(&self) -> u32 {
+        match self {
+            Self::R8 | Self::Rg8 | Self::Rgb8 | Self::Rgba8 | Self::Srgb8 | Self::Srgb8Alpha8 => {
+                gl::UNSIGNED_BYTE
+            }
+            _ => gl::FLOAT,
+        }
+    }
+
+    /// Size of one pixel of the uploaded data in bytes
+    pub fn bytes_per_pixel(&self) -> usize {
+        let component = if self.is_float() { 4 } else { 1 };
+        self.channels() * component
+    }
+}
+
+///
+/// How an image is turned into a texture
+///
+/// Images are stored top row first while OpenGL puts the first row at `v = 0`, so by
+/// default rows are flipped to keep `v = 1` at the top of the image.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureOptions {
+    pub color_space: ColorSpace,
+    pub flip_vertically: bool,
+    /// Overrides the format detected from the image, e.g. full floats for HDR data
+    pub format: Option<TextureFormat>,
+    pub spec: TextureSpec,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            color_space: ColorSpace::Linear,
+            flip_vertically: true,
+            format: None,
+            spec: TextureSpec::default(),
+        }
+    }
+}
+
+impl TextureOptions {
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    pub fn with_flip(mut self, flip_vertically: bool) -> Self {
+        self.flip_vertically = flip_vertically;
+        self
+    }
+
+    pub fn with_format(mut self, format: TextureFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn with_spec(mut self, spec: TextureSpec) -> Self {
+        self.spec = spec;
+        self
+    }
+
+    /// Resolves the format of `image` and flips it if requested, ready for upload
+    pub(crate) fn prepare(&self, image: &mut TextureImage) -> Result<TextureFormat, TextureError> {
+        let format = self.resolve_format(image)?;
+        if self.flip_vertically {
+            image.flip_vertically();
+        }
+        Ok(format)
+    }
+
+    /// The format given explicitly or detected from `image`, checked against its contents
+    fn resolve_format(&self, image: &TextureImage) -> Result<TextureFormat, TextureError> {
+        let (channels, hdr) = (image.depth(), image.is_hdr());
+        let format = match self.format {
+            Some(format) => format,
+            None => TextureFormat::detect(channels, hdr, self.color_space)?,
+        };
+        if format.channels() != channels || format.is_float() != hdr {
+            return Err(TextureError::FormatMismatch {
+                format,
+                channels,
+                hdr,
+            });
+        }
+        Ok(format)
+    }
+}
 
 pub enum TextureImage {
     U8(Image<u8>),
     F32(Image<f32>),
 }
 
+impl From<DynamicImage> for TextureImage {
+    /// Keeps 8-bit and 32-bit float images as they are, other types are converted to RGBA8
+    fn from(value: DynamicImage) -> Self {
+        let (width, height) = (value.width() as usize, value.height() as usize);
+        let bytes = |depth: usize, data: Vec<u8>| {
+            TextureImage::U8(Image {
+                width,
+                height,
+                depth,
+                data,
+            })
+        };
+        let floats = |depth: usize, data: Vec<f32>| {
+            TextureImage::F32(Image {
+                width,
+                height,
+                depth,
+                data,
+            })
+        };
+
+        match value {
+            DynamicImage::ImageLuma8(image) => bytes(1, image.into_raw()),
+            DynamicImage::ImageLumaA8(image) => bytes(2, image.into_raw()),
+            DynamicImage::ImageRgb8(image) => bytes(3, image.into_raw()),
+            DynamicImage::ImageRgba8(image) => bytes(4, image.into_raw()),
+            DynamicImage::ImageRgb32F(image) => floats(3, image.into_raw()),
+            DynamicImage::ImageRgba32F(image) => floats(4, image.into_raw()),
+            image => bytes(4, image.to_rgba8().into_raw()),
+        }
+    }
+}
+
 impl TextureImage {
+    /// Decodes an image file, top row first
+    pub fn load(path: impl Into<String>) -> Result<Self, TextureError> {
+        Self::from_load_result(stb_image::image::load(path.into()))
+    }
+
+    /// Decodes an encoded image (PNG, JPEG, HDR, ...), e.g. one embedded with `include_bytes!`
+    pub fn load_from_memory(bytes: &[u8]) -> Result<Self, TextureError> {
+        Self::from_load_result(stb_image::image::load_from_memory(bytes))
+    }
+
+    fn from_load_result(result: LoadResult) -> Result<Self, TextureError> {
+        match result {
+            LoadResult::ImageU8(image) => Ok(TextureImage::U8(image)),
+            LoadResult::ImageF32(image) => Ok(TextureImage::F32(image)),
+            LoadResult::Error(e) => Err(TextureError::Load(e)),
+        }
+    }
+
+    /// Wraps tightly packed 8-bit RGBA pixels, top row first
+    pub fn from_rgba(width: usize, height: usize, pixels: &[u8]) -> Result<Self, TextureError> {
+        let expected = width * height * 4;
+        if pixels.len() != expected {
+            return Err(TextureError::DataSize {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+        Ok(TextureImage::U8(Image {
+            width,
+            height,
+            depth: 4,
+            data: pixels.to_vec(),
+        }))
+    }
+
+    /// Reverses the order of the rows
+    pub fn flip_vertically(&mut self) {
+        fn flip<T>(data: &mut [T], row_size: usize) {
+            let rows = data.len() / row_size.max(1);
+            for row in 0..rows / 2 {
+                let (top, bottom) = data.split_at_mut((rows - row - 1) * row_size);
+                top[row * row_size..(row + 1) * row_size].swap_with_slice(&mut bottom[..row_size]);
+            }
+        }
+
+        match self {
+            TextureImage::U8(im) => flip(&mut im.data, im.width * im.depth),
+            TextureImage::F32(im) => flip(&mut im.data, im.width * im.depth),
+        }
+    }
+
     pub fn width(&self) -> usize {
         match self {
             TextureImage::F32(im) => im.width,
@@ -24,6 +420,17 @@ impl TextureImage {
         }
     }
 
+    pub fn depth(&self) -> usize {
+        match self {
+            TextureImage::F32(im) => im.depth,
+            TextureImage::U8(im) => im.depth,
+        }
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        matches!(self, TextureImage::F32(_))
+    }
+
     pub fn ptr(&self) -> *const c_void {
         match self {
             TextureImage::F32(im) => im.data.as_ptr() as *const c_void,
@@ -32,9 +439,92 @@ impl TextureImage {
     }
 }
 
+///
+/// Shared by every texture type so materials can bind any of them to a texture unit
+///
+/// `GLComponent::bind` binds to the active unit, `bind_unit` selects the unit first.
+///
+pub trait TextureObject: GLComponent {
+    /// `TEXTURE_2D`, `TEXTURE_2D_ARRAY`, `TEXTURE_CUBE_MAP` or `TEXTURE_3D`
+    fn target(&self) -> u32;
+
+    fn bind_unit(&self, unit: u32) {
+        gl_call!({
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+        });
+        self.bind();
+    }
+
+    /// Applies the sampling parameters, generating mipmaps if the spec filters between them
+    fn set_spec(&self, spec: &TextureSpec) {
+        let id = self.renderer_id();
+        spec.apply_with(
+            |parameter, value| {
+                gl_call!({
+                    gl::TextureParameteri(id, parameter, value);
+                });
+            },
+            |parameter, value| {
+                gl_call!({
+                    gl::TextureParameterf(id, parameter, value);
+                });
+            },
+            |parameter, value| {
+                gl_call!({
+                    gl::TextureParameterfv(id, parameter, value.as_ptr());
+                });
+            },
+        );
+        if spec.mipmap_filter.is_some() {
+            self.generate_mipmaps();
+        }
+    }
+
+    fn with_spec(self, spec: TextureSpec) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_spec(&spec);
+        self
+    }
+
+    /// Rebuilds the mip chain from the base level, e.g. after rendering into it
+    fn generate_mipmaps(&self) {
+        gl_call!({
+            gl::GenerateTextureMipmap(self.renderer_id());
+        });
+    }
+}
+
 pub struct Texture {
     renderer_id: u32,
-    image: TextureImage,
+    format: TextureFormat,
+    width: usize,
+    height: usize,
+}
+
+impl GLComponent for Texture {
+    fn renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+
+    fn bind(&self) {
+        gl_call!({
+            gl::BindTexture(gl::TEXTURE_2D, self.renderer_id);
+        });
+    }
+
+    fn unbind(&self) {
+        gl_call!({
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        });
+    }
+}
+
+impl TextureObject for Texture {
+    fn target(&self) -> u32 {
+        gl::TEXTURE_2D
+    }
 }
 
 impl Drop for Texture {
@@ -46,73 +536,318 @@ impl Drop for Texture {
 }
 
 impl Texture {
+    /// Loads an image as linear data, panicking if it cannot be loaded or uploaded
     pub fn new(path: impl Into<String>, index: u32) -> Self {
-        let mut instance = {
-            unsafe {
-                stbi_set_flip_vertically_on_load(1);
-            }
-            let image = stb_image::image::load(path.into());
+        TextureImage::load(path)
+            .and_then(|image| Self::upload(image, &TextureOptions::default(), index))
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
 
-            let image = match image {
-                LoadResult::ImageU8(image) => TextureImage::U8(image),
-                LoadResult::ImageF32(image) => TextureImage::F32(image),
-                LoadResult::Error(e) => panic!("Failed to load image: {}", e),
-            };
+    ///
+    /// Loads an image file, picking the format from its channels and data type unless the
+    /// options set one
+    ///
+    pub fn load(path: impl Into<String>, options: &TextureOptions) -> Result<Self, TextureError> {
+        Self::upload(TextureImage::load(path)?, options, 0)
+    }
 
-            Self {
-                renderer_id: 0,
-                image,
-            }
+    /// Decodes an encoded image (PNG, JPEG, HDR, ...), e.g. one embedded with `include_bytes!`
+    pub fn from_memory(bytes: &[u8], options: &TextureOptions) -> Result<Self, TextureError> {
+        Self::upload(TextureImage::load_from_memory(bytes)?, options, 0)
+    }
+
+    /// Uploads tightly packed 8-bit RGBA pixels, top row first
+    pub fn from_rgba(
+        width: usize,
+        height: usize,
+        pixels: &[u8],
+        options: &TextureOptions,
+    ) -> Result<Self, TextureError> {
+        Self::upload(TextureImage::from_rgba(width, height, pixels)?, options, 0)
+    }
+
+    pub fn from_image(image: DynamicImage, options: &TextureOptions) -> Result<Self, TextureError> {
+        Self::upload(image.into(), options, 0)
+    }
+
+    pub fn from_texture_image(
+        image: TextureImage,
+        options: &TextureOptions,
+    ) -> Result<Self, TextureError> {
+        Self::upload(image, options, 0)
+    }
+
+    /// Allocates a texture with undefined contents, e.g. to render into or fill later
+    pub fn empty(width: usize, height: usize, format: TextureFormat) -> Result<Self, TextureError> {
+        Self::allocate(width, height, format, std::ptr::null(), 0)
+    }
+
+    fn upload(
+        mut image: TextureImage,
+        options: &TextureOptions,
+        unit: u32,
+    ) -> Result<Self, TextureError> {
+        let format = options.prepare(&mut image)?;
+
+        let instance = Self::allocate(image.width(), image.height(), format, image.ptr(), unit)?;
+
+        set_grayscale_swizzle(instance.renderer_id, format);
+        instance.set_spec(&options.spec);
+        Ok(instance)
+    }
+
+    fn allocate(
+        width: usize,
+        height: usize,
+        format: TextureFormat,
+        data: *const c_void,
+        unit: u32,
+    ) -> Result<Self, TextureError> {
+        check_size(width, height, gl::MAX_TEXTURE_SIZE)?;
+
+        let mut instance = Self {
+            renderer_id: 0,
+            format,
+            width,
+            height,
         };
+        let alignment = unpack_alignment(width * format.bytes_per_pixel());
 
         gl_call!({
             gl::GenTextures(1, &mut instance.renderer_id);
-            gl::ActiveTexture(gl::TEXTURE0 + index);
-            gl::BindTexture(gl::TEXTURE_2D, instance.renderer_id);
-
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-
+        });
+        instance.bind_unit(unit);
+        gl_call!({
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                gl::RGBA8 as i32,
-                instance.image.width() as i32,
-                instance.image.height() as i32,
+                format.internal_format() as i32,
+                width as i32,
+                height as i32,
                 0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                instance.image.ptr(),
+                format.pixel_format(),
+                format.data_type(),
+                data,
             );
-            instance.unbind();
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
         });
 
-        instance
+        instance.unbind();
+        instance.set_spec(&TextureSpec::default());
+
+        Ok(instance)
     }
 
-    pub fn width(&self) -> usize {
-        self.image.width()
+    ///
+    /// Creates an empty depth texture, e.g. to render a shadow map into
+    ///
+    /// Sampling outside the texture returns the far plane so everything outside a shadow
+    /// map is lit.
+    ///
+    pub fn depth(width: usize, height: usize) -> Self {
+        let instance =
+            Self::empty(width, height, TextureFormat::Depth24).unwrap_or_else(|e| panic!("{}", e));
+        instance.set_spec(
+            &TextureSpec::default()
+                .with_filter(TextureFilter::Nearest)
+                .with_wrap(WrapMode::ClampToBorder)
+                .with_border_color([1.0, 1.0, 1.0, 1.0]),
+        );
+
+        instance
     }
-    pub fn height(&self) -> usize {
-        self.image.height()
+
+    ///
+    /// Replaces a region of the base level with tightly packed pixels in the texture's format
+    ///
+    /// Rows are uploaded in order starting at row `y`, without flipping. Mipmaps are not
+    /// updated, call `generate_mipmaps` afterwards if the texture uses them.
+    ///
+    pub fn update_region(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        pixels: &[u8],
+    ) -> Result<(), TextureError> {
+        let in_bounds = x
+            .checked_add(width)
+            .is_some_and(|right| right <= self.width)
+            && y.checked_add(height).is_some_and(|top| top <= self.height);
+        if !in_bounds {
+            return Err(TextureError::RegionOutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            });
+        }
+        let row_size = width * self.format.bytes_per_pixel();
+        let expected = row_size * height;
+        if pixels.len() != expected {
+            return Err(TextureError::DataSize {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+
+        gl_call!({
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, unpack_alignment(row_size));
+            gl::TextureSubImage2D(
+                self.renderer_id,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                self.format.pixel_format(),
+                self.format.data_type(),
+                pixels.as_ptr() as *const c_void,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        });
+        Ok(())
     }
 
-    pub fn bind(&self, slot: u32) {
+    ///
+    /// Reallocates the texture at a new size, discarding its contents while keeping the format
+    /// and sampling parameters
+    ///
+    /// If the texture filters between mipmaps the whole chain is reallocated, as levels keeping
+    /// the old size would leave it incomplete. Call `generate_mipmaps` again once the base
+    /// level has new contents.
+    ///
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), TextureError> {
+        check_size(width, height, gl::MAX_TEXTURE_SIZE)?;
+
         gl_call!({
-            gl::ActiveTexture(gl::TEXTURE0 + slot);
             gl::BindTexture(gl::TEXTURE_2D, self.renderer_id);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                self.format.internal_format() as i32,
+                width as i32,
+                height as i32,
+                0,
+                self.format.pixel_format(),
+                self.format.data_type(),
+                std::ptr::null(),
+            );
         });
-    }
+        self.unbind();
+        self.width = width;
+        self.height = height;
 
-    pub fn renderer_id(&self) -> u32 {
-        self.renderer_id
+        let mut min_filter = 0;
+        unsafe {
+            gl::GetTextureParameteriv(self.renderer_id, gl::TEXTURE_MIN_FILTER, &mut min_filter);
+        }
+        let mipmapped = [
+            gl::NEAREST_MIPMAP_NEAREST,
+            gl::LINEAR_MIPMAP_NEAREST,
+            gl::NEAREST_MIPMAP_LINEAR,
+            gl::LINEAR_MIPMAP_LINEAR,
+        ]
+        .contains(&(min_filter as u32));
+        if mipmapped {
+            self.generate_mipmaps();
+        }
+        Ok(())
     }
 
-    pub fn unbind(&self) {
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, 0);
+    /// Reads back the base level, flipped so the first row is the top of the image. Float
+    /// values are clamped to 0-1 and depth is returned as grayscale
+    pub fn read_pixels(&self) -> RgbaImage {
+        let mut pixels = vec![0u8; self.width * self.height * 4];
+        if self.format == TextureFormat::Depth24 {
+            let mut depth = vec![0f32; self.width * self.height];
+            gl_call!({
+                gl::GetTextureImage(
+                    self.renderer_id,
+                    0,
+                    gl::DEPTH_COMPONENT,
+                    gl::FLOAT,
+                    (depth.len() * std::mem::size_of::<f32>()) as i32,
+                    depth.as_mut_ptr() as *mut c_void,
+                );
+            });
+            for (pixel, depth) in pixels.chunks_exact_mut(4).zip(depth) {
+                let value = (depth.clamp(0.0, 1.0) * 255.0) as u8;
+                pixel.copy_from_slice(&[value, value, value, 255]);
+            }
+        } else {
+            gl_call!({
+                gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+                gl::GetTextureImage(
+                    self.renderer_id,
+                    0,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    pixels.len() as i32,
+                    pixels.as_mut_ptr() as *mut c_void,
+                );
+            });
         }
+
+        let image = RgbaImage::from_raw(self.width as u32, self.height as u32, pixels)
+            .expect("Texture readback size does not match its dimensions");
+        image::imageops::flip_vertical(&image)
+    }
+
+    /// Writes the contents to a PNG file, e.g. to inspect an atlas or render target
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), image::ImageError> {
+        self.read_pixels()
+            .save_with_format(path, image::ImageFormat::Png)
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
     }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// Fails for empty textures or ones larger than the `max_parameter` limit, e.g. `MAX_TEXTURE_SIZE`
+pub(crate) fn check_size(
+    width: usize,
+    height: usize,
+    max_parameter: u32,
+) -> Result<(), TextureError> {
+    let mut max_size = 0;
+    gl_call!({
+        gl::GetIntegerv(max_parameter, &mut max_size);
+    });
+    if width == 0 || height == 0 || width.max(height) > max_size as usize {
+        return Err(TextureError::InvalidSize { width, height });
+    }
+    Ok(())
+}
+
+/// Grayscale images sample as gray instead of red, with the second channel as alpha
+pub(crate) fn set_grayscale_swizzle(renderer_id: u32, format: TextureFormat) {
+    let swizzle = match format.channels() {
+        1 if format != TextureFormat::Depth24 => [gl::RED, gl::RED, gl::RED, gl::ONE],
+        2 => [gl::RED, gl::RED, gl::RED, gl::GREEN],
+        _ => return,
+    };
+    let swizzle = swizzle.map(|channel| channel as i32);
+    gl_call!({
+        gl::TextureParameteriv(renderer_id, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr());
+    });
+}
+
+/// Largest row alignment dividing `row_size`, the default of 4 skews RGB and single channel
+/// data whose rows are not a multiple of 4 bytes
+pub(crate) fn unpack_alignment(row_size: usize) -> i32 {
+    [8, 4, 2, 1]
+        .into_iter()
+        .find(|&alignment| row_size.is_multiple_of(alignment))
+        .unwrap_or(1) as i32
 }
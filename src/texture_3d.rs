@@ -0,0 +1,202 @@
+use std::ffi::c_void;
+
+use glcall_macro::gl_call;
+
+use crate::{
+    gl_component::GLComponent,
+    sampler::TextureSpec,
+    texture::{check_size, unpack_alignment, TextureError, TextureFormat, TextureObject},
+};
+
+///
+/// A volume of texels sampled with three coordinates, e.g. density fields or color grading
+/// lookup tables
+///
+/// Data is laid out slice by slice, each slice row by row.
+///
+pub struct Texture3D {
+    renderer_id: u32,
+    format: TextureFormat,
+    width: usize,
+    height: usize,
+    depth: usize,
+}
+
+impl GLComponent for Texture3D {
+    fn renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+
+    fn bind(&self) {
+        gl_call!({
+            gl::BindTexture(gl::TEXTURE_3D, self.renderer_id);
+        });
+    }
+
+    fn unbind(&self) {
+        gl_call!({
+            gl::BindTexture(gl::TEXTURE_3D, 0);
+        });
+    }
+}
+
+impl TextureObject for Texture3D {
+    fn target(&self) -> u32 {
+        gl::TEXTURE_3D
+    }
+}
+
+impl Drop for Texture3D {
+    fn drop(&mut self) {
+        gl_call!({
+            gl::DeleteTextures(1, &self.renderer_id);
+        });
+    }
+}
+
+impl Texture3D {
+    /// Allocates a volume with undefined contents
+    pub fn empty(
+        width: usize,
+        height: usize,
+        depth: usize,
+        format: TextureFormat,
+    ) -> Result<Self, TextureError> {
+        Self::allocate(width, height, depth, format, std::ptr::null())
+    }
+
+    /// Uploads tightly packed texels in `format`
+    pub fn from_data(
+        width: usize,
+        height: usize,
+        depth: usize,
+        format: TextureFormat,
+        data: &[u8],
+    ) -> Result<Self, TextureError> {
+        let expected = width * height * depth * format.bytes_per_pixel();
+        if data.len() != expected {
+            return Err(TextureError::DataSize {
+                expected,
+                actual: data.len(),
+            });
+        }
+        Self::allocate(width, height, depth, format, data.as_ptr() as *const c_void)
+    }
+
+    /// A color grading lookup table that maps every color to itself, a starting point to
+    /// bake grades into
+    pub fn identity_lut(size: usize) -> Result<Self, TextureError> {
+        let max = (size.max(2) - 1) as f32;
+        let mut data = Vec::with_capacity(size * size * size * 3);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.extend(
+                        [r, g, b].map(|channel| (channel as f32 / max * 255.0).round() as u8),
+                    );
+                }
+            }
+        }
+        Self::from_data(size, size, size, TextureFormat::Rgb8, &data)
+    }
+
+    fn allocate(
+        width: usize,
+        height: usize,
+        depth: usize,
+        format: TextureFormat,
+        data: *const c_void,
+    ) -> Result<Self, TextureError> {
+        check_size(width, height, gl::MAX_3D_TEXTURE_SIZE)?;
+        let mut max_size = 0;
+        gl_call!({
+            gl::GetIntegerv(gl::MAX_3D_TEXTURE_SIZE, &mut max_size);
+        });
+        if depth == 0 || depth > max_size as usize {
+            return Err(TextureError::InvalidDepth { depth });
+        }
+
+        let mut instance = Self {
+            renderer_id: 0,
+            format,
+            width,
+            height,
+            depth,
+        };
+        let alignment = unpack_alignment(width * format.bytes_per_pixel());
+
+        gl_call!({
+            gl::GenTextures(1, &mut instance.renderer_id);
+        });
+        instance.bind();
+        gl_call!({
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
+            gl::TexImage3D(
+                gl::TEXTURE_3D,
+                0,
+                format.internal_format() as i32,
+                width as i32,
+                height as i32,
+                depth as i32,
+                0,
+                format.pixel_format(),
+                format.data_type(),
+                data,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        });
+        instance.unbind();
+        instance.set_spec(&TextureSpec::default());
+
+        Ok(instance)
+    }
+
+    /// Replaces the whole volume with tightly packed texels in the texture's format
+    pub fn update(&self, data: &[u8]) -> Result<(), TextureError> {
+        let expected = self.width * self.height * self.depth * self.format.bytes_per_pixel();
+        if data.len() != expected {
+            return Err(TextureError::DataSize {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        gl_call!({
+            gl::PixelStorei(
+                gl::UNPACK_ALIGNMENT,
+                unpack_alignment(self.width * self.format.bytes_per_pixel()),
+            );
+            gl::TextureSubImage3D(
+                self.renderer_id,
+                0,
+                0,
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                self.depth as i32,
+                self.format.pixel_format(),
+                self.format.data_type(),
+                data.as_ptr() as *const c_void,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        });
+        Ok(())
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
@@ -0,0 +1,205 @@
+use std::ffi::c_void;
+
+use glcall_macro::gl_call;
+
+use crate::{
+    gl_component::GLComponent,
+    sampler::TextureSpec,
+    texture::{
+        check_size, unpack_alignment, TextureError, TextureFormat, TextureImage, TextureObject,
+        TextureOptions,
+    },
+};
+
+///
+/// Layers of equally sized images behind a single `sampler2DArray`
+///
+/// Unlike arrays of samplers, the layer is a regular texture coordinate so it can vary
+/// per vertex, which lets a batch draw from many images without rebinding units.
+///
+pub struct Texture2DArray {
+    renderer_id: u32,
+    format: TextureFormat,
+    width: usize,
+    height: usize,
+    layers: usize,
+}
+
+impl GLComponent for Texture2DArray {
+    fn renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+
+    fn bind(&self) {
+        gl_call!({
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.renderer_id);
+        });
+    }
+
+    fn unbind(&self) {
+        gl_call!({
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        });
+    }
+}
+
+impl TextureObject for Texture2DArray {
+    fn target(&self) -> u32 {
+        gl::TEXTURE_2D_ARRAY
+    }
+}
+
+impl Drop for Texture2DArray {
+    fn drop(&mut self) {
+        gl_call!({
+            gl::DeleteTextures(1, &self.renderer_id);
+        });
+    }
+}
+
+impl Texture2DArray {
+    /// Allocates layers with undefined contents, to be filled with `update_layer`
+    pub fn empty(
+        width: usize,
+        height: usize,
+        layers: usize,
+        format: TextureFormat,
+    ) -> Result<Self, TextureError> {
+        Self::allocate(width, height, layers, format)
+    }
+
+    /// Loads one layer per image file, see `from_images`
+    pub fn load(paths: &[impl AsRef<str>], options: &TextureOptions) -> Result<Self, TextureError> {
+        let images = paths
+            .iter()
+            .map(|path| TextureImage::load(path.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_images(images, options)
+    }
+
+    /// Uploads one layer per image, all of the size and format of the first one
+    pub fn from_images(
+        mut images: Vec<TextureImage>,
+        options: &TextureOptions,
+    ) -> Result<Self, TextureError> {
+        let first = images
+            .first_mut()
+            .ok_or(TextureError::InvalidDepth { depth: 0 })?;
+        let format = options.prepare(first)?;
+        let (width, height) = (first.width(), first.height());
+        for (layer, image) in images.iter_mut().enumerate().skip(1) {
+            if image.width() != width || image.height() != height {
+                return Err(TextureError::LayerMismatch { layer });
+            }
+            options.with_format(format).prepare(image)?;
+        }
+
+        let instance = Self::allocate(width, height, images.len(), format)?;
+        for (layer, image) in images.iter().enumerate() {
+            instance.upload_layer(layer, image.ptr());
+        }
+        instance.set_spec(&options.spec);
+        Ok(instance)
+    }
+
+    fn allocate(
+        width: usize,
+        height: usize,
+        layers: usize,
+        format: TextureFormat,
+    ) -> Result<Self, TextureError> {
+        check_size(width, height, gl::MAX_TEXTURE_SIZE)?;
+        let mut max_layers = 0;
+        gl_call!({
+            gl::GetIntegerv(gl::MAX_ARRAY_TEXTURE_LAYERS, &mut max_layers);
+        });
+        if layers == 0 || layers > max_layers as usize {
+            return Err(TextureError::InvalidDepth { depth: layers });
+        }
+
+        let mut instance = Self {
+            renderer_id: 0,
+            format,
+            width,
+            height,
+            layers,
+        };
+
+        gl_call!({
+            gl::GenTextures(1, &mut instance.renderer_id);
+        });
+        instance.bind();
+        gl_call!({
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                format.internal_format() as i32,
+                width as i32,
+                height as i32,
+                layers as i32,
+                0,
+                format.pixel_format(),
+                format.data_type(),
+                std::ptr::null(),
+            );
+        });
+        instance.unbind();
+        instance.set_spec(&TextureSpec::default());
+
+        Ok(instance)
+    }
+
+    /// Replaces a whole layer with tightly packed pixels in the array's format, without flipping
+    pub fn update_layer(&self, layer: usize, pixels: &[u8]) -> Result<(), TextureError> {
+        if layer >= self.layers {
+            return Err(TextureError::InvalidDepth { depth: layer });
+        }
+        let expected = self.width * self.height * self.format.bytes_per_pixel();
+        if pixels.len() != expected {
+            return Err(TextureError::DataSize {
+                expected,
+                actual: pixels.len(),
+            });
+        }
+
+        self.upload_layer(layer, pixels.as_ptr() as *const c_void);
+        Ok(())
+    }
+
+    fn upload_layer(&self, layer: usize, data: *const c_void) {
+        let alignment = unpack_alignment(self.width * self.format.bytes_per_pixel());
+        gl_call!({
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
+            gl::TextureSubImage3D(
+                self.renderer_id,
+                0,
+                0,
+                0,
+                layer as i32,
+                self.width as i32,
+                self.height as i32,
+                1,
+                self.format.pixel_format(),
+                self.format.data_type(),
+                data,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        });
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn layers(&self) -> usize {
+        self.layers
+    }
+}
@@ -0,0 +1,332 @@
+use std::rc::Rc;
+
+use glcall_macro::gl_call;
+use nalgebra_glm as glm;
+
+use crate::{
+    framebuffer::FramebufferIncompleteError,
+    gl_component::GLComponent,
+    material::Material,
+    mesh::{GpuMesh, Mesh},
+    renderer::Renderer,
+    sampler::{TextureFilter, TextureSpec},
+    texture::{
+        check_size, unpack_alignment, Texture, TextureError, TextureFormat, TextureImage,
+        TextureObject, TextureOptions,
+    },
+    ShaderBuilder,
+};
+
+///
+/// Six square faces sampled by direction, e.g. skyboxes and environment maps
+///
+/// Faces are ordered +X, -X, +Y, -Y, +Z, -Z as in `TEXTURE_CUBE_MAP_POSITIVE_X + i`.
+///
+pub struct TextureCube {
+    renderer_id: u32,
+    format: TextureFormat,
+    size: usize,
+}
+
+impl GLComponent for TextureCube {
+    fn renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+
+    fn bind(&self) {
+        gl_call!({
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.renderer_id);
+        });
+    }
+
+    fn unbind(&self) {
+        gl_call!({
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        });
+    }
+}
+
+impl TextureObject for TextureCube {
+    fn target(&self) -> u32 {
+        gl::TEXTURE_CUBE_MAP
+    }
+}
+
+impl Drop for TextureCube {
+    fn drop(&mut self) {
+        gl_call!({
+            gl::DeleteTextures(1, &self.renderer_id);
+        });
+    }
+}
+
+impl TextureCube {
+    /// Allocates faces with undefined contents, e.g. to render into
+    pub fn empty(size: usize, format: TextureFormat) -> Result<Self, TextureError> {
+        Self::allocate(size, format, [std::ptr::null(); 6])
+    }
+
+    /// Loads six image files, see `from_face_images`
+    pub fn from_faces(
+        paths: [impl Into<String>; 6],
+        options: &TextureOptions,
+    ) -> Result<Self, TextureError> {
+        let [px, nx, py, ny, pz, nz] = paths.map(TextureImage::load);
+        Self::from_face_images([px?, nx?, py?, ny?, pz?, nz?], options)
+    }
+
+    ///
+    /// Uploads six square faces of the same size and format
+    ///
+    /// Faces are never flipped, whatever the options say: cube maps expect the first row of
+    /// each face at the top, the way skybox images are stored.
+    ///
+    pub fn from_face_images(
+        mut faces: [TextureImage; 6],
+        options: &TextureOptions,
+    ) -> Result<Self, TextureError> {
+        let options = options.with_flip(false);
+        let format = options.prepare(&mut faces[0])?;
+        let size = faces[0].width();
+        for (layer, face) in faces.iter_mut().enumerate().skip(1) {
+            if face.width() != size || face.height() != size {
+                return Err(TextureError::LayerMismatch { layer });
+            }
+            options.with_format(format).prepare(face)?;
+        }
+
+        let instance = Self::allocate(size, format, faces.each_ref().map(|face| face.ptr()))?;
+        instance.set_spec(&options.spec);
+        Ok(instance)
+    }
+
+    /// Projects an equirectangular (latitude-longitude) texture onto a mipmapped RGB16F cube
+    pub fn from_equirectangular(
+        renderer: &Renderer,
+        equirectangular: &Rc<Texture>,
+        size: usize,
+    ) -> Result<Self, TextureError> {
+        let shader = ShaderBuilder::default()
+            .with_shader_source(include_str!("res/shaders/EquirectangularToCube.glsl").into())
+            .expect("Failed to build shader from source")
+            .build();
+        let material =
+            Material::new(shader).with_texture("u_EquirectangularMap", equirectangular.clone());
+        Self::from_material(renderer, size, &material)
+    }
+
+    ///
+    /// Renders a material on the inside of a cube into each face of a mipmapped RGB16F cube
+    ///
+    /// The material's shader gets `u_Projection` and `u_View` for each face and should
+    /// output the color in the direction of the cube's local vertex position.
+    ///
+    pub fn from_material(
+        renderer: &Renderer,
+        size: usize,
+        material: &Material,
+    ) -> Result<Self, TextureError> {
+        let cube = Self::empty(size, TextureFormat::Rgb16F)?
+            .with_spec(TextureSpec::default().with_mipmaps(TextureFilter::Linear));
+
+        let capture = CubeCapture::new(renderer);
+        capture.render(renderer, &cube, 0, material)?;
+        drop(capture);
+
+        cube.generate_mipmaps();
+        Ok(cube)
+    }
+
+    fn allocate(
+        size: usize,
+        format: TextureFormat,
+        faces: [*const std::ffi::c_void; 6],
+    ) -> Result<Self, TextureError> {
+        check_size(size, size, gl::MAX_CUBE_MAP_TEXTURE_SIZE)?;
+
+        let mut instance = Self {
+            renderer_id: 0,
+            format,
+            size,
+        };
+        let alignment = unpack_alignment(size * format.bytes_per_pixel());
+
+        gl_call!({
+            gl::GenTextures(1, &mut instance.renderer_id);
+        });
+        instance.bind();
+        gl_call!({
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
+        });
+        for (face, data) in faces.into_iter().enumerate() {
+            gl_call!({
+                gl::TexImage2D(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                    0,
+                    format.internal_format() as i32,
+                    size as i32,
+                    size as i32,
+                    0,
+                    format.pixel_format(),
+                    format.data_type(),
+                    data,
+                );
+            });
+        }
+        gl_call!({
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        });
+        instance.unbind();
+        instance.set_spec(&TextureSpec::default());
+
+        Ok(instance)
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Width and height of each face
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+///
+/// Renders into the faces of cube maps through an offscreen framebuffer
+///
+/// Creating a capture disables depth testing and face culling and enables seamless cube
+/// map filtering. Dropping it binds the screen framebuffer and restores the viewport.
+///
+pub(crate) struct CubeCapture {
+    framebuffer: u32,
+    renderbuffer: u32,
+    cube: GpuMesh,
+    views: [glm::Mat4; 6],
+    projection: glm::Mat4,
+    viewport: [i32; 4],
+}
+
+impl Drop for CubeCapture {
+    fn drop(&mut self) {
+        let [x, y, width, height] = self.viewport;
+        gl_call!({
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::DeleteRenderbuffers(1, &self.renderbuffer);
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::Viewport(x, y, width, height);
+        });
+    }
+}
+
+impl CubeCapture {
+    pub(crate) fn new(renderer: &Renderer) -> Self {
+        let origin = glm::vec3(0.0, 0.0, 0.0);
+        let look = |direction: [f32; 3], up: [f32; 3]| {
+            glm::look_at(&origin, &glm::Vec3::from(direction), &glm::Vec3::from(up))
+        };
+
+        // Same face order as TEXTURE_CUBE_MAP_POSITIVE_X + i
+        let views = [
+            look([1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+            look([-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+            look([0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+            look([0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+            look([0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+            look([0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+        ];
+
+        let mut instance = Self {
+            framebuffer: 0,
+            renderbuffer: 0,
+            cube: Mesh::cube(2.0).upload(),
+            views,
+            projection: glm::perspective(1.0, 90f32.to_radians(), 0.1, 10.0),
+            viewport: [0; 4],
+        };
+        gl_call!({
+            gl::GetIntegerv(gl::VIEWPORT, instance.viewport.as_mut_ptr());
+            gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+            gl::GenFramebuffers(1, &mut instance.framebuffer);
+            gl::GenRenderbuffers(1, &mut instance.renderbuffer);
+        });
+        renderer.set_depth_test(false);
+        renderer.set_face_culling(None);
+        instance
+    }
+
+    /// Binds the capture framebuffer with a depth buffer and viewport of `size`
+    pub(crate) fn begin(&self, size: usize) {
+        gl_call!({
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, self.renderbuffer);
+            gl::RenderbufferStorage(
+                gl::RENDERBUFFER,
+                gl::DEPTH_COMPONENT24,
+                size as i32,
+                size as i32,
+            );
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::RENDERBUFFER,
+                self.renderbuffer,
+            );
+            gl::Viewport(0, 0, size as i32, size as i32);
+        });
+    }
+
+    pub(crate) fn attach(
+        &self,
+        target: u32,
+        texture: u32,
+        mip: u32,
+    ) -> Result<(), FramebufferIncompleteError> {
+        gl_call!({
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                target,
+                texture,
+                mip as i32,
+            );
+        });
+        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(FramebufferIncompleteError { status });
+        }
+        Ok(())
+    }
+
+    /// Renders `material` into every face of mip level `mip`
+    pub(crate) fn render(
+        &self,
+        renderer: &Renderer,
+        cube_map: &TextureCube,
+        mip: u32,
+        material: &Material,
+    ) -> Result<(), FramebufferIncompleteError> {
+        self.begin((cube_map.size() >> mip).max(1));
+
+        let material = Rc::new(
+            material
+                .clone()
+                .with_uniform("u_Projection", self.projection),
+        );
+        for (face, view) in self.views.iter().enumerate() {
+            self.attach(
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                cube_map.renderer_id(),
+                mip,
+            )?;
+            renderer.clear();
+            let material = Material::instance(&material).with_uniform("u_View", *view);
+            self.cube.draw(renderer, &material);
+        }
+
+        gl_call!({
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        });
+        Ok(())
+    }
+}
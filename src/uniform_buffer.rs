@@ -0,0 +1,101 @@
+use crate::gl_component::GLComponent;
+use glcall_macro::gl_call;
+use std::ffi::c_void;
+
+///
+/// A uniform buffer object attached to a binding point, shared between every shader whose
+/// uniform block is bound to the same point with `Shader::bind_uniform_block`
+///
+pub struct UniformBuffer {
+    renderer_id: u32,
+    size: usize,
+    binding: u32,
+}
+
+impl GLComponent for UniformBuffer {
+    fn renderer_id(&self) -> u32 {
+        self.renderer_id
+    }
+
+    fn bind(&self) {
+        gl_call!({
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.renderer_id);
+        });
+    }
+
+    fn unbind(&self) {
+        gl_call!({
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        });
+    }
+}
+
+impl Drop for UniformBuffer {
+    fn drop(&mut self) {
+        gl_call!({
+            gl::DeleteBuffers(1, &self.renderer_id);
+        });
+    }
+}
+
+impl UniformBuffer {
+    /// Allocates `size` bytes and attaches the buffer to `binding`
+    pub fn new(size: usize, binding: u32) -> Self {
+        let mut renderer_id = 0;
+        gl_call!({
+            gl::GenBuffers(1, &mut renderer_id);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, renderer_id);
+            gl::BufferData(
+                gl::UNIFORM_BUFFER,
+                size as isize,
+                std::ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, binding, renderer_id);
+        });
+        Self {
+            renderer_id,
+            size,
+            binding,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn binding(&self) -> u32 {
+        self.binding
+    }
+
+    /// Uploads `data` starting `offset` bytes into the buffer
+    pub fn set_data<T: Copy>(&self, offset: usize, data: &[T]) {
+        let bytes = std::mem::size_of_val(data);
+        assert!(
+            offset + bytes <= self.size,
+            "Uniform buffer write of {} bytes at offset {} overflows its {} bytes",
+            bytes,
+            offset,
+            self.size
+        );
+        gl_call!({
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.renderer_id);
+            gl::BufferSubData(
+                gl::UNIFORM_BUFFER,
+                offset as isize,
+                bytes as isize,
+                data.as_ptr() as *const c_void,
+            );
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        });
+    }
+
+    /// Attaches the buffer to another binding point
+    pub fn bind_base(&mut self, binding: u32) {
+        self.binding = binding;
+        gl_call!({
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, binding, self.renderer_id);
+        });
+    }
+}